@@ -1,9 +1,13 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State, CELL_WIDTH,
-    COLORS, COLUMNS, FIELD_COLOR, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State, CELL_WIDTH, COLORS, FIELD_COLOR, LINE_WIDTH, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(From)]
@@ -15,11 +19,12 @@ pub struct Exports {
     remaining: usize,
     speedup: bool,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(variant: bool) -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
+    pub fn new(variant: bool, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
 
         Self {
             path: vec![],
@@ -29,17 +34,27 @@ impl Exports {
             remaining: 0,
             speedup: variant,
             state: State::Setup,
+            rng,
         }
     }
     pub fn filled(&self) -> f32 {
-        1.0 - ((self.remaining as f32) / (COLUMNS * ROWS))
+        1.0 - ((self.remaining as f32) / (self.grid.width() * self.grid.height()) as f32)
     }
     pub fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "aldousbroder",
+    name: "Aldous-Broder",
+    params: &[Param::Flag("slow", "fast")],
+    default_variant: "slow",
+    shape: Shape::Square,
+    new: |values, width, height, rng| Box::new(Exports::new(values.flag(0), width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         if self.speedup {
             String::from("Faster Aldous-Broderish")
@@ -48,7 +63,10 @@ impl Algorithm for Exports {
         }
     }
     fn re_init(&mut self, variant: String) {
-        self.from(Exports::new(variant == "fast"));
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(values.flag(0), width, height, rng));
     }
     fn get_variant(&self) -> String {
         if self.speedup {
@@ -58,10 +76,11 @@ impl Algorithm for Exports {
         }
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         if self.state == State::Setup {
-            self.curr = (gen_range(0, COLUMNS as usize), gen_range(0, ROWS as usize));
+            self.curr = (self.rng.gen_range(0, width), self.rng.gen_range(0, height));
             self.prev = self.curr;
-            self.remaining = (ROWS * COLUMNS) as usize - 1;
+            self.remaining = width * height - 1;
             self.state = State::Running;
             return;
         }
@@ -78,7 +97,7 @@ impl Algorithm for Exports {
 
             let (x, y) = self.curr;
             let mut potentials: Vec<Direction> = EnumSet::all().iter().collect();
-            potentials.shuffle();
+            potentials.shuffle(&mut self.rng);
             for direction in potentials {
                 let (new_x, new_y) = match direction {
                     Direction::North => (x as i32, y as i32 - 1),
@@ -86,10 +105,11 @@ impl Algorithm for Exports {
                     Direction::South => (x as i32, y as i32 + 1),
                     Direction::West => (x as i32 - 1, y as i32),
                 };
-                if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32 {
+                if 0 <= new_x && new_x < width as i32 && 0 <= new_y && new_y < height as i32 {
                     // This isn't officially part of Aldous-Broder, but preventing the random walk
                     // from going back and forth a bunch seems to speed up the run by about 3x
-                    // (from 30 minutes to 10 minutes)…
+                    // (from 30 minutes to 10 minutes)… at the cost of some mazes being more
+                    // likely than others.
                     if self.speedup && (new_x as usize, new_y as usize) == self.prev {
                         continue;
                     }
@@ -108,15 +128,32 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         if self.state == State::Running {
             let curr_color = COLORS[1];
-            for x in 0..COLUMNS as usize {
-                for y in 0..ROWS as usize {
+            for x in 0..self.grid.width() {
+                for y in 0..self.grid.height() {
                     if self.grid[y][x] == EnumSet::new() {
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -126,7 +163,7 @@ impl Algorithm for Exports {
                     }
                 }
             }
-            draw_rectangle(
+            canvas.draw_rectangle(
                 self.curr.0 as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 self.curr.1 as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -135,21 +172,21 @@ impl Algorithm for Exports {
             );
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -0,0 +1,63 @@
+//! Timing how long the algorithms take to build their mazes, so that speedups (and slowdowns)
+//! can be measured instead of eyeballed.
+
+use std::{
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+use crate::{generator::State, util::Algorithm};
+
+pub struct Timings {
+    pub runs: usize,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p99: Duration,
+    /// How many calls to `update` it took to finish each maze, on average.
+    pub steps: f64,
+}
+
+/// Build `runs` mazes, using the run number as the seed so that every bench sees the same mazes,
+/// and time each of them from the first step to the last.
+pub fn measure(runs: usize, make: impl Fn(u64) -> Box<dyn Algorithm>) -> Timings {
+    let mut times = vec![];
+    let mut steps = 0;
+    for run in 0..runs {
+        let mut algorithm = make(run as u64);
+        let start = Instant::now();
+        while algorithm.get_state() != State::Done {
+            algorithm.update();
+            steps += 1;
+        }
+        times.push(start.elapsed());
+    }
+    times.sort();
+
+    let runs = times.len().max(1);
+    let percentile = |p: usize| times.get((runs * p).div_ceil(100) - 1).copied();
+    Timings {
+        runs: times.len(),
+        mean: times.iter().sum::<Duration>() / runs as u32,
+        median: percentile(50).unwrap_or_default(),
+        p99: percentile(99).unwrap_or_default(),
+        steps: steps as f64 / runs as f64,
+    }
+}
+
+impl Timings {
+    pub const HEADER: &'static str = "      Mean     Median        p99       Steps";
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "{:8.3}ms {:8.3}ms {:8.3}ms {:11.1}",
+            ms(self.mean),
+            ms(self.median),
+            ms(self.p99),
+            self.steps
+        )
+    }
+}
@@ -1,21 +1,35 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State, CELL_WIDTH,
-    COLORS, COLUMNS, FIELD_COLOR, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State, CELL_WIDTH, COLORS, FIELD_COLOR, OFFSET,
 };
 use derive_more::Display;
 use enumset::EnumSet;
 use itertools::Itertools;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
-#[derive(Display)]
-enum Bias {
+#[derive(Clone, Copy, Display)]
+pub enum Bias {
     NorthEast,
     SouthEast,
     SouthWest,
     NorthWest,
 }
 
+impl Bias {
+    pub const ALL: [Bias; 4] = [
+        Bias::NorthEast,
+        Bias::SouthEast,
+        Bias::SouthWest,
+        Bias::NorthWest,
+    ];
+    pub const NAMES: [&'static str; 4] = ["NorthEast", "SouthEast", "SouthWest", "NorthWest"];
+}
+
 #[derive(From)]
 pub struct Exports {
     path: Vec<(usize, usize)>,
@@ -24,31 +38,21 @@ pub struct Exports {
     random: bool,
     remaining: Vec<(usize, usize)>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(variant: String) -> Self {
-        let mut args = variant.splitn(2, ':');
-        let random = args.next().unwrap_or("random") == "random";
-        let bias = args.next().unwrap_or("NorthWest");
-
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        let mut remaining: Vec<(usize, usize)> = (0..ROWS as usize)
-            .cartesian_product(0..COLUMNS as usize)
+    pub fn new(random: bool, bias: Bias, width: usize, height: usize, mut rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
+        let mut remaining: Vec<(usize, usize)> = (0..height)
+            .cartesian_product(0..width)
             .map(|(y, x)| (x, y))
             .collect();
         if random {
-            remaining.shuffle();
+            remaining.shuffle(&mut rng);
         } else {
             remaining.reverse();
         }
-        let bias = match bias {
-            "NorthEast" => Bias::NorthEast,
-            "SouthEast" => Bias::SouthEast,
-            "SouthWest" => Bias::SouthWest,
-            "NorthWest" => Bias::NorthWest,
-            _ => panic!("Unknown bias {}", bias),
-        };
 
         Self {
             path: vec![],
@@ -57,6 +61,7 @@ impl Exports {
             random,
             remaining,
             state: State::Setup,
+            rng,
         }
     }
     fn carve(&mut self, cell: (usize, usize), direction: Direction) {
@@ -72,18 +77,48 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "bintree",
+    name: "Binary Tree",
+    params: &[
+        Param::Flag("ordered", "random"),
+        Param::Choice(&Bias::NAMES),
+    ],
+    default_variant: "random:NorthWest",
+    shape: Shape::Square,
+    new: |values, width, height, rng| {
+        Box::new(Exports::new(
+            values.flag(0),
+            Bias::ALL[values.choice(1)],
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Binary Tree")
     }
     fn re_init(&mut self, variant: String) {
-        self.from(Exports::new(variant));
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(
+            values.flag(0),
+            Bias::ALL[values.choice(1)],
+            width,
+            height,
+            rng,
+        ));
     }
     fn get_variant(&self) -> String {
         let rv = if self.random { "random" } else { "ordered" };
         format!("{}:{}", rv, self.bias)
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         if self.state == State::Setup {
             self.state = State::Running;
             return;
@@ -102,14 +137,14 @@ impl Algorithm for Exports {
             // log::info!("{:?}", curr);
             match self.bias {
                 Bias::NorthEast => {
-                    if curr.0 == COLUMNS as usize - 1 && curr.1 == 0 {
+                    if curr.0 == width - 1 && curr.1 == 0 {
                         return;
                     }
-                    let direction = if curr.0 == COLUMNS as usize - 1 {
+                    let direction = if curr.0 == width - 1 {
                         Direction::North
                     } else if curr.1 == 0 {
                         Direction::East
-                    } else if gen_range(0, 2) == 0 {
+                    } else if self.rng.gen_range(0, 2) == 0 {
                         Direction::North
                     } else {
                         Direction::East
@@ -117,14 +152,14 @@ impl Algorithm for Exports {
                     self.carve(curr, direction);
                 }
                 Bias::SouthEast => {
-                    if curr.0 == COLUMNS as usize - 1 && curr.1 == ROWS as usize - 1 {
+                    if curr.0 == width - 1 && curr.1 == height - 1 {
                         return;
                     }
-                    let direction = if curr.0 == COLUMNS as usize - 1 {
+                    let direction = if curr.0 == width - 1 {
                         Direction::South
-                    } else if curr.1 == ROWS as usize - 1 {
+                    } else if curr.1 == height - 1 {
                         Direction::East
-                    } else if gen_range(0, 2) == 0 {
+                    } else if self.rng.gen_range(0, 2) == 0 {
                         Direction::South
                     } else {
                         Direction::East
@@ -132,14 +167,14 @@ impl Algorithm for Exports {
                     self.carve(curr, direction);
                 }
                 Bias::SouthWest => {
-                    if curr.0 == 0 && curr.1 == ROWS as usize - 1 {
+                    if curr.0 == 0 && curr.1 == height - 1 {
                         return;
                     }
                     let direction = if curr.0 == 0 {
                         Direction::South
-                    } else if curr.1 == ROWS as usize - 1 {
+                    } else if curr.1 == height - 1 {
                         Direction::West
-                    } else if gen_range(0, 2) == 0 {
+                    } else if self.rng.gen_range(0, 2) == 0 {
                         Direction::South
                     } else {
                         Direction::West
@@ -154,7 +189,7 @@ impl Algorithm for Exports {
                         Direction::North
                     } else if curr.1 == 0 {
                         Direction::West
-                    } else if gen_range(0, 2) == 0 {
+                    } else if self.rng.gen_range(0, 2) == 0 {
                         Direction::North
                     } else {
                         Direction::West
@@ -167,16 +202,33 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         if self.state == State::Running {
             let mut curr_color = COLORS[1];
             curr_color.a = 0.3;
-            for x in 0..COLUMNS as usize {
-                for y in 0..ROWS as usize {
+            for x in 0..self.grid.width() {
+                for y in 0..self.grid.height() {
                     if self.grid[y][x] == EnumSet::new() {
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -184,7 +236,7 @@ impl Algorithm for Exports {
                             FIELD_COLOR,
                         );
                     } else if self.remaining.contains(&(x, y)) {
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -196,21 +248,21 @@ impl Algorithm for Exports {
             }
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -1,9 +1,12 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State as BaseState,
-    CELL_WIDTH, COLORS, COLUMNS, EMPTY_COLOR, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State as BaseState, CELL_WIDTH, COLORS, EMPTY_COLOR, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(PartialEq, Eq, Debug)]
@@ -26,24 +29,27 @@ enum Blob {
 #[derive(From)]
 pub struct Exports {
     path: Vec<(usize, usize)>,
-    finished: [[bool; COLUMNS as usize]; ROWS as usize],
+    finished: Board<bool>,
     grid: Grid,
-    stack: Vec<[[Blob; COLUMNS as usize]; ROWS as usize]>,
+    stack: Vec<Board<Blob>>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let mut grid = [[EnumSet::all(); COLUMNS as usize]; ROWS as usize];
-        for x in 0..COLUMNS as usize {
-            grid[0][x].remove(Direction::North);
-            grid[ROWS as usize - 1][x].remove(Direction::South);
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let mut grid = Board::new(width, height, EnumSet::all());
+        for cell in grid[0].iter_mut() {
+            cell.remove(Direction::North);
+        }
+        for cell in grid[height - 1].iter_mut() {
+            cell.remove(Direction::South);
         }
         for row in grid.iter_mut() {
             row[0].remove(Direction::West);
-            row[COLUMNS as usize - 1].remove(Direction::East);
+            row[width - 1].remove(Direction::East);
         }
-        let finished = [[false; COLUMNS as usize]; ROWS as usize];
+        let finished = Board::new(width, height, false);
 
         Self {
             path: vec![],
@@ -51,10 +57,11 @@ impl Exports {
             grid,
             stack: vec![],
             state: State::Setup,
+            rng,
         }
     }
 
-    fn choose_starts(board: &[[Blob; COLUMNS as usize]; ROWS as usize]) -> [(usize, usize); 2] {
+    fn choose_starts(board: &Board<Blob>, rng: &mut Rng) -> [(usize, usize); 2] {
         let mut potentials = vec![];
         for (y, row) in board.iter().enumerate() {
             for (x, cell) in row.iter().enumerate() {
@@ -64,24 +71,23 @@ impl Exports {
             }
         }
         let mut rv = [(0usize, 0usize), (0usize, 0usize)];
-        for (b, slot) in potentials.choose_multiple(rv.len()).zip(rv.iter_mut()) {
+        for (b, slot) in potentials.choose_multiple(rv.len(), rng).zip(rv.iter_mut()) {
             *slot = *b;
         }
         // log::info!("Chose {:?} from {:?}", rv, potentials);
         rv
     }
 
-    fn expand_blobs(
-        board: &[[Blob; COLUMNS as usize]; ROWS as usize],
-    ) -> ([[Blob; COLUMNS as usize]; ROWS as usize], usize) {
+    fn expand_blobs(board: &Board<Blob>, rng: &mut Rng) -> (Board<Blob>, usize) {
+        let (width, height) = (board.width() as i32, board.height() as i32);
         let mut remaining = 0;
-        let mut new_board = *board;
+        let mut new_board = board.clone();
         let mut moved = [false, false];
         for (y, row) in board.iter().enumerate() {
             for (x, cell) in row.iter().enumerate() {
                 if cell == &Blob::None {
                     let mut potentials: Vec<Direction> = EnumSet::all().iter().collect();
-                    potentials.shuffle();
+                    potentials.shuffle(rng);
                     for direction in potentials {
                         let (new_x, new_y) = match direction {
                             Direction::North => (x as i32, y as i32 - 1),
@@ -90,12 +96,11 @@ impl Exports {
                             Direction::West => (x as i32 - 1, y as i32),
                         };
                         // log::info!("{:?} / {:?} -> {:?}", (x,y), direction, (new_x, new_y));
-                        if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32
-                        {
+                        if 0 <= new_x && new_x < width && 0 <= new_y && new_y < height {
                             let (new_x, new_y) = (new_x as usize, new_y as usize);
                             if [Blob::First, Blob::Second].contains(&board[new_y][new_x]) {
                                 // Only expand half the time.
-                                if gen_range(0, 2) == 0 {
+                                if rng.gen_range(0, 2) == 0 {
                                     new_board[y][x] = board[new_y][new_x];
                                 }
 
@@ -129,20 +134,31 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "blobby",
+    name: "Blobby Recursive Subdivision",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Blobby Recursive Division")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         if self.state == State::Setup {
-            self.stack
-                .push([[Blob::None; COLUMNS as usize]; ROWS as usize]);
+            self.stack.push(Board::new(width, height, Blob::None));
             self.state = State::Choosing;
             return;
         }
@@ -157,14 +173,14 @@ impl Algorithm for Exports {
         match self.state {
             State::Choosing => {
                 let board = self.stack.last_mut().unwrap();
-                let [a, b] = Self::choose_starts(board);
+                let [a, b] = Self::choose_starts(board, &mut self.rng);
                 board[a.1][a.0] = Blob::First;
                 board[b.1][b.0] = Blob::Second;
                 self.state = State::Expanding;
             }
             State::Expanding => {
                 let board = self.stack.last_mut().unwrap();
-                let (new_board, remaining) = Self::expand_blobs(board);
+                let (new_board, remaining) = Self::expand_blobs(board, &mut self.rng);
                 *board = new_board;
 
                 // log::info!("Expanding: {} remaining…", remaining);
@@ -178,8 +194,8 @@ impl Algorithm for Exports {
                 let mut first_size = 0;
                 let mut second_size = 0;
                 let mut walls = vec![];
-                for x in 0..COLUMNS as usize {
-                    for y in 0..ROWS as usize {
+                for x in 0..width {
+                    for y in 0..height {
                         match board[y][x] {
                             Blob::First => first_size += 1,
                             Blob::Second => second_size += 1,
@@ -193,9 +209,9 @@ impl Algorithm for Exports {
                                 Direction::West => (x as i32 - 1, y as i32),
                             };
                             if 0 <= new_x
-                                && new_x < COLUMNS as i32
+                                && new_x < width as i32
                                 && 0 <= new_y
-                                && new_y < ROWS as i32
+                                && new_y < height as i32
                             {
                                 let (new_x, new_y) = (new_x as usize, new_y as usize);
                                 match (board[y][x], board[new_y][new_x]) {
@@ -214,7 +230,7 @@ impl Algorithm for Exports {
                     }
                 }
                 // Carve a door in the wall.
-                walls.shuffle();
+                walls.shuffle(&mut self.rng);
                 let (x, y, direction) = walls.pop().unwrap();
                 let (new_x, new_y) = match direction {
                     Direction::North => (x, y - 1),
@@ -241,7 +257,7 @@ impl Algorithm for Exports {
 
                 // If either of the blobs are big enough, add them to the stack.
                 if first_size > 3 {
-                    let mut new_board = board;
+                    let mut new_board = board.clone();
                     for (y, row) in board.iter().enumerate() {
                         for (x, cell) in row.iter().enumerate() {
                             new_board[y][x] = match cell {
@@ -253,7 +269,7 @@ impl Algorithm for Exports {
                     self.stack.push(new_board);
                 }
                 if second_size > 3 {
-                    let mut new_board = board;
+                    let mut new_board = board.clone();
                     for (y, row) in board.iter().enumerate() {
                         for (x, cell) in row.iter().enumerate() {
                             new_board[y][x] = match cell {
@@ -291,8 +307,29 @@ impl Algorithm for Exports {
         });
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         // if self.state != State::Done {
         let mut none_color = COLORS[1];
@@ -312,7 +349,7 @@ impl Algorithm for Exports {
                             Blob::Second => second_color,
                             Blob::Outside => EMPTY_COLOR,
                         };
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -324,25 +361,21 @@ impl Algorithm for Exports {
             }
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -0,0 +1,146 @@
+//! Something to draw on, whether that's the window or an image in memory.
+
+use macroquad::{
+    color::Color,
+    math::{vec2, Vec2},
+    prelude::ImageFormat,
+    shapes::{
+        draw_arc, draw_line, draw_poly, draw_poly_lines, draw_rectangle, draw_rectangle_lines,
+        draw_triangle,
+    },
+    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
+};
+
+use crate::util::WHITE;
+
+/// The drawing calls the algorithms use, named after (and with the same arguments as) macroquad's.
+pub trait Canvas {
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color);
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color);
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    );
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color);
+    fn draw_poly(&mut self, x: f32, y: f32, sides: u8, radius: f32, rotation: f32, color: Color);
+    #[allow(clippy::too_many_arguments)]
+    fn draw_poly_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    );
+    /// A thick band `arc` degrees around a circle, starting `rotation` degrees clockwise from the
+    /// right, with its inside edge `radius` from the middle.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        arc: f32,
+        color: Color,
+    );
+    /// Draw the little robot that walks through the finished mazes.
+    fn draw_robot(&mut self, x: f32, y: f32, w: f32, h: f32);
+}
+
+/// The corners of a regular polygon, the same way macroquad lays them out.
+pub fn poly_corners(x: f32, y: f32, sides: u8, radius: f32, rotation: f32) -> Vec<(f32, f32)> {
+    let rotation = rotation.to_radians();
+    (0..sides)
+        .map(|i| {
+            let angle = i as f32 / sides as f32 * std::f32::consts::TAU + rotation;
+            (x + radius * angle.cos(), y + radius * angle.sin())
+        })
+        .collect()
+}
+
+/// The macroquad window.
+pub struct Screen;
+
+impl Canvas for Screen {
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        draw_line(x1, y1, x2, y2, thickness, color);
+    }
+
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        draw_rectangle(x, y, w, h, color);
+    }
+
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        draw_rectangle_lines(x, y, w, h, thickness, color);
+    }
+
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color) {
+        draw_triangle(v1, v2, v3, color);
+    }
+
+    fn draw_poly(&mut self, x: f32, y: f32, sides: u8, radius: f32, rotation: f32, color: Color) {
+        draw_poly(x, y, sides, radius, rotation, color);
+    }
+
+    fn draw_poly_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        draw_poly_lines(x, y, sides, radius, rotation, thickness, color);
+    }
+
+    fn draw_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        arc: f32,
+        color: Color,
+    ) {
+        draw_arc(x, y, sides, radius, rotation, thickness, arc, color);
+    }
+
+    fn draw_robot(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let image = Texture2D::from_file_with_format(
+            include_bytes!("../static/little_guy.png"),
+            Some(ImageFormat::Png),
+        );
+        draw_texture_ex(
+            &image,
+            x,
+            y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(w, h)),
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Boards of triangles, pointing up and down in turn like a row of teeth.
+
+use enumset::EnumSet;
+use macroquad::{color::Color, math::vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::Canvas;
+use crate::generator::Maze;
+use crate::graph::{Cell, CellGraph};
+use crate::registry::Shape;
+use crate::util::{Board, Heading, Wall, COLORS, LINE_WIDTH, OFFSET};
+
+pub type Grid = Board<EnumSet<Direction>>;
+
+/// How long each side of a triangle is.
+pub const CELL_WIDTH: f32 = 28.0;
+pub const ROWS: usize = 24;
+pub const COLUMNS: usize = 56;
+
+/// How tall each row of triangles is.
+fn row_height() -> f32 {
+    CELL_WIDTH * f32::sqrt(3.0) / 2.0
+}
+
+/// The sides of a triangle. The ones pointing up have a south side and the ones pointing down
+/// have a north side, but nobody has both.
+#[derive(EnumSetType, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    /// The cell on the other side of this side of `(x, y)`, if it has this side and it isn't on
+    /// the edge of the board.
+    pub fn offset<T>(self, (x, y): Cell, board: &Board<T>) -> Option<Cell> {
+        match self {
+            Direction::North if !points_up((x, y)) && y > 0 => Some((x, y - 1)),
+            Direction::East if x + 1 < board.width() => Some((x + 1, y)),
+            Direction::South if points_up((x, y)) && y + 1 < board.height() => Some((x, y + 1)),
+            Direction::West if x > 0 => Some((x - 1, y)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the triangle at `(x, y)` has its point at the top. The top left one always does.
+pub fn points_up((x, y): Cell) -> bool {
+    (x + y) % 2 == 0
+}
+
+/// Which bands of parallel lines a cell sits between: the row, and then the two sets of slanted
+/// lines. Going through any side moves one band in exactly one of them.
+fn bands((x, y): Cell) -> (isize, isize, isize) {
+    let (x, y) = (x as isize, y as isize);
+    (y, (x + y).div_euclid(2), (x - y + 1).div_euclid(2))
+}
+
+/// How many steps apart two cells are, ignoring walls.
+pub fn distance(from: Cell, to: Cell) -> usize {
+    let (a, b) = (bands(from), bands(to));
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1) + a.2.abs_diff(b.2)
+}
+
+/// Close off every side that doesn't lead to another cell.
+pub fn set_border(grid: &mut Grid) {
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            for direction in grid[y][x] {
+                if direction.offset((x, y), grid).is_none() {
+                    grid[y][x].remove(direction);
+                }
+            }
+        }
+    }
+}
+
+/// The size in pixels of a triangle board, including the margins.
+pub fn board_size(width: usize, height: usize) -> (f32, f32) {
+    (
+        (width + 1) as f32 * CELL_WIDTH / 2.0 + OFFSET * 2.0,
+        height as f32 * row_height() + OFFSET * 2.0,
+    )
+}
+
+/// The corners of the triangle at `(x, y)`, starting at the point and going clockwise, pulled in
+/// by `inset` on every side.
+pub fn corners((x, y): Cell, inset: f32) -> [(f32, f32); 3] {
+    let left = x as f32 * CELL_WIDTH / 2.0 + OFFSET;
+    let (right, middle) = (left + CELL_WIDTH, left + CELL_WIDTH / 2.0);
+    let (top, bottom) = (
+        y as f32 * row_height() + OFFSET,
+        (y + 1) as f32 * row_height() + OFFSET,
+    );
+    let corners = if points_up((x, y)) {
+        [(middle, top), (right, bottom), (left, bottom)]
+    } else {
+        [(middle, bottom), (left, top), (right, top)]
+    };
+    // Shrinking towards the middle moves every side in by the same amount.
+    let (cx, cy) = center_pixel((x, y));
+    let scale = 1.0 - inset / (row_height() / 3.0);
+    corners.map(|(px, py)| (cx + (px - cx) * scale, cy + (py - cy) * scale))
+}
+
+/// The middle of the triangle, a third of the way up from its flat side.
+pub fn center_pixel((x, y): Cell) -> (f32, f32) {
+    let top = y as f32 * row_height() + OFFSET;
+    let middle = if points_up((x, y)) {
+        top + row_height() * 2.0 / 3.0
+    } else {
+        top + row_height() / 3.0
+    };
+    ((x + 1) as f32 * CELL_WIDTH / 2.0 + OFFSET, middle)
+}
+
+pub fn cell_from_pos<T>((x, y): (f32, f32), board: &Board<T>) -> Option<Cell> {
+    let (x, y) = (
+        (x - OFFSET) / (CELL_WIDTH / 2.0),
+        (y - OFFSET) / row_height(),
+    );
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+    // Find the bands the point is in, the same way `bands` does for a cell, and work back.
+    let p = ((x + y - 1.0) / 2.0).floor() as isize;
+    let q = ((x - y + 1.0) / 2.0).floor() as isize;
+    let (x, y) = (usize::try_from(p + q).ok()?, y as usize);
+    (x < board.width() && y < board.height()).then_some((x, y))
+}
+
+/// The walls of a triangle board, as line segments, leaving the entrance and exit open.
+pub fn walls(grid: &Grid) -> Vec<Wall> {
+    let (width, height) = (grid.width(), grid.height());
+    let mut walls = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            let cell = grid[y][x];
+            let [point, a, b] = corners((x, y), 0.0);
+            // Up or down, `a` and `b` are the ends of the flat side, but which one is east flips.
+            let (east, west) = if points_up((x, y)) { (a, b) } else { (b, a) };
+            let flat = if points_up((x, y)) {
+                Direction::South
+            } else {
+                Direction::North
+            };
+
+            //Figure out which lines to draw.
+            if !cell.contains(flat) {
+                walls.push((a, b));
+            }
+            if !cell.contains(Direction::East) && (x, y) != (width - 1, height - 1) {
+                walls.push((point, east));
+            }
+            if !cell.contains(Direction::West) && (x, y) != (0, 0) {
+                walls.push((point, west));
+            }
+        }
+    }
+    walls
+}
+
+pub fn draw_board(canvas: &mut dyn Canvas, grid: &Grid) {
+    for ((x1, y1), (x2, y2)) in walls(grid) {
+        canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH, COLORS[0]);
+    }
+}
+
+pub fn draw_cell(canvas: &mut dyn Canvas, cell: Cell, inset: f32, color: Color) {
+    let [a, b, c] = corners(cell, inset);
+    canvas.draw_triangle(vec2(a.0, a.1), vec2(b.0, b.1), vec2(c.0, c.1), color);
+}
+
+fn draw_little_robot(canvas: &mut dyn Canvas, cell: Cell, color: Color) {
+    let [a, b, c] = corners(cell, 2.0);
+    for ((x1, y1), (x2, y2)) in [(a, b), (b, c), (c, a)] {
+        canvas.draw_line(x1, y1, x2, y2, 4.0, color);
+    }
+
+    // Small enough to fit inside, even though the middle is nearer the flat side.
+    let (x, y) = center_pixel(cell);
+    let size = row_height() * 0.45;
+    canvas.draw_robot(x - size / 2.0, y - size / 2.0, size, size);
+}
+
+pub fn draw_path(canvas: &mut dyn Canvas, path: &[Cell]) {
+    let mut color = COLORS[10];
+    if let Some((&last, rest)) = path.split_last() {
+        color.a = 0.6;
+        draw_little_robot(canvas, last, color);
+        color.a = 0.3;
+        for &cell in rest {
+            draw_cell(canvas, cell, 0.0, color)
+        }
+    }
+}
+
+impl CellGraph for Grid {
+    const SHAPE: Shape = Shape::Delta;
+
+    fn empty(width: usize, height: usize) -> Self {
+        Board::new(width, height, EnumSet::new())
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .collect()
+    }
+
+    fn neighbours(&self, cell: Cell) -> Vec<Cell> {
+        EnumSet::<Direction>::all()
+            .iter()
+            .filter_map(|direction| direction.offset(cell, self))
+            .collect()
+    }
+
+    fn links(&self, (x, y): Cell) -> Vec<Cell> {
+        self[y][x]
+            .iter()
+            .filter_map(|direction| direction.offset((x, y), self))
+            .collect()
+    }
+
+    fn link(&mut self, a: Cell, b: Cell) {
+        let direction = towards(a, b);
+        self[a.1][a.0] |= direction;
+        self[b.1][b.0] |= direction.opposite();
+    }
+
+    fn unlink(&mut self, a: Cell, b: Cell) {
+        let direction = towards(a, b);
+        self[a.1][a.0] -= direction;
+        self[b.1][b.0] -= direction.opposite();
+    }
+
+    fn start(&self) -> Option<Cell> {
+        Some((0, 0))
+    }
+
+    fn goal(&self) -> Option<Cell> {
+        Some((self.width() - 1, self.height() - 1))
+    }
+
+    fn step(&self, (x, y): Cell, heading: Heading) -> Option<Cell> {
+        let Heading::Delta(direction) = heading else {
+            return None;
+        };
+        if !self[y][x].contains(direction) {
+            return None;
+        }
+        direction.offset((x, y), self)
+    }
+
+    fn maze(&self) -> Maze {
+        Maze::Delta(self.clone())
+    }
+
+    fn pixel_size(&self) -> (f32, f32) {
+        board_size(self.width(), self.height())
+    }
+
+    fn center(&self, cell: Cell) -> (f32, f32) {
+        center_pixel(cell)
+    }
+
+    fn cell_at(&self, pos: (f32, f32)) -> Option<Cell> {
+        cell_from_pos(pos, self)
+    }
+
+    fn walls(&self) -> Vec<Wall> {
+        walls(self)
+    }
+
+    fn draw_cell(&self, canvas: &mut dyn Canvas, cell: Cell, inset: f32, color: Color) {
+        draw_cell(canvas, cell, inset, color);
+    }
+
+    fn draw_current(&self, canvas: &mut dyn Canvas, cell: Cell, color: Color) {
+        draw_cell(canvas, cell, LINE_WIDTH * 1.5, color);
+    }
+
+    fn draw_path(&self, canvas: &mut dyn Canvas, path: &[Cell]) {
+        draw_path(canvas, path);
+    }
+}
+
+/// Which way `b` is from `a`, when they're next to each other.
+fn towards(a: Cell, b: Cell) -> Direction {
+    match (b.0 as isize - a.0 as isize, b.1 as isize - a.1 as isize) {
+        (0, -1) => Direction::North,
+        (1, 0) => Direction::East,
+        (0, 1) => Direction::South,
+        (-1, 0) => Direction::West,
+        delta => panic!(
+            "{:?} and {:?} aren't next to each other ({:?})",
+            a, b, delta
+        ),
+    }
+}
+
+#[test]
+fn finding_cells() {
+    let grid = Grid::empty(COLUMNS, ROWS);
+    for cell in grid.cells() {
+        assert_eq!(cell_from_pos(center_pixel(cell), &grid), Some(cell));
+        for next in grid.neighbours(cell) {
+            assert_eq!(distance(cell, next), 1);
+        }
+    }
+    assert_eq!(distance((0, 0), (0, 1)), 1);
+    // Straight down takes a zigzag, since only every other triangle has a way down.
+    assert_eq!(distance((0, 0), (0, 2)), 4);
+    assert_eq!(cell_from_pos((OFFSET + 1.0, OFFSET + 1.0), &grid), None);
+}
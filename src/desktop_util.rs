@@ -1,77 +1,450 @@
-use crate::util::Args;
-use clap::{command, Arg};
+use std::{fs::File, io::BufWriter};
+
+use clap::{
+    builder::{PossibleValue, PossibleValuesParser},
+    command,
+    error::ErrorKind,
+    parser::ValueSource,
+    value_parser, Arg, ArgAction, Command,
+};
+use mazes::{
+    bench::{self, Timings},
+    generator::Maze,
+    raster::{self, Style},
+    record::{self, Format},
+    registry::{self, ALGORITHMS},
+    saved::{self, SavedMaze},
+    solver,
+    stats::Stats,
+    svg, text,
+    util::{Algorithm, Args, Speed},
+};
+
+/// What the `bench` subcommand should time.
+pub struct Bench {
+    runs: usize,
+    sizes: Vec<(usize, usize)>,
+    algorithms: Vec<(String, String)>,
+}
 
 pub struct Desktop {
     algorithm: String,
     variant: String,
+    width: Option<usize>,
+    height: Option<usize>,
+    seed: Option<u64>,
+    speed: Speed,
+    svg: Option<String>,
+    png: Option<String>,
+    style: Style,
+    record: Option<String>,
+    every: usize,
+    text: Option<String>,
+    save: Option<String>,
+    load: Option<String>,
+    solver: Option<String>,
+    stats: Option<usize>,
+    bench: Option<Bench>,
 }
 
 impl Desktop {
     pub fn new() -> Self {
-        let matches = command!("\n")
+        let mut command = command!("\n")
             .arg(
                 Arg::new("algorithm")
                     .short('a')
                     .help("Which algorithm to run")
                     .long_help("Specify an algorithm to run.")
-                    .value_parser([
-                        "parallel",
-                        "eller",
-                        "kruskal",
-                        "prim",
-                        "recdiv",
-                        "blobby",
-                        "aldousbroder",
-                        "fastaldousbroder",
-                        "wilson",
-                        "slowwilson",
-                        "houston",
-                        "huntandkill",
-                        "growingtree",
-                        "growingbintree",
-                        "bintree",
-                        "sidewinder",
-                        "originshift",
-                        "hexparallel",
-                        "hexblobby",
-                        "penrose",
-                    ])
-                    .default_value("parallel"),
-            )
-            .arg(Arg::new("variant").short('v').default_value_ifs([
-                ("algorithm", "parallel", Some("6")),
-                ("algorithm", "aldousbroder", Some("slow")),
-                ("algorithm", "wilson", Some("fast")),
-                ("algorithm", "growingtree", Some("middle")),
-                ("algorithm", "bintree", Some("random:NorthWest")),
-                ("algorithm", "sidewinder", Some("hard")),
-                ("algorithm", "originshift", Some("1")),
-                ("algorithm", "hexparallel", Some("6")),
-                ("algorithm", "penrose", Some("king")),
-                // ("algorithm", None, Some("unused")),
-            ]))
-            .get_matches();
+                    .value_parser(PossibleValuesParser::new(
+                        ALGORITHMS
+                            .iter()
+                            .map(|entry| PossibleValue::new(entry.id).help(entry.name)),
+                    ))
+                    .default_value(ALGORITHMS[0].id),
+            )
+            .arg(
+                Arg::new("variant")
+                    .short('v')
+                    .help("Which variant of the algorithm to run")
+                    .default_value_ifs(
+                        (ALGORITHMS.iter())
+                            .filter(|entry| !entry.params.is_empty())
+                            .map(|entry| ("algorithm", entry.id, Some(entry.default_variant))),
+                    ),
+            )
+            .arg(
+                Arg::new("width")
+                    .long("width")
+                    .help("How many cells wide the maze should be")
+                    .value_parser(value_parser!(u64).range(2..)),
+            )
+            .arg(
+                Arg::new("height")
+                    .long("height")
+                    .help("How many cells high the maze should be")
+                    .value_parser(value_parser!(u64).range(2..)),
+            )
+            .arg(
+                Arg::new("seed")
+                    .long("seed")
+                    .help("Which seed to start from, to repeat an earlier maze")
+                    .value_parser(value_parser!(u64)),
+            )
+            .arg(
+                Arg::new("steps")
+                    .long("steps")
+                    .help("How many steps to take at a time while animating")
+                    .value_parser(value_parser!(u64).range(1..=Speed::MAX_STEPS as u64))
+                    .default_value("1"),
+            )
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .value_name("SECONDS")
+                    .help("How long to wait between each lot of steps while animating")
+                    .value_parser(|interval: &str| {
+                        interval
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|interval| (0.0..=Speed::MAX_INTERVAL).contains(interval))
+                            .ok_or(format!(
+                                "expected a number of seconds from 0 to {}",
+                                Speed::MAX_INTERVAL
+                            ))
+                    })
+                    .default_value("0.08"),
+            )
+            .arg(
+                Arg::new("svg")
+                    .long("svg")
+                    .value_name("FILE")
+                    .help("Write the finished maze to an SVG file, instead of opening a window"),
+            )
+            .arg(
+                Arg::new("png")
+                    .long("png")
+                    .value_name("FILE")
+                    .help("Write the finished maze to a PNG file, instead of opening a window"),
+            )
+            .arg(
+                Arg::new("solution")
+                    .long("solution")
+                    .action(ArgAction::SetTrue)
+                    .help("Shade in the solution in the PNG file"),
+            )
+            .arg(
+                Arg::new("palette")
+                    .long("palette")
+                    .action(ArgAction::SetTrue)
+                    .help("Use the window's colours in the PNG file, instead of black and white"),
+            )
+            .arg(
+                Arg::new("heatmap")
+                    .long("heatmap")
+                    .action(ArgAction::SetTrue)
+                    .help("Colour each cell by its distance from the start in the PNG file"),
+            )
+            .arg(
+                Arg::new("record")
+                    .long("record")
+                    .value_name("FILE")
+                    .help("Record the maze being built to an animated GIF or PNG file")
+                    .value_parser(|path: &str| {
+                        Format::from_path(path)
+                            .map(|_| path.to_owned())
+                            .ok_or("expected a .gif, .png, or .apng file")
+                    }),
+            )
+            .arg(
+                Arg::new("every")
+                    .long("every")
+                    .value_name("STEPS")
+                    .help("How many steps to take between each frame of the recording")
+                    .value_parser(value_parser!(u64).range(1..))
+                    .default_value("1"),
+            )
+            .arg(
+                Arg::new("text")
+                    .long("text")
+                    .help("Print the finished maze as text, instead of opening a window")
+                    .value_parser(["ascii", "unicode"]),
+            )
+            .arg(
+                Arg::new("save")
+                    .long("save")
+                    .value_name("FILE")
+                    .help("Save the finished maze to a JSON file, instead of opening a window"),
+            )
+            .arg(
+                Arg::new("load")
+                    .long("load")
+                    .value_name("FILE")
+                    .help("Load a maze saved with --save, instead of building a new one"),
+            )
+            .arg(
+                Arg::new("solve")
+                    .long("solve")
+                    .help("Which solver to run once the maze is done")
+                    .value_parser(solver::SOLVERS),
+            )
+            .arg(
+                Arg::new("stats")
+                    .long("stats")
+                    .value_name("RUNS")
+                    .help("Build this many mazes, and print their average statistics")
+                    .value_parser(value_parser!(u64).range(1..)),
+            )
+            .subcommand(
+                Command::new("bench")
+                    .about("Time how long each variant of each algorithm takes to build its mazes")
+                    .arg(
+                        Arg::new("runs")
+                            .long("runs")
+                            .help("How many mazes to build for each algorithm and size")
+                            .value_parser(value_parser!(u64).range(1..))
+                            .default_value("20"),
+                    )
+                    .arg(
+                        Arg::new("sizes")
+                            .long("sizes")
+                            .value_name("WxH,...")
+                            .help("Which sizes of maze to build")
+                            .value_delimiter(',')
+                            .value_parser(|size: &str| {
+                                size.split_once('x')
+                                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                                    .filter(|&(w, h): &(usize, usize)| w >= 2 && h >= 2)
+                                    .ok_or("expected a size like 40x30")
+                            })
+                            .default_value("10x10,40x30,80x60"),
+                    ),
+            );
+        let matches = command.get_matches_mut();
         let algorithm = matches.get_one::<String>("algorithm").unwrap().to_owned();
-        let variant = match algorithm.as_str() {
-            "bintree" => {
-                let mut args = matches.get_one::<String>("variant").unwrap().splitn(2, ':');
-                let random = args.next().unwrap_or("random");
-                let random = if random.is_empty() || random == "random" {
-                    "random"
-                } else {
-                    "ordered"
-                };
-                let bias = args.next().unwrap_or("NorthWest");
-                let bias = if bias.is_empty() { "NorthWest" } else { bias };
-
-                format!("{}:{}", random, bias)
+        let entry = registry::find(&algorithm).unwrap();
+        let variant = matches
+            .get_one::<String>("variant")
+            .map_or(entry.default_variant, String::as_str);
+        let variant = match entry.parse(variant) {
+            Ok(values) => values.to_string(),
+            Err(error) => command
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("Bad variant for {}: {}", entry.name, error),
+                )
+                .exit(),
+        };
+        let width = matches.get_one::<u64>("width").map(|&width| width as usize);
+        let height = matches
+            .get_one::<u64>("height")
+            .map(|&height| height as usize);
+        let (full_width, full_height) = entry.size((width, height));
+        if let Err(error) = entry.check_size(full_width, full_height) {
+            command.error(ErrorKind::ValueValidation, error).exit();
+        }
+        let seed = matches.get_one::<u64>("seed").copied();
+        let speed = Speed::new(
+            *matches.get_one::<u64>("steps").unwrap() as usize,
+            *matches.get_one::<f32>("interval").unwrap(),
+        );
+        let svg = matches.get_one::<String>("svg").cloned();
+        let png = matches.get_one::<String>("png").cloned();
+        let style = Style {
+            solution: matches.get_flag("solution"),
+            palette: matches.get_flag("palette"),
+            heatmap: matches.get_flag("heatmap"),
+        };
+        let record = matches.get_one::<String>("record").cloned();
+        let every = *matches.get_one::<u64>("every").unwrap() as usize;
+        let text = matches.get_one::<String>("text").cloned();
+        let save = matches.get_one::<String>("save").cloned();
+        let load = matches.get_one::<String>("load").cloned();
+        let solver = matches.get_one::<String>("solve").cloned();
+        let stats = matches.get_one::<u64>("stats").map(|&runs| runs as usize);
+        let bench = matches.subcommand_matches("bench").map(|bench| {
+            // Time every variant of everything, unless we were asked for one algorithm in
+            // particular, or one variant of it.
+            let algorithms = match (
+                matches.value_source("algorithm"),
+                matches.value_source("variant"),
+            ) {
+                (Some(ValueSource::CommandLine), Some(ValueSource::CommandLine)) => {
+                    vec![(algorithm.clone(), variant.clone())]
+                }
+                (Some(ValueSource::CommandLine), _) => (entry.variants().iter())
+                    .map(|values| (algorithm.clone(), values.to_string()))
+                    .collect(),
+                _ => (ALGORITHMS.iter())
+                    .flat_map(|entry| {
+                        (entry.variants().iter())
+                            .map(|values| (entry.id.to_owned(), values.to_string()))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect(),
+            };
+            Bench {
+                runs: *bench.get_one::<u64>("runs").unwrap() as usize,
+                sizes: bench
+                    .get_many::<(usize, usize)>("sizes")
+                    .unwrap()
+                    .copied()
+                    .collect(),
+                algorithms,
             }
-            _ => matches
-                .get_one::<String>("variant")
-                .unwrap_or(&"unused".to_string())
-                .to_owned(),
+        });
+        Self {
+            algorithm,
+            variant,
+            width,
+            height,
+            seed,
+            speed,
+            svg,
+            png,
+            style,
+            record,
+            every,
+            text,
+            save,
+            load,
+            solver,
+            stats,
+            bench,
+        }
+    }
+
+    /// Whether we're only writing files, and don't need a window.
+    pub fn is_headless(&self) -> bool {
+        self.svg.is_some()
+            || self.png.is_some()
+            || self.record.is_some()
+            || self.text.is_some()
+            || self.save.is_some()
+            || self.stats.is_some()
+    }
+
+    /// Run the `bench` subcommand, if it was asked for, using `make` to build each algorithm from
+    /// its name, variant, size, and seed. Returns whether there was anything to run.
+    pub fn bench(
+        &self,
+        make: impl Fn(&str, String, (usize, usize), u64) -> Box<dyn Algorithm>,
+    ) -> bool {
+        let Some(bench) = &self.bench else {
+            return false;
         };
-        Self { algorithm, variant }
+        // Make the columns wide enough for the longest names.
+        let width = |header: &str, column: fn(&(String, String)) -> &String| {
+            (bench.algorithms.iter())
+                .map(|row| column(row).len())
+                .fold(header.len(), usize::max)
+        };
+        let algorithm_width = width("Algorithm", |(algorithm, _)| algorithm);
+        let variant_width = width("Variant", |(_, variant)| variant);
+        println!(
+            "{:<algorithm_width$} {:<variant_width$} {:>7} {}",
+            "Algorithm",
+            "Variant",
+            "Size",
+            Timings::HEADER
+        );
+        for (algorithm, variant) in &bench.algorithms {
+            let entry = registry::find(algorithm).unwrap();
+            for &(width, height) in &bench.sizes {
+                // Leave out the sizes it can't build, rather than stopping every other algorithm.
+                if entry.check_size(width, height).is_err() {
+                    continue;
+                }
+                let timings = bench::measure(bench.runs, |seed| {
+                    make(algorithm, variant.clone(), (width, height), seed)
+                });
+                let size = format!("{}x{}", width, height);
+                println!(
+                    "{:<algorithm_width$} {:<variant_width$} {:>7} {}",
+                    algorithm, variant, size, timings
+                );
+            }
+        }
+        true
+    }
+
+    /// The maze from `--load`, if there was one.
+    pub fn load(&self) -> std::io::Result<Option<SavedMaze>> {
+        let Some(path) = &self.load else {
+            return Ok(None);
+        };
+        let json = std::fs::read_to_string(path)?;
+        Ok(Some(saved::from_json(&json)?))
+    }
+
+    /// Write out whichever files were asked for, using `make` to build the algorithm each time. It
+    /// comes from the registry's `id` entry, which saved mazes keep.
+    pub fn export(&self, id: &str, make: impl Fn() -> Box<dyn Algorithm>) -> std::io::Result<()> {
+        if let Some(path) = &self.record {
+            // Keep it in memory until it's done, so that one that can't be made leaves no file.
+            let format = Format::from_path(path).unwrap();
+            let mut bytes = vec![];
+            let frames = record::record(&make, self.every, format, &mut bytes)?;
+            std::fs::write(path, bytes)?;
+            println!("Wrote {} frames to {}.", frames, path);
+        }
+        if let Some(runs) = self.stats {
+            // Each run forks the random number generator, so the whole set repeats from the seed.
+            let mut algorithm = make();
+            let mut all = vec![];
+            for run in 0..runs {
+                if run > 0 {
+                    algorithm.re_init(algorithm.get_variant());
+                }
+                let stats = Stats::new(&algorithm.run()).ok_or_else(|| {
+                    std::io::Error::other("Only mazes with an entrance and an exit have statistics")
+                })?;
+                all.push(stats);
+            }
+            println!(
+                "{} ({}), {} mazes from seed {}:",
+                algorithm.name(),
+                algorithm.get_variant(),
+                runs,
+                make().get_seed()
+            );
+            print!("{}", Stats::mean(&all));
+        }
+        if self.svg.is_none() && self.png.is_none() && self.text.is_none() && self.save.is_none() {
+            return Ok(());
+        }
+
+        let mut algorithm = make();
+        let maze = algorithm.run();
+        if let Some(path) = &self.save {
+            let saved = SavedMaze::new(id, algorithm.as_ref()).ok_or_else(|| {
+                std::io::Error::other(match maze {
+                    Maze::Penrose(_) => "Penrose mazes can't be saved yet",
+                    _ => "Only mazes with an entrance and an exit can be saved",
+                })
+            })?;
+            std::fs::write(path, saved::to_json(&saved))?;
+        }
+        if let Some(path) = &self.svg {
+            std::fs::write(path, svg::to_svg(&maze))?;
+        }
+        if let Some(path) = &self.png {
+            let file = BufWriter::new(File::create(path)?);
+            raster::render(&maze, &self.style)
+                .write_png(file)
+                .map_err(std::io::Error::other)?;
+        }
+        if let Some(style) = &self.text {
+            let Maze::Square(grid) = &maze else {
+                return Err(std::io::Error::other(
+                    "Only square mazes can be printed as text",
+                ));
+            };
+            match style.as_str() {
+                "ascii" => print!("{}", text::ascii(grid)),
+                _ => print!("{}", text::unicode(grid)),
+            }
+        }
+        Ok(())
     }
 }
 
@@ -84,7 +457,41 @@ impl Args for Desktop {
         self.variant.clone()
     }
 
+    fn get_size(&self) -> (Option<usize>, Option<usize>) {
+        (self.width, self.height)
+    }
+
+    fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    fn get_solver(&self) -> Option<String> {
+        self.solver.clone()
+    }
+
+    fn get_speed(&self) -> Speed {
+        self.speed
+    }
+
     fn needs_reset(&self) -> bool {
         false
     }
+
+    fn new_speed(&self) -> Option<Speed> {
+        None
+    }
+
+    fn needs_step(&self) -> bool {
+        false
+    }
+
+    fn needs_finish(&self) -> bool {
+        false
+    }
+
+    fn seek_to(&self) -> Option<usize> {
+        None
+    }
+
+    fn show_history(&self, _step: usize, _furthest: usize) {}
 }
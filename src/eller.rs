@@ -1,10 +1,12 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State as BaseState,
-    CELL_WIDTH, COLORS, COLUMNS, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State as BaseState, CELL_WIDTH, COLORS, LINE_WIDTH, OFFSET,
 };
-use array_init::array_init;
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(PartialEq, Eq, Debug)]
@@ -23,15 +25,16 @@ pub struct Exports {
     current_column: usize,
     empty_sets: Vec<usize>,
     grid: Grid,
-    grid_sets: [[Option<usize>; COLUMNS as usize]; ROWS as usize],
+    grid_sets: Board<Option<usize>>,
     sets: Vec<(Vec<usize>, usize)>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        let grid_sets = [[None; COLUMNS as usize]; ROWS as usize];
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
+        let grid_sets = Board::new(width, height, None);
 
         Self {
             path: vec![],
@@ -42,25 +45,38 @@ impl Exports {
             grid_sets,
             sets: vec![],
             state: State::Setup,
+            rng,
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "eller",
+    name: "Eller",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Eller")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         // log::info!("{}, {:?}", self.current_row, self.state);
         match self.state {
             State::Setup => {
-                for x in 0..COLUMNS as usize {
+                for x in 0..width {
                     self.grid_sets[self.current_row][x] = Some(x);
                 }
                 self.state = State::Merging;
@@ -73,7 +89,7 @@ impl Algorithm for Exports {
                     self.grid_sets[self.current_row][self.current_column + 1] =
                         self.empty_sets.pop();
                 }
-                if gen_range(0, 2) == 0 || self.current_row == (ROWS - 1.0) as usize {
+                if self.rng.gen_range(0, 2) == 0 || self.current_row == height - 1 {
                     // Merge the cells, if they're in different sets.
                     let old_set = self.grid_sets[self.current_row][self.current_column + 1];
                     let new_set = self.grid_sets[self.current_row][self.current_column];
@@ -86,7 +102,7 @@ impl Algorithm for Exports {
                         self.grid[self.current_row][self.current_column] |= Direction::East;
                         self.grid[self.current_row][self.current_column + 1] |= Direction::West;
 
-                        for i in 0..COLUMNS as usize {
+                        for i in 0..width {
                             if self.grid_sets[self.current_row][i] == old_set {
                                 self.grid_sets[self.current_row][i] = new_set;
                             }
@@ -94,8 +110,8 @@ impl Algorithm for Exports {
                     }
                 }
                 self.current_column += 1;
-                if self.current_column == (COLUMNS - 1.0) as usize {
-                    if self.current_row != (ROWS - 1.0) as usize {
+                if self.current_column == width - 1 {
+                    if self.current_row != height - 1 {
                         self.state = State::NextLine;
                     } else {
                         self.current_row += 1;
@@ -109,8 +125,8 @@ impl Algorithm for Exports {
                 // Find the current sets.
                 self.sets.clear();
                 self.empty_sets.clear();
-                let mut sets: [Vec<usize>; COLUMNS as usize] = array_init(|_| Vec::new());
-                for x in 0..COLUMNS as usize {
+                let mut sets = vec![Vec::new(); width];
+                for x in 0..width {
                     let i = self.grid_sets[self.current_row][x].unwrap();
                     sets[i].push(x);
                 }
@@ -132,8 +148,8 @@ impl Algorithm for Exports {
                 // Pick 1..n of each set and drop it.
                 if let Some((set, i)) = self.sets.pop() {
                     // print!("{}: {:?}, Dropping: ", i, set);
-                    let count = gen_range(1, set.len() + 1);
-                    for &cell in set.choose_multiple(count) {
+                    let count = self.rng.gen_range(1, set.len() + 1);
+                    for &cell in set.choose_multiple(count, &mut self.rng) {
                         // print!("{}, ", cell);
                         self.grid[self.current_row][cell] |= Direction::South;
                         self.grid[self.current_row + 1][cell] |= Direction::North;
@@ -152,19 +168,40 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
         // Draw code here...
-        draw_board(self.grid);
+        draw_board(canvas, &self.grid);
 
         for row in self.current_row..self.current_row + 2 {
-            if row < ROWS as usize {
-                for x in 0..COLUMNS as usize {
+            if row < self.grid.height() {
+                for x in 0..self.grid.width() {
                     // log::info!("{:?}.", self.grid[self.current_row][x]);
                     if let Some(i) = self.grid_sets[row][x] {
                         let curr_color = COLORS[i + 1];
                         let mut cell_color = COLORS[i + 1];
                         cell_color.a = 0.5;
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             row as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -176,7 +213,7 @@ impl Algorithm for Exports {
                             && x == self.current_column
                             && self.state == State::Merging
                         {
-                            draw_rectangle(
+                            canvas.draw_rectangle(
                                 x as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                                 row as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                                 CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -188,28 +225,53 @@ impl Algorithm for Exports {
                 }
             }
         }
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
         &mut self.path
     }
 }
+
+#[test]
+fn seeking_back_restores_the_sets() {
+    use crate::history::History;
+    use std::any::Any;
+
+    let inside = |algorithm: &dyn Algorithm| {
+        let eller = (algorithm as &dyn Any).downcast_ref::<Exports>().unwrap();
+        (
+            eller.grid.clone(),
+            eller.grid_sets.clone(),
+            eller.sets.clone(),
+        )
+    };
+
+    let mut algorithm = ENTRY.build(&ENTRY.defaults(), (Some(8), Some(6)), Rng::new(3));
+    let mut history = History::new();
+    let mut steps = vec![];
+    while algorithm.get_state() != BaseState::Done {
+        steps.push(inside(algorithm.as_ref()));
+        algorithm.update();
+        history.record();
+    }
+    for (step, before) in steps.iter().enumerate().rev() {
+        history.seek(&ENTRY, &mut algorithm, step);
+        while history.replay(algorithm.as_mut()) {}
+        assert_eq!(inside(algorithm.as_ref()), *before, "step {}", step);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+use crate::{
+    delta_util,
+    graph::CellGraph,
+    hex_util, penrose, polar_util,
+    util::{self, Board},
+};
+
+pub use crate::util::State;
+
+/// Whatever a generator has built so far, in the shape of the board it was built on.
+#[derive(Clone, Debug)]
+pub enum Maze {
+    Square(util::Grid),
+    Hex(hex_util::Grid),
+    Delta(delta_util::Grid),
+    Polar(polar_util::Grid),
+    /// Until the tiles are small enough to be cells, it's only a tiling.
+    Penrose(penrose::Grid),
+}
+
+impl Maze {
+    /// The cell the robot starts in, where the maze has an opening.
+    pub fn start(&self) -> Option<(usize, usize)> {
+        match self {
+            Maze::Square(grid) => grid.start(),
+            Maze::Hex(grid) => grid.start(),
+            Maze::Delta(grid) => grid.start(),
+            Maze::Polar(grid) => grid.start(),
+            Maze::Penrose(grid) => grid.start(),
+        }
+    }
+
+    /// The cell with the other opening, that the robot is trying to get to.
+    pub fn goal(&self) -> Option<(usize, usize)> {
+        match self {
+            Maze::Square(grid) => grid.goal(),
+            Maze::Hex(grid) => grid.goal(),
+            Maze::Delta(grid) => grid.goal(),
+            Maze::Polar(grid) => grid.goal(),
+            Maze::Penrose(grid) => grid.goal(),
+        }
+    }
+
+    /// How many cells wide and high the board is.
+    pub fn size(&self) -> (usize, usize) {
+        match self {
+            Maze::Square(grid) => grid.size(),
+            Maze::Hex(grid) => grid.size(),
+            Maze::Delta(grid) => grid.size(),
+            Maze::Polar(grid) => grid.size(),
+            Maze::Penrose(grid) => grid.size(),
+        }
+    }
+
+    /// How many steps apart two cells would be if there were no walls in the way.
+    pub fn distance(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        match self {
+            Maze::Hex(_) => hex_util::distance(from, to),
+            Maze::Delta(_) => delta_util::distance(from, to),
+            Maze::Polar(_) => polar_util::distance(from, to),
+            Maze::Penrose(grid) => grid.distance(from, to),
+            _ => from.0.abs_diff(to.0) + from.1.abs_diff(to.1),
+        }
+    }
+
+    /// The cells you can walk to from `cell` without going through a wall.
+    pub fn neighbours(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        match self {
+            Maze::Square(grid) => grid.links(cell),
+            Maze::Hex(grid) => grid.links(cell),
+            Maze::Delta(grid) => grid.links(cell),
+            Maze::Polar(grid) => grid.links(cell),
+            Maze::Penrose(grid) => grid.links(cell),
+        }
+    }
+
+    /// The shortest path from the start to the goal, or nothing if there isn't one.
+    pub fn solution(&self) -> Vec<(usize, usize)> {
+        let (Some(start), Some(goal)) = (self.start(), self.goal()) else {
+            return vec![];
+        };
+        let (width, height) = self.size();
+        let mut previous = Board::new(width, height, None);
+        previous[start.1][start.0] = Some(start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(cell) = queue.pop_front() {
+            if cell == goal {
+                let mut path = vec![goal];
+                while let Some(&cell) = path.last().filter(|&&cell| cell != start) {
+                    path.push(previous[cell.1][cell.0].unwrap());
+                }
+                path.reverse();
+                return path;
+            }
+            for (x, y) in self.neighbours(cell) {
+                if previous[y][x].is_none() {
+                    previous[y][x] = Some(cell);
+                    queue.push_back((x, y));
+                }
+            }
+        }
+        vec![]
+    }
+}
+
+/// The headless half of an algorithm, which can be stepped or run without opening a window.
+pub trait Generator {
+    fn name(&self) -> String;
+    fn re_init(&mut self, variant: String);
+    fn get_variant(&self) -> String;
+    fn update(&mut self);
+    fn get_state(&self) -> State;
+    /// The seed this maze was built from, to build it again.
+    fn get_seed(&self) -> u64;
+    fn get_maze(&self) -> Maze;
+
+    /// Keep updating until the maze is done, and then hand it back.
+    fn run(&mut self) -> Maze {
+        while self.get_state() != State::Done {
+            self.update();
+        }
+        self.get_maze()
+    }
+}
@@ -0,0 +1,134 @@
+//! What every kind of board has in common, whatever shape its cells are, so that an algorithm only
+//! needs writing once to run on all of them.
+//!
+//! ```
+//! use mazes::{graph::CellGraph, hex_util, util};
+//!
+//! fn corridor<G: CellGraph>(width: usize) -> G {
+//!     let mut grid = G::empty(width, 3);
+//!     let cells = grid.cells();
+//!     for pair in cells.windows(2) {
+//!         if grid.neighbours(pair[0]).contains(&pair[1]) {
+//!             grid.link(pair[0], pair[1]);
+//!         }
+//!     }
+//!     grid
+//! }
+//!
+//! let square: util::Grid = corridor(4);
+//! assert!(square.is_linked((0, 0), (1, 0)));
+//! let hex: hex_util::Grid = corridor(4);
+//! assert_eq!(hex.links(hex.start().unwrap()).len(), 1);
+//! ```
+
+use macroquad::color::Color;
+
+use crate::canvas::Canvas;
+use crate::generator::Maze;
+use crate::registry::Shape;
+use crate::util::{follow, Heading, Wall, COLORS, LINE_WIDTH};
+
+/// Where a cell is on its board. What the numbers mean is up to the board.
+pub type Cell = (usize, usize);
+
+/// A board of cells, and the passages between them.
+pub trait CellGraph: Clone + 'static {
+    const SHAPE: Shape;
+
+    /// A board `width` by `height` cells, with all of its walls up.
+    fn empty(width: usize, height: usize) -> Self;
+    /// How many cells wide and high the board is. Not every spot has to be a cell.
+    fn size(&self) -> (usize, usize);
+    /// Every cell on the board, row by row.
+    fn cells(&self) -> Vec<Cell>;
+    /// The cells next to `cell`, whether or not there's a wall in the way.
+    fn neighbours(&self, cell: Cell) -> Vec<Cell>;
+    /// The cells you can walk to from `cell` without going through a wall.
+    fn links(&self, cell: Cell) -> Vec<Cell>;
+    /// Knock down the wall between two cells that are next to each other.
+    fn link(&mut self, a: Cell, b: Cell);
+    /// Put the wall between two cells that are next to each other back up.
+    fn unlink(&mut self, a: Cell, b: Cell);
+    /// The cell the robot starts in, where the maze has an opening.
+    fn start(&self) -> Option<Cell>;
+    /// The cell with the other opening, that the robot is trying to get to.
+    fn goal(&self) -> Option<Cell>;
+    /// The cell `heading` from `cell`, if there isn't a wall in the way.
+    fn step(&self, cell: Cell, heading: Heading) -> Option<Cell>;
+    fn maze(&self) -> Maze;
+
+    /// The size in pixels of the board, including the margins.
+    fn pixel_size(&self) -> (f32, f32);
+    /// The middle of `cell`, in pixels.
+    fn center(&self, cell: Cell) -> (f32, f32);
+    /// The cell under `pos`, if there is one.
+    fn cell_at(&self, pos: (f32, f32)) -> Option<Cell>;
+    /// The walls, as line segments, leaving the entrance and exit open.
+    fn walls(&self) -> Vec<Wall>;
+    fn draw_cell(&self, canvas: &mut dyn Canvas, cell: Cell, inset: f32, color: Color);
+    /// Mark the cell an algorithm is working on.
+    fn draw_current(&self, canvas: &mut dyn Canvas, cell: Cell, color: Color);
+    /// Draw the robot at the end of `path`, and the trail it left behind it.
+    fn draw_path(&self, canvas: &mut dyn Canvas, path: &[Cell]);
+
+    fn is_linked(&self, a: Cell, b: Cell) -> bool {
+        self.links(a).contains(&b)
+    }
+
+    /// Whether nothing has been carved into `cell` yet.
+    fn is_empty(&self, cell: Cell) -> bool {
+        self.links(cell).is_empty()
+    }
+
+    /// The cells the robot goes through to get from `from` to `to`, if it can get there without
+    /// any turns. Most boards only let it go one cell at a time.
+    fn route(&self, from: Cell, to: Cell) -> Option<Vec<Cell>> {
+        self.is_linked(from, to).then(|| vec![to])
+    }
+
+    fn draw_board(&self, canvas: &mut dyn Canvas) {
+        for ((x1, y1), (x2, y2)) in self.walls() {
+            canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH, COLORS[0]);
+        }
+    }
+}
+
+/// Send the robot towards the cell under `pos`, if there's a way through.
+pub fn move_to<G: CellGraph>(grid: &G, path: &mut Vec<Cell>, pos: (f32, f32)) {
+    let (Some(&last), Some(cursor)) = (path.last(), grid.cell_at(pos)) else {
+        return;
+    };
+    for cell in grid.route(last, cursor).unwrap_or_default() {
+        follow(path, cell);
+    }
+}
+
+/// Take the robot one cell `heading`, unless there's a wall in the way.
+pub fn walk<G: CellGraph>(grid: &G, path: &mut Vec<Cell>, heading: Heading) -> bool {
+    let Some(next) = path.last().and_then(|&cell| grid.step(cell, heading)) else {
+        return false;
+    };
+    follow(path, next);
+    true
+}
+
+#[test]
+fn linking_works_both_ways() {
+    use crate::{hex_util, util};
+
+    fn check<G: CellGraph>(mut grid: G) {
+        for cell in grid.cells() {
+            for next in grid.neighbours(cell) {
+                assert!(grid.neighbours(next).contains(&cell));
+                grid.link(cell, next);
+                assert!(grid.is_linked(next, cell));
+                grid.unlink(next, cell);
+                assert!(grid.is_empty(cell) && grid.is_empty(next));
+            }
+        }
+    }
+    check(util::Grid::empty(5, 4));
+    check(hex_util::Grid::empty(9, 6));
+    check(crate::delta_util::Grid::empty(7, 5));
+    check(crate::polar_util::Grid::empty(16, 6));
+}
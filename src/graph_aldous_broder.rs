@@ -0,0 +1,174 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::params::Param;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, ChooseRandom, Heading, Rng, State, COLORS, FIELD_COLOR};
+use macroquad::logging as log;
+use maze_utils::From;
+
+/// Aldous-Broder on any shape of board. The square one has its own look, so it's in
+/// `aldous_broder`.
+#[derive(From)]
+pub struct AldousBroder<G: CellGraph> {
+    path: Vec<Cell>,
+    curr: Cell,
+    grid: G,
+    prev: Cell,
+    remaining: usize,
+    speedup: bool,
+    state: State,
+    rng: Rng,
+}
+
+pub type HexExports = AldousBroder<hex_util::Grid>;
+pub type DeltaExports = AldousBroder<delta_util::Grid>;
+pub type PolarExports = AldousBroder<polar_util::Grid>;
+
+impl<G: CellGraph> AldousBroder<G> {
+    pub fn new(variant: bool, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+
+        Self {
+            path: vec![],
+            curr: (0, 0),
+            grid,
+            prev: (0, 0),
+            remaining: 0,
+            speedup: variant,
+            state: State::Setup,
+            rng,
+        }
+    }
+}
+
+const PARAMS: &[Param] = &[Param::Flag("slow", "fast")];
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexaldousbroder",
+    name: "Hex Aldous-Broder",
+    params: PARAMS,
+    default_variant: "slow",
+    shape: Shape::Hex,
+    new: |values, width, height, rng| Box::new(HexExports::new(values.flag(0), width, height, rng)),
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltaaldousbroder",
+    name: "Delta Aldous-Broder",
+    params: PARAMS,
+    default_variant: "slow",
+    shape: Shape::Delta,
+    new: |values, width, height, rng| {
+        Box::new(DeltaExports::new(values.flag(0), width, height, rng))
+    },
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polaraldousbroder",
+    name: "Polar Aldous-Broder",
+    params: PARAMS,
+    default_variant: "slow",
+    shape: Shape::Polar,
+    new: |values, width, height, rng| {
+        Box::new(PolarExports::new(values.flag(0), width, height, rng))
+    },
+};
+
+impl<G: CellGraph> Generator for AldousBroder<G> {
+    fn name(&self) -> String {
+        if self.speedup {
+            format!("Faster {} Aldous-Broderish", G::SHAPE.name())
+        } else {
+            format!("{} Aldous-Broder", G::SHAPE.name())
+        }
+    }
+    fn re_init(&mut self, variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        let values = HEX_ENTRY.parse_or_default(&variant);
+        self.from(AldousBroder::new(values.flag(0), width, height, rng));
+    }
+    fn get_variant(&self) -> String {
+        if self.speedup {
+            "fast".to_owned()
+        } else {
+            "slow".to_owned()
+        }
+    }
+    fn update(&mut self) {
+        if self.state == State::Setup {
+            let cells = self.grid.cells();
+            if let Some(first) = cells.choose(&mut self.rng) {
+                self.curr = first;
+                self.prev = first;
+                self.remaining = cells.len() - 1;
+            }
+            self.state = State::Running;
+            return;
+        }
+
+        if self.remaining == 0 {
+            self.path.extend(self.grid.start());
+            self.state = State::Done;
+            log::info!("Done!");
+            return;
+        }
+
+        let mut potentials = self.grid.neighbours(self.curr);
+        // The same not-going-straight-back tweak as the square version, with the same bias.
+        if self.speedup && potentials.len() > 1 {
+            potentials.retain(|&cell| cell != self.prev);
+        }
+        let next = potentials.choose(&mut self.rng).unwrap();
+        if self.grid.is_empty(next) {
+            self.grid.link(self.curr, next);
+            self.remaining -= 1;
+        }
+        self.prev = self.curr;
+        self.curr = next;
+    }
+
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for AldousBroder<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        if self.state == State::Running {
+            for cell in self.grid.cells() {
+                if self.grid.is_empty(cell) {
+                    self.grid.draw_cell(canvas, cell, 0.0, FIELD_COLOR);
+                }
+            }
+            self.grid.draw_current(canvas, self.curr, COLORS[1]);
+        }
+
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
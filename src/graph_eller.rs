@@ -0,0 +1,270 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, Board, ChooseRandom, Heading, Rng, State as BaseState, COLORS};
+use macroquad::logging as log;
+use maze_utils::From;
+
+#[derive(PartialEq, Eq, Debug)]
+enum State {
+    Setup,
+    Merging,
+    Dropping,
+    Done,
+}
+
+/// Eller on any shape of board, a row at a time, where the rows are however `cells` lists them.
+/// The square one has its own look, so it's in `eller`.
+#[derive(From)]
+pub struct Eller<G: CellGraph> {
+    path: Vec<Cell>,
+    current_row: usize,
+    /// The cells of the current row, in order.
+    row: Vec<Cell>,
+    /// Where we're up to in `row`.
+    current: usize,
+    /// The sets in the current row that still need to drop down to the next one.
+    drops: Vec<usize>,
+    grid: G,
+    grid_sets: Board<Option<usize>>,
+    next_set: usize,
+    state: State,
+    rng: Rng,
+}
+
+pub type HexExports = Eller<hex_util::Grid>;
+pub type DeltaExports = Eller<delta_util::Grid>;
+pub type PolarExports = Eller<polar_util::Grid>;
+
+impl<G: CellGraph> Eller<G> {
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+        let grid_sets = Board::new(width, height, None);
+
+        Self {
+            path: vec![],
+            current_row: 0,
+            row: vec![],
+            current: 0,
+            drops: vec![],
+            grid,
+            grid_sets,
+            next_set: 0,
+            state: State::Setup,
+            rng,
+        }
+    }
+
+    /// Start the next row, giving any cells nothing dropped into a set of their own.
+    fn start_row(&mut self) {
+        let y = self.current_row;
+        self.row = self.grid.cells();
+        self.row.retain(|&(_, j)| j == y);
+        for &(x, y) in &self.row {
+            if self.grid_sets[y][x].is_none() {
+                self.grid_sets[y][x] = Some(self.next_set);
+                self.next_set += 1;
+            }
+        }
+        self.current = 0;
+        self.state = State::Merging;
+    }
+
+    fn set(&self, (x, y): Cell) -> Option<usize> {
+        self.grid_sets[y][x]
+    }
+
+    /// Put everything in set `old` into set `new`, in the current row and the next one, which are
+    /// the only ones that matter any more.
+    fn join(&mut self, old: usize, new: usize) {
+        let rows = self.current_row..(self.current_row + 2).min(self.grid.size().1);
+        for y in rows {
+            for set in self.grid_sets[y].iter_mut().flatten() {
+                if *set == old {
+                    *set = new;
+                }
+            }
+        }
+    }
+
+    /// Every way from a cell in `set` into row `j`, as pairs of cells.
+    fn ways(&self, set: usize, j: usize) -> Vec<(Cell, Cell)> {
+        (self.row.iter())
+            .filter(|&&cell| self.set(cell) == Some(set))
+            .flat_map(|&cell| {
+                let neighbours = self.grid.neighbours(cell);
+                neighbours.into_iter().map(move |next| (cell, next))
+            })
+            .filter(|&(_, (_, y))| y == j)
+            .collect()
+    }
+}
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexeller",
+    name: "Hex Eller",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Hex,
+    new: |_values, width, height, rng| Box::new(HexExports::new(width, height, rng)),
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltaeller",
+    name: "Delta Eller",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Delta,
+    new: |_values, width, height, rng| Box::new(DeltaExports::new(width, height, rng)),
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polareller",
+    name: "Polar Eller",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Polar,
+    new: |_values, width, height, rng| Box::new(PolarExports::new(width, height, rng)),
+};
+
+impl<G: CellGraph> Generator for Eller<G> {
+    fn name(&self) -> String {
+        format!("{} Eller", G::SHAPE.name())
+    }
+    fn re_init(&mut self, _variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        self.from(Eller::new(width, height, rng));
+    }
+    fn get_variant(&self) -> String {
+        "unused".to_owned()
+    }
+    fn update(&mut self) {
+        let height = self.grid.size().1;
+        let y = self.current_row;
+        match self.state {
+            State::Setup => self.start_row(),
+            State::Merging => {
+                let (Some(&cell), Some(&next)) =
+                    (self.row.get(self.current), self.row.get(self.current + 1))
+                else {
+                    // That's the end of the row.
+                    if y + 1 >= height {
+                        self.state = State::Done;
+                        self.path.extend(self.grid.start());
+                        log::info!("Done!");
+                    } else {
+                        self.drops = self.row.iter().filter_map(|&cell| self.set(cell)).collect();
+                        self.drops.sort_unstable();
+                        self.drops.dedup();
+                        self.state = State::Dropping;
+                    }
+                    return;
+                };
+                // Merge the cells, if they're in different sets, and always on the last row.
+                let (set, other) = (self.set(cell).unwrap(), self.set(next).unwrap());
+                if (self.rng.gen_range(0, 2) == 0 || y == height - 1)
+                    && other != set
+                    && self.grid.neighbours(cell).contains(&next)
+                {
+                    self.grid.link(cell, next);
+                    self.join(other, set);
+                }
+                self.current += 1;
+            }
+            State::Dropping => {
+                // Drop 1..n of the ways down from this set, without looping back into itself.
+                let Some(set) = self.drops.pop() else {
+                    self.current_row += 1;
+                    self.start_row();
+                    return;
+                };
+                let mut potentials = self.ways(set, y + 1);
+                if potentials.is_empty() {
+                    // Some cells have no way down, like triangles standing on their points, so
+                    // join sideways onto a set that does, or will. Unless it's been joined into
+                    // another set already, and there's nothing left of it.
+                    let mut sideways = self.ways(set, y);
+                    sideways.retain(|&(_, next)| self.set(next) != Some(set));
+                    if let Some((cell, next)) = sideways.choose(&mut self.rng) {
+                        self.grid.link(cell, next);
+                        self.join(set, self.set(next).unwrap());
+                    }
+                    return;
+                }
+                let count = self.rng.gen_range(1, potentials.len() + 1);
+                potentials.shuffle(&mut self.rng);
+                let mut dropped = 0;
+                for (cell, (i, j)) in potentials {
+                    if dropped == count {
+                        break;
+                    }
+                    match self.grid_sets[j][i] {
+                        Some(other) if other == set => continue,
+                        Some(other) => self.join(other, set),
+                        None => self.grid_sets[j][i] = Some(set),
+                    }
+                    self.grid.link(cell, (i, j));
+                    dropped += 1;
+                }
+            }
+            State::Done => {}
+        }
+    }
+
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for Eller<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        if self.state != State::Done {
+            let rows = self.current_row..self.current_row + 2;
+            for cell in self.grid.cells() {
+                if !rows.contains(&cell.1) {
+                    continue;
+                }
+                if let Some(set) = self.set(cell) {
+                    let curr_color = COLORS[1 + set % (COLORS.len() - 1)];
+                    let mut cell_color = curr_color;
+                    cell_color.a = 0.5;
+                    self.grid.draw_cell(canvas, cell, 0.0, cell_color);
+                    if self.state == State::Merging && self.row.get(self.current) == Some(&cell) {
+                        self.grid.draw_current(canvas, cell, curr_color);
+                    }
+                }
+            }
+        }
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
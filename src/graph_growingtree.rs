@@ -0,0 +1,204 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::growingtree::Variant;
+use crate::hex_util;
+use crate::params::Param;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, ChooseRandom, Heading, Rng, State, COLORS, FIELD_COLOR};
+use macroquad::logging as log;
+use maze_utils::From;
+use std::collections::VecDeque;
+
+/// Growing Tree on any shape of board. The square one has its own look, so it's in
+/// `growingtree`.
+#[derive(From)]
+pub struct GrowingTree<G: CellGraph> {
+    path: Vec<Cell>,
+    curr: Option<Cell>,
+    grid: G,
+    stack: VecDeque<Cell>,
+    state: State,
+    variant: Variant,
+    rng: Rng,
+}
+
+pub type HexExports = GrowingTree<hex_util::Grid>;
+pub type DeltaExports = GrowingTree<delta_util::Grid>;
+pub type PolarExports = GrowingTree<polar_util::Grid>;
+
+impl<G: CellGraph> GrowingTree<G> {
+    pub fn new(variant: Variant, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+
+        Self {
+            path: vec![],
+            curr: None,
+            grid,
+            stack: VecDeque::new(),
+            state: State::Setup,
+            variant,
+            rng,
+        }
+    }
+}
+
+const PARAMS: &[Param] = &[Param::Choice(&Variant::NAMES)];
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexgrowingtree",
+    name: "Hex Growing Tree",
+    params: PARAMS,
+    default_variant: "middle",
+    shape: Shape::Hex,
+    new: |values, width, height, rng| {
+        Box::new(HexExports::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltagrowingtree",
+    name: "Delta Growing Tree",
+    params: PARAMS,
+    default_variant: "middle",
+    shape: Shape::Delta,
+    new: |values, width, height, rng| {
+        Box::new(DeltaExports::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polargrowingtree",
+    name: "Polar Growing Tree",
+    params: PARAMS,
+    default_variant: "middle",
+    shape: Shape::Polar,
+    new: |values, width, height, rng| {
+        Box::new(PolarExports::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+impl<G: CellGraph> Generator for GrowingTree<G> {
+    fn name(&self) -> String {
+        let shape = G::SHAPE.name();
+        match self.variant {
+            Variant::Newest => format!("Newest {} Growing Tree", shape),
+            Variant::Middle => format!("Middle {} Growing Tree", shape),
+            Variant::Oldest => format!("Oldest {} Growing Tree", shape),
+            Variant::Random => format!("Random {} Growing Tree", shape),
+        }
+    }
+    fn re_init(&mut self, variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        let values = HEX_ENTRY.parse_or_default(&variant);
+        self.from(GrowingTree::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ));
+    }
+    fn get_variant(&self) -> String {
+        Variant::NAMES[self.variant as usize].to_owned()
+    }
+    fn update(&mut self) {
+        if self.state == State::Setup {
+            self.stack.extend(self.grid.cells().choose(&mut self.rng));
+            self.state = State::Running;
+            return;
+        }
+
+        if self.stack.is_empty() {
+            self.state = State::Done;
+            self.curr = None;
+            self.path.extend(self.grid.start());
+            log::info!("Done!");
+            return;
+        }
+
+        let index = match self.variant {
+            Variant::Newest => 0,
+            Variant::Middle => (self.stack.len() - 1) / 2,
+            Variant::Oldest => self.stack.len() - 1,
+            Variant::Random => self.rng.gen_range(0, self.stack.len()),
+        };
+
+        let cell = self.stack[index];
+        self.curr = Some(cell);
+        let mut potentials = self.grid.neighbours(cell);
+        potentials.retain(|&next| self.grid.is_empty(next) && !self.stack.contains(&next));
+
+        let Some(next) = potentials.choose(&mut self.rng) else {
+            self.stack.remove(index);
+            return;
+        };
+        self.grid.link(cell, next);
+        self.stack.push_front(next);
+        self.curr = Some(next);
+    }
+
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for GrowingTree<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        let mut cell_color = COLORS[1];
+        cell_color.a = 0.5;
+        for cell in self.grid.cells() {
+            if self.grid.is_empty(cell) {
+                self.grid.draw_cell(canvas, cell, 0.0, FIELD_COLOR);
+            }
+        }
+        for &cell in &self.stack {
+            if Some(cell) != self.curr {
+                self.grid.draw_cell(canvas, cell, 0.0, cell_color);
+            }
+        }
+        if let Some(cell) = self.curr {
+            self.grid.draw_current(canvas, cell, COLORS[1]);
+        }
+
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
@@ -0,0 +1,212 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, ChooseRandom, Heading, Rng, State as BaseState, COLORS, FIELD_COLOR};
+use macroquad::logging as log;
+use maze_utils::From;
+
+#[derive(PartialEq, Eq, Debug)]
+enum State {
+    Setup,
+    Walking,
+    Finding,
+    Done,
+}
+
+/// Hunt and Kill on any shape of board. The square one has its own look, so it's in
+/// `huntandkill`.
+#[derive(From)]
+pub struct HuntAndKill<G: CellGraph> {
+    path: Vec<Cell>,
+    curr: Option<Cell>,
+    first_empty_line: usize,
+    grid: G,
+    scan_line: Option<usize>,
+    state: State,
+    rng: Rng,
+}
+
+pub type HexExports = HuntAndKill<hex_util::Grid>;
+pub type DeltaExports = HuntAndKill<delta_util::Grid>;
+pub type PolarExports = HuntAndKill<polar_util::Grid>;
+
+impl<G: CellGraph> HuntAndKill<G> {
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+
+        Self {
+            path: vec![],
+            curr: None,
+            first_empty_line: 0,
+            grid,
+            scan_line: None,
+            state: State::Setup,
+            rng,
+        }
+    }
+
+    /// The cells of row `y`.
+    fn row(&self, y: usize) -> Vec<Cell> {
+        let mut cells = self.grid.cells();
+        cells.retain(|&(_, j)| j == y);
+        cells
+    }
+}
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexhuntandkill",
+    name: "Hex Hunt and Kill",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Hex,
+    new: |_values, width, height, rng| Box::new(HexExports::new(width, height, rng)),
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltahuntandkill",
+    name: "Delta Hunt and Kill",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Delta,
+    new: |_values, width, height, rng| Box::new(DeltaExports::new(width, height, rng)),
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polarhuntandkill",
+    name: "Polar Hunt and Kill",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Polar,
+    new: |_values, width, height, rng| Box::new(PolarExports::new(width, height, rng)),
+};
+
+impl<G: CellGraph> Generator for HuntAndKill<G> {
+    fn name(&self) -> String {
+        format!("{} Hunt and Kill", G::SHAPE.name())
+    }
+    fn re_init(&mut self, _variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        self.from(HuntAndKill::new(width, height, rng));
+    }
+    fn get_variant(&self) -> String {
+        "unused".to_owned()
+    }
+    fn update(&mut self) {
+        match self.state {
+            State::Setup => {
+                self.curr = self.grid.cells().choose(&mut self.rng);
+                self.state = State::Walking;
+            }
+
+            State::Walking => {
+                let potentials = self.curr.map_or(vec![], |curr| {
+                    let mut potentials = self.grid.neighbours(curr);
+                    potentials.retain(|&cell| self.grid.is_empty(cell));
+                    potentials
+                });
+                if let Some(next) = potentials.choose(&mut self.rng) {
+                    self.grid.link(self.curr.unwrap(), next);
+                    self.curr = Some(next);
+                } else {
+                    // We didn't find a direction to go, so start the Finding!
+                    self.curr = None;
+                    self.scan_line = Some(self.first_empty_line);
+                    self.state = State::Finding;
+                }
+            }
+
+            State::Finding => {
+                let y = self.scan_line.unwrap();
+                let mut empty = self.row(y);
+                empty.retain(|&cell| self.grid.is_empty(cell));
+                let mut potentials = vec![];
+                for &cell in &empty {
+                    let mut visited = self.grid.neighbours(cell);
+                    visited.retain(|&next| !self.grid.is_empty(next));
+                    if let Some(next) = visited.choose(&mut self.rng) {
+                        potentials.push((cell, next));
+                    }
+                }
+
+                let Some((cell, next)) = potentials.choose(&mut self.rng) else {
+                    if y + 1 < self.grid.size().1 {
+                        // Move to the next line…
+                        self.scan_line = Some(y + 1);
+                        if empty.is_empty() {
+                            self.first_empty_line = y + 1;
+                        }
+                    } else {
+                        // We're done!
+                        self.scan_line = None;
+                        self.path.extend(self.grid.start());
+                        self.state = State::Done;
+                        log::info!("Done!");
+                    }
+                    return;
+                };
+
+                // Otherwise, join one of them up, and go from there!
+                self.grid.link(cell, next);
+                self.curr = Some(cell);
+                self.scan_line = None;
+                self.state = State::Walking;
+            }
+            State::Done => {}
+        }
+    }
+
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for HuntAndKill<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        let mut line_color = COLORS[1];
+        line_color.a = 0.3;
+
+        for cell in self.grid.cells() {
+            if self.grid.is_empty(cell) {
+                self.grid.draw_cell(canvas, cell, 0.0, FIELD_COLOR);
+            }
+            if Some(cell.1) == self.scan_line {
+                self.grid.draw_cell(canvas, cell, 0.0, line_color);
+            }
+        }
+        if let Some(cell) = self.curr {
+            self.grid.draw_current(canvas, cell, COLORS[1]);
+        }
+
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
@@ -0,0 +1,176 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, Board, ChooseRandom, Heading, Rng, State, COLORS};
+use macroquad::logging as log;
+use maze_utils::From;
+
+/// Kruskal on any shape of board. The square one has its own look, so it's in `kruskal`.
+#[derive(From)]
+pub struct Kruskal<G: CellGraph> {
+    path: Vec<Cell>,
+    edges: Vec<(Cell, Cell)>,
+    grid: G,
+    parents: Board<Option<Cell>>,
+    sizes: Board<usize>,
+    state: State,
+    rng: Rng,
+}
+
+pub type HexExports = Kruskal<hex_util::Grid>;
+pub type DeltaExports = Kruskal<delta_util::Grid>;
+pub type PolarExports = Kruskal<polar_util::Grid>;
+
+impl<G: CellGraph> Kruskal<G> {
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+        let parents = Board::new(width, height, None);
+        let sizes = Board::new(width, height, 1);
+
+        Self {
+            path: vec![],
+            edges: vec![],
+            grid,
+            parents,
+            sizes,
+            state: State::Setup,
+            rng,
+        }
+    }
+
+    fn find_root(&self, (x, y): Cell) -> Cell {
+        let (mut x, mut y) = (x, y);
+        while let Some((new_x, new_y)) = self.parents[y][x] {
+            x = new_x;
+            y = new_y;
+        }
+        (x, y)
+    }
+}
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexkruskal",
+    name: "Hex Kruskal",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Hex,
+    new: |_values, width, height, rng| Box::new(HexExports::new(width, height, rng)),
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltakruskal",
+    name: "Delta Kruskal",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Delta,
+    new: |_values, width, height, rng| Box::new(DeltaExports::new(width, height, rng)),
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polarkruskal",
+    name: "Polar Kruskal",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Polar,
+    new: |_values, width, height, rng| Box::new(PolarExports::new(width, height, rng)),
+};
+
+impl<G: CellGraph> Generator for Kruskal<G> {
+    fn name(&self) -> String {
+        format!("{} Kruskal", G::SHAPE.name())
+    }
+    fn re_init(&mut self, _variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        self.from(Kruskal::new(width, height, rng));
+    }
+    fn get_variant(&self) -> String {
+        "unused".to_owned()
+    }
+    fn update(&mut self) {
+        if self.state == State::Setup {
+            // Every edge once, by only looking forwards from each cell.
+            for cell in self.grid.cells() {
+                for next in self.grid.neighbours(cell) {
+                    if cell < next {
+                        self.edges.push((cell, next));
+                    }
+                }
+            }
+            self.edges.shuffle(&mut self.rng);
+
+            self.state = State::Running;
+            return;
+        }
+
+        while let Some((cell, next)) = self.edges.pop() {
+            let root = self.find_root(cell);
+            let new_root = self.find_root(next);
+            if root == new_root {
+                continue;
+            }
+
+            // Connect the cells, and join the smaller set to the bigger set.
+            self.grid.link(cell, next);
+            let (big, small) = if self.sizes[root.1][root.0] >= self.sizes[new_root.1][new_root.0] {
+                (root, new_root)
+            } else {
+                (new_root, root)
+            };
+            self.parents[small.1][small.0] = Some(big);
+            self.sizes[big.1][big.0] += self.sizes[small.1][small.0];
+            return;
+        }
+
+        self.path.extend(self.grid.start());
+        self.state = State::Done;
+        log::info!("Done!");
+    }
+
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for Kruskal<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        if self.state == State::Running {
+            // Colour each set by where its root is.
+            let width = self.grid.size().0;
+            for cell in self.grid.cells() {
+                if !self.grid.is_empty(cell) {
+                    let (i, j) = self.find_root(cell);
+                    let mut color = COLORS[1 + (j * width + i) % (COLORS.len() - 1)];
+                    color.a = 0.5;
+                    self.grid.draw_cell(canvas, cell, 0.0, color);
+                }
+            }
+        }
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
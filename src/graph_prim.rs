@@ -0,0 +1,168 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, Board, ChooseRandom, Heading, Rng, State, COLORS, EMPTY_COLOR};
+use macroquad::logging as log;
+use maze_utils::From;
+
+/// Prim on any shape of board. The square one has its own look, so it's in `prim`.
+#[derive(From)]
+pub struct Prim<G: CellGraph> {
+    path: Vec<Cell>,
+    frontier: Vec<Cell>,
+    grid: G,
+    grid_state: Board<bool>,
+    state: State,
+    rng: Rng,
+}
+
+pub type HexExports = Prim<hex_util::Grid>;
+pub type DeltaExports = Prim<delta_util::Grid>;
+pub type PolarExports = Prim<polar_util::Grid>;
+
+impl<G: CellGraph> Prim<G> {
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+        let grid_state = Board::new(width, height, false);
+
+        Self {
+            path: vec![],
+            frontier: vec![],
+            grid,
+            grid_state,
+            state: State::Setup,
+            rng,
+        }
+    }
+}
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexprim",
+    name: "Hex Prim",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Hex,
+    new: |_values, width, height, rng| Box::new(HexExports::new(width, height, rng)),
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltaprim",
+    name: "Delta Prim",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Delta,
+    new: |_values, width, height, rng| Box::new(DeltaExports::new(width, height, rng)),
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polarprim",
+    name: "Polar Prim",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Polar,
+    new: |_values, width, height, rng| Box::new(PolarExports::new(width, height, rng)),
+};
+
+impl<G: CellGraph> Generator for Prim<G> {
+    fn name(&self) -> String {
+        format!("{} Prim", G::SHAPE.name())
+    }
+    fn re_init(&mut self, _variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        self.from(Prim::new(width, height, rng));
+    }
+    fn get_variant(&self) -> String {
+        "unused".to_owned()
+    }
+    fn update(&mut self) {
+        if self.state == State::Setup {
+            // Add an initial cell to the frontier…
+            self.frontier
+                .extend(self.grid.cells().choose(&mut self.rng));
+            self.state = State::Running;
+            return;
+        }
+
+        let Some((x, y)) = self.frontier.pop() else {
+            self.path.extend(self.grid.start());
+            self.state = State::Done;
+            log::info!("Done!");
+            return;
+        };
+        // Add the cell to the in set!
+        self.grid_state[y][x] = true;
+
+        let mut carved = false;
+        let mut potentials = self.grid.neighbours((x, y));
+        potentials.shuffle(&mut self.rng);
+        for (new_x, new_y) in potentials {
+            match (self.grid_state[new_y][new_x], carved) {
+                (true, false) => {
+                    // Find another in cell to carve a path to.
+                    self.grid.link((x, y), (new_x, new_y));
+                    carved = true;
+                }
+                (true, true) => {}
+                (false, _) => {
+                    // Add the other out cells to the frontier.
+                    if !self.frontier.contains(&(new_x, new_y)) {
+                        self.frontier.push((new_x, new_y));
+                    }
+                }
+            }
+        }
+        self.frontier.shuffle(&mut self.rng);
+    }
+
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for Prim<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        if self.state == State::Running {
+            let mut cell_color = COLORS[1];
+            cell_color.a = 0.5;
+
+            for (x, y) in self.grid.cells() {
+                if !self.grid_state[y][x] && !self.frontier.contains(&(x, y)) {
+                    self.grid.draw_cell(canvas, (x, y), 0.0, EMPTY_COLOR);
+                }
+            }
+            for &cell in &self.frontier {
+                self.grid.draw_cell(canvas, cell, 0.0, cell_color);
+            }
+            if let Some(&cell) = self.frontier.last() {
+                self.grid.draw_current(canvas, cell, COLORS[1]);
+            }
+        }
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
@@ -0,0 +1,265 @@
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::params::Param;
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{
+    Algorithm, Board, ChooseRandom, Heading, Rng, State as BaseState, COLORS, FIELD_COLOR,
+    LINE_WIDTH,
+};
+use macroquad::logging as log;
+use maze_utils::From;
+
+#[derive(PartialEq, Eq, Debug)]
+enum State {
+    Setup,
+    Finding,
+    Following,
+    Done,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mark {
+    /// Part of the walk, which went on to this cell next.
+    Towards(Cell),
+    In,
+    Out,
+}
+
+/// Wilson on any shape of board. The square one has its own look, so it's in `wilson`.
+#[derive(From)]
+pub struct Wilson<G: CellGraph> {
+    path: Vec<Cell>,
+    current: Option<Cell>,
+    grid: G,
+    previous: Option<Cell>,
+    processing: Board<Mark>,
+    remaining: usize,
+    slowdown: bool,
+    start: Option<Cell>,
+    state: State,
+    rng: Rng,
+}
+
+pub type HexExports = Wilson<hex_util::Grid>;
+pub type DeltaExports = Wilson<delta_util::Grid>;
+pub type PolarExports = Wilson<polar_util::Grid>;
+
+impl<G: CellGraph> Wilson<G> {
+    pub fn new(variant: bool, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+        let processing = Board::new(width, height, Mark::Out);
+
+        Self {
+            path: vec![],
+            current: None,
+            grid,
+            previous: None,
+            processing,
+            remaining: 0,
+            slowdown: variant,
+            start: None,
+            state: State::Setup,
+            rng,
+        }
+    }
+}
+
+const PARAMS: &[Param] = &[Param::Flag("fast", "slow")];
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexwilson",
+    name: "Hex Wilson",
+    params: PARAMS,
+    default_variant: "fast",
+    shape: Shape::Hex,
+    new: |values, width, height, rng| Box::new(HexExports::new(values.flag(0), width, height, rng)),
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltawilson",
+    name: "Delta Wilson",
+    params: PARAMS,
+    default_variant: "fast",
+    shape: Shape::Delta,
+    new: |values, width, height, rng| {
+        Box::new(DeltaExports::new(values.flag(0), width, height, rng))
+    },
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polarwilson",
+    name: "Polar Wilson",
+    params: PARAMS,
+    default_variant: "fast",
+    shape: Shape::Polar,
+    new: |values, width, height, rng| {
+        Box::new(PolarExports::new(values.flag(0), width, height, rng))
+    },
+};
+
+impl<G: CellGraph> Generator for Wilson<G> {
+    fn name(&self) -> String {
+        if self.slowdown {
+            format!("Slower {} Wilsonish", G::SHAPE.name())
+        } else {
+            format!("{} Wilson", G::SHAPE.name())
+        }
+    }
+    fn re_init(&mut self, variant: String) {
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        let values = HEX_ENTRY.parse_or_default(&variant);
+        self.from(Wilson::new(values.flag(0), width, height, rng));
+    }
+    fn get_variant(&self) -> String {
+        if self.slowdown {
+            "slow".to_owned()
+        } else {
+            "fast".to_owned()
+        }
+    }
+    fn update(&mut self) {
+        if self.state == State::Setup {
+            let cells = self.grid.cells();
+            let (x, y) = cells.choose(&mut self.rng).unwrap();
+            self.processing[y][x] = Mark::In;
+            self.remaining = cells.len() - 1;
+
+            self.state = State::Finding;
+            return;
+        }
+
+        if self.remaining == 0 {
+            self.start = None;
+            self.current = None;
+            self.state = State::Done;
+            self.path.extend(self.grid.start());
+            log::info!("Done!");
+            return;
+        }
+
+        match self.state {
+            State::Finding => {
+                if self.start.is_none() {
+                    let potentials = (self.grid.cells().into_iter())
+                        .filter(|&(x, y)| self.processing[y][x] == Mark::Out)
+                        .collect::<Vec<_>>();
+                    self.start = potentials.choose(&mut self.rng);
+                    self.current = self.start;
+                }
+                let (x, y) = self.current.unwrap();
+
+                let mut potentials = self.grid.neighbours((x, y));
+                // The same not-going-straight-back tweak as the square version, with the same bias.
+                if self.slowdown && potentials.len() > 1 {
+                    potentials.retain(|&cell| Some(cell) != self.previous);
+                }
+                let (new_x, new_y) = potentials.choose(&mut self.rng).unwrap();
+                self.processing[y][x] = Mark::Towards((new_x, new_y));
+                self.previous = self.current;
+                self.current = Some((new_x, new_y));
+                if self.processing[new_y][new_x] == Mark::In {
+                    // We found it!!!
+                    self.current = self.start;
+                    self.start = None;
+                    self.state = State::Following;
+                }
+            }
+            State::Following => {
+                let (x, y) = self.current.unwrap();
+                match self.processing[y][x] {
+                    Mark::Towards(next) => {
+                        self.processing[y][x] = Mark::In;
+                        self.remaining -= 1;
+                        self.grid.link((x, y), next);
+                        self.current = Some(next);
+                    }
+                    Mark::In => {
+                        // We found it! Forget the rest of the walk, since it looped back.
+                        for mark in self.processing.iter_mut().flatten() {
+                            if let Mark::Towards(_) = mark {
+                                *mark = Mark::Out;
+                            }
+                        }
+                        self.state = State::Finding;
+                    }
+                    mark => {
+                        panic!("Should be unable to hit cell {:?} at ({},{})!", mark, x, y);
+                    }
+                }
+            }
+            _ => {
+                panic!(
+                    "Should be unable to hit state {:?} in this match!",
+                    self.state
+                );
+            }
+        }
+    }
+
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for Wilson<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+
+        let mut start_color = COLORS[1];
+        start_color.a = 0.5;
+
+        for (x, y) in self.grid.cells() {
+            match self.processing[y][x] {
+                Mark::Out if Some((x, y)) != self.start => {
+                    self.grid.draw_cell(canvas, (x, y), 0.0, FIELD_COLOR);
+                }
+                Mark::Towards(next) => {
+                    self.grid.draw_cell(canvas, (x, y), 0.0, start_color);
+                    // Point the way the walk went, from the middle of this cell towards the next.
+                    let (x1, y1) = self.grid.center((x, y));
+                    let (x2, y2) = self.grid.center(next);
+                    let (x2, y2) = (x1 + (x2 - x1) * 0.6, y1 + (y2 - y1) * 0.6);
+                    canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH, COLORS[1]);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(cell) = self.start {
+            self.grid.draw_cell(canvas, cell, 0.0, start_color);
+        }
+        if let Some(cell) = self.current {
+            self.grid.draw_current(canvas, cell, COLORS[1]);
+        }
+
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
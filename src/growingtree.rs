@@ -1,21 +1,35 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State, CELL_WIDTH,
-    COLORS, COLUMNS, FIELD_COLOR, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State, CELL_WIDTH, COLORS, FIELD_COLOR, LINE_WIDTH, OFFSET,
 };
 use maze_utils::From;
 use std::collections::VecDeque;
 
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 
-#[derive(Debug)]
-enum Variant {
+#[derive(Clone, Copy, Debug)]
+pub enum Variant {
     Newest,
     Middle,
     Oldest,
     Random,
 }
 
+impl Variant {
+    pub const ALL: [Variant; 4] = [
+        Variant::Newest,
+        Variant::Middle,
+        Variant::Oldest,
+        Variant::Random,
+    ];
+    pub const NAMES: [&'static str; 4] = ["newest", "middle", "oldest", "random"];
+}
+
 #[derive(From)]
 pub struct Exports {
     path: Vec<(usize, usize)>,
@@ -24,18 +38,12 @@ pub struct Exports {
     stack: VecDeque<(usize, usize)>,
     state: State,
     variant: Variant,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(variant: String) -> Self {
-        let variant = match variant.as_str() {
-            "newest" => Variant::Newest,
-            "middle" => Variant::Middle,
-            "oldest" => Variant::Oldest,
-            "random" => Variant::Random,
-            _ => panic!("Unknown Variant \"{}\"!", variant),
-        };
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
+    pub fn new(variant: Variant, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
 
         Self {
             path: vec![],
@@ -44,11 +52,28 @@ impl Exports {
             stack: VecDeque::new(),
             state: State::Setup,
             variant,
+            rng,
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "growingtree",
+    name: "Growing Tree",
+    params: &[Param::Choice(&Variant::NAMES)],
+    default_variant: "middle",
+    shape: Shape::Square,
+    new: |values, width, height, rng| {
+        Box::new(Exports::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         match self.variant {
             Variant::Newest => String::from("Newest Growing Tree"),
@@ -58,7 +83,15 @@ impl Algorithm for Exports {
         }
     }
     fn re_init(&mut self, variant: String) {
-        self.from(Exports::new(variant));
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ));
     }
     fn get_variant(&self) -> String {
         match self.variant {
@@ -69,10 +102,13 @@ impl Algorithm for Exports {
         }
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width() as i32, self.grid.height() as i32);
         // log::info!("Updating {}", self.name());
         if self.state == State::Setup {
-            self.stack
-                .push_front((gen_range(0, COLUMNS as usize), gen_range(0, ROWS as usize)));
+            self.stack.push_front((
+                self.rng.gen_range(0, width as usize),
+                self.rng.gen_range(0, height as usize),
+            ));
             self.state = State::Running;
             return;
         }
@@ -90,7 +126,7 @@ impl Algorithm for Exports {
             Variant::Newest => 0,
             Variant::Middle => (self.stack.len() - 1) / 2,
             Variant::Oldest => self.stack.len() - 1,
-            Variant::Random => gen_range(0, self.stack.len()),
+            Variant::Random => self.rng.gen_range(0, self.stack.len()),
         };
 
         let (x, y) = self.stack[index];
@@ -107,7 +143,7 @@ impl Algorithm for Exports {
                     Direction::West => (x as i32 - 1, y as i32),
                 };
                 // log::info!("{:?} / {:?} -> {:?}", (x,y), direction, (new_x, new_y));
-                if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32 {
+                if 0 <= new_x && new_x < width && 0 <= new_y && new_y < height {
                     let (new_x, new_y) = (new_x as usize, new_y as usize);
                     if self.grid[new_y][new_x] == EnumSet::new()
                         && !self.stack.contains(&(new_x, new_y))
@@ -127,7 +163,7 @@ impl Algorithm for Exports {
             self.stack.remove(index);
             return;
         }
-        let (new_x, new_y, direction) = potentials.choose().unwrap().to_owned();
+        let (new_x, new_y, direction) = potentials.choose(&mut self.rng).unwrap().to_owned();
         self.grid[y][x] |= direction;
         self.grid[new_y][new_x] |= direction.opposite();
         // log::info!("  pushing ({},{})", new_x, new_y);
@@ -135,17 +171,34 @@ impl Algorithm for Exports {
         self.curr = Some((new_x, new_y));
     }
 
-    fn draw(&self) {
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
         // Draw code here...
-        draw_board(self.grid);
+        draw_board(canvas, &self.grid);
 
         let curr_color = COLORS[1];
         let mut cell_color = COLORS[1];
         cell_color.a = 0.5;
-        for x in 0..COLUMNS as usize {
-            for y in 0..ROWS as usize {
+        for x in 0..self.grid.width() {
+            for y in 0..self.grid.height() {
                 if self.grid[y][x] == EnumSet::new() {
-                    draw_rectangle(
+                    canvas.draw_rectangle(
                         x as f32 * CELL_WIDTH + OFFSET,
                         y as f32 * CELL_WIDTH + OFFSET,
                         CELL_WIDTH,
@@ -157,7 +210,7 @@ impl Algorithm for Exports {
         }
         for (x, y) in self.stack.iter() {
             if Some((*x, *y)) != self.curr {
-                draw_rectangle(
+                canvas.draw_rectangle(
                     *x as f32 * CELL_WIDTH + OFFSET,
                     *y as f32 * CELL_WIDTH + OFFSET,
                     CELL_WIDTH,
@@ -167,7 +220,7 @@ impl Algorithm for Exports {
             }
         }
         if let Some((x, y)) = self.curr {
-            draw_rectangle(
+            canvas.draw_rectangle(
                 x as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 y as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -175,21 +228,21 @@ impl Algorithm for Exports {
                 curr_color,
             );
         }
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
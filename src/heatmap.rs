@@ -0,0 +1,185 @@
+//! How far every cell is from one of them, coloured in. Lining the heat maps of two algorithms up
+//! side by side is the easiest way to see the difference in their textures.
+
+use std::collections::VecDeque;
+
+use macroquad::color::Color;
+
+use crate::{
+    canvas::Canvas,
+    delta_util,
+    generator::Maze,
+    graph::CellGraph,
+    hex_util, polar_util,
+    util::{self, Board, CELL_WIDTH, COLORS, LINE_WIDTH, OFFSET},
+};
+
+/// How many steps each cell is from `from`, or `None` if you can't get there.
+pub fn flood(maze: &Maze, from: (usize, usize)) -> Board<Option<usize>> {
+    let (width, height) = maze.size();
+    let mut distances = Board::new(width, height, None);
+    distances[from.1][from.0] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        let distance = distances[cell.1][cell.0].unwrap();
+        for (x, y) in maze.neighbours(cell) {
+            if distances[y][x].is_none() {
+                distances[y][x] = Some(distance + 1);
+                queue.push_back((x, y));
+            }
+        }
+    }
+    distances
+}
+
+/// The cell furthest from wherever `distances` was flooded from.
+pub fn furthest(distances: &Board<Option<usize>>) -> ((usize, usize), usize) {
+    let mut rv = ((0, 0), 0);
+    for (y, row) in distances.iter().enumerate() {
+        for (x, distance) in row.iter().enumerate() {
+            if let Some(distance) = *distance {
+                if distance > rv.1 {
+                    rv = ((x, y), distance);
+                }
+            }
+        }
+    }
+    rv
+}
+
+pub struct HeatMap {
+    maze: Maze,
+    source: (usize, usize),
+    distances: Board<Option<usize>>,
+    max: usize,
+    /// The longest path anywhere in the maze, from one end to the other.
+    longest: Vec<(usize, usize)>,
+}
+
+impl HeatMap {
+    pub fn new(maze: Maze, source: (usize, usize)) -> Self {
+        let distances = flood(&maze, source);
+
+        // The cell furthest from anywhere is one end of the longest path, and the cell furthest
+        // from that is the other end. Then walk back downhill to get the cells in between.
+        let (end, max) = furthest(&distances);
+        let from_end = flood(&maze, end);
+        let (mut cell, length) = furthest(&from_end);
+        let mut longest = vec![cell];
+        for distance in (0..length).rev() {
+            cell = maze
+                .neighbours(cell)
+                .into_iter()
+                .find(|&(x, y)| from_end[y][x] == Some(distance))
+                .unwrap();
+            longest.push(cell);
+        }
+
+        Self {
+            maze,
+            source,
+            distances,
+            max,
+            longest,
+        }
+    }
+
+    /// The cell under `pos`, to flood from next.
+    pub fn cell_at(&self, pos: (f32, f32)) -> Option<(usize, usize)> {
+        match &self.maze {
+            Maze::Square(grid) => util::cell_from_pos(pos, grid),
+            Maze::Hex(grid) => hex_util::cell_from_pos(pos.0, pos.1, grid),
+            Maze::Delta(grid) => delta_util::cell_from_pos(pos, grid),
+            Maze::Polar(grid) => polar_util::cell_from_pos(pos, grid),
+            Maze::Penrose(grid) => grid.cell_at(pos),
+        }
+    }
+
+    /// The colour for a cell `distance` steps away, from pale yellow nearby to green at the far end.
+    fn color(&self, distance: usize) -> Color {
+        let (near, far) = (COLORS[40], COLORS[17]);
+        let t = distance as f32 / self.max.max(1) as f32;
+        Color::new(
+            near.r + (far.r - near.r) * t,
+            near.g + (far.g - near.g) * t,
+            near.b + (far.b - near.b) * t,
+            0.7,
+        )
+    }
+
+    fn centre(&self, (x, y): (usize, usize)) -> (f32, f32) {
+        match &self.maze {
+            Maze::Hex(grid) => hex_util::center_pixel(x, y, grid.height()),
+            Maze::Delta(_) => delta_util::center_pixel((x, y)),
+            Maze::Polar(grid) => polar_util::center_pixel((x, y), grid),
+            Maze::Penrose(grid) => grid.center((x, y)),
+            _ => (
+                (x as f32 + 0.5) * CELL_WIDTH + OFFSET,
+                (y as f32 + 0.5) * CELL_WIDTH + OFFSET,
+            ),
+        }
+    }
+
+    /// Colour in every cell by how far it is from the source.
+    pub fn draw_distances(&self, canvas: &mut dyn Canvas) {
+        for (y, row) in self.distances.iter().enumerate() {
+            for (x, distance) in row.iter().enumerate() {
+                let Some(distance) = *distance else {
+                    continue;
+                };
+                let color = self.color(distance);
+                match &self.maze {
+                    Maze::Hex(grid) => hex_util::draw_cell(canvas, x, y, grid.height(), 0.0, color),
+                    Maze::Delta(_) => delta_util::draw_cell(canvas, (x, y), 0.0, color),
+                    Maze::Polar(grid) => polar_util::draw_cell(canvas, (x, y), grid, 0.0, color),
+                    Maze::Penrose(grid) => grid.draw_cell(canvas, (x, y), 0.0, color),
+                    _ => util::draw_cell(canvas, x, y, 0.0, color),
+                }
+            }
+        }
+    }
+
+    /// Draw a line along the longest path, and a ring around the source.
+    pub fn draw_longest(&self, canvas: &mut dyn Canvas) {
+        let color = COLORS[13];
+        for pair in self.longest.windows(2) {
+            let ((x1, y1), (x2, y2)) = (self.centre(pair[0]), self.centre(pair[1]));
+            canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH * 1.5, color);
+        }
+        let (x, y) = self.centre(self.source);
+        canvas.draw_poly_lines(x, y, 20, CELL_WIDTH / 3.0, 0.0, LINE_WIDTH, color);
+    }
+
+    /// The whole overlay, walls and all, to go over the top of whatever's in the window.
+    pub fn draw(&self, canvas: &mut dyn Canvas) {
+        self.draw_distances(canvas);
+        match &self.maze {
+            Maze::Square(grid) => util::draw_board(canvas, grid),
+            Maze::Hex(grid) => hex_util::draw_board(canvas, grid),
+            Maze::Delta(grid) => delta_util::draw_board(canvas, grid),
+            Maze::Polar(grid) => polar_util::draw_board(canvas, grid),
+            Maze::Penrose(grid) => grid.draw_board(canvas),
+        }
+        self.draw_longest(canvas);
+    }
+}
+
+#[test]
+fn longest_path() {
+    use crate::{generator::Generator, kruskal, util::Rng};
+
+    let maze = kruskal::Exports::new(12, 8, Rng::new(5)).run();
+    let heat_map = HeatMap::new(maze.clone(), (0, 0));
+    let ends = (heat_map.longest[0], *heat_map.longest.last().unwrap());
+    assert_eq!(
+        furthest(&flood(&maze, ends.0)).1,
+        heat_map.longest.len() - 1
+    );
+    assert_eq!(
+        furthest(&flood(&maze, ends.1)).1,
+        heat_map.longest.len() - 1
+    );
+    for pair in heat_map.longest.windows(2) {
+        assert!(maze.neighbours(pair[0]).contains(&pair[1]));
+    }
+}
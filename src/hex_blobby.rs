@@ -1,13 +1,16 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::{
-    hex_util::{draw_path, set_border, Grid, Playable},
-    util::{Algorithm, ChooseRandom, State as BaseState, COLORS, EMPTY_COLOR},
+    hex_util::{board_size, draw_path, set_border, Grid, Playable},
+    util::{Algorithm, Board, ChooseRandom, Heading, Rng, State as BaseState, COLORS, EMPTY_COLOR},
 };
 
-use crate::hex_util::{draw_board, draw_cell, init_grid, Direction, COLUMNS, ROWS};
+use crate::hex_util::{draw_board, draw_cell, init_grid, Direction};
 
 use enumset::EnumSet;
 use itertools::Itertools;
-use macroquad::{logging as log, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(PartialEq, Eq, Debug)]
@@ -30,17 +33,18 @@ enum Blob {
 #[derive(From)]
 pub struct Exports {
     path: Vec<(usize, usize)>,
-    finished: [[Option<bool>; COLUMNS as usize]; ROWS as usize],
-    grid: [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as usize],
-    stack: Vec<[[Option<Blob>; COLUMNS as usize]; ROWS as usize]>,
+    finished: Board<Option<bool>>,
+    grid: Grid,
+    stack: Vec<Board<Option<Blob>>>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let mut grid = init_grid(EnumSet::all());
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let mut grid = init_grid(width, height, EnumSet::all());
         set_border(&mut grid);
-        let finished = init_grid(false);
+        let finished = init_grid(width, height, false);
 
         let stack = vec![];
         let state = State::Setup;
@@ -52,12 +56,11 @@ impl Exports {
             grid,
             stack,
             state,
+            rng,
         }
     }
 
-    fn choose_starts(
-        board: &[[Option<Blob>; COLUMNS as usize]; ROWS as usize],
-    ) -> [(usize, usize); 2] {
+    fn choose_starts(board: &Board<Option<Blob>>, rng: &mut Rng) -> [(usize, usize); 2] {
         let mut potentials = vec![];
         for (y, row) in board.iter().enumerate() {
             for (x, cell) in row.iter().enumerate() {
@@ -67,29 +70,27 @@ impl Exports {
             }
         }
         let mut rv = [(0usize, 0usize), (0usize, 0usize)];
-        for (b, slot) in potentials.choose_multiple(rv.len()).zip(rv.iter_mut()) {
+        for (b, slot) in potentials.choose_multiple(rv.len(), rng).zip(rv.iter_mut()) {
             *slot = *b;
         }
         // log::info!("Chose {:?} from {:?}", rv, potentials);
         rv
     }
 
-    fn expand_blobs(
-        board: &[[Option<Blob>; COLUMNS as usize]; ROWS as usize],
-    ) -> ([[Option<Blob>; COLUMNS as usize]; ROWS as usize], usize) {
+    fn expand_blobs(board: &Board<Option<Blob>>, rng: &mut Rng) -> (Board<Option<Blob>>, usize) {
+        let (width, height) = (board.width() as i32, board.height() as i32);
         let mut remaining = 0;
-        let mut new_board = *board;
+        let mut new_board = board.clone();
         let mut moved = [false, false];
         for (y, row) in board.iter().enumerate() {
             for (x, cell) in row.iter().enumerate() {
                 if cell == &Some(Blob::None) {
                     let mut potentials: Vec<Direction> = EnumSet::all().iter().collect();
-                    potentials.shuffle();
+                    potentials.shuffle(rng);
                     for direction in potentials {
                         let (new_x, new_y) = direction.next(x as i32, y as i32);
                         // log::info!("{:?} / {:?} -> {:?}", (x,y), direction, (new_x, new_y));
-                        if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32
-                        {
+                        if 0 <= new_x && new_x < width && 0 <= new_y && new_y < height {
                             let (new_x, new_y) = (new_x as usize, new_y as usize);
                             if [Some(Blob::First), Some(Blob::Second)]
                                 .contains(&board[new_y][new_x])
@@ -101,7 +102,7 @@ impl Exports {
                                     moved[1] = true;
                                 }
 
-                                if gen_range(0, 2) == 0 {
+                                if rng.gen_range(0, 2) == 0 {
                                     // Only expand half the time.
                                     new_board[y][x] = board[new_y][new_x];
                                 }
@@ -133,19 +134,31 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "hexblobby",
+    name: "Blobby Hex Recursive Subdivision",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Hex,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Blobby Recursive Division")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         if self.state == State::Setup {
-            self.stack.push(init_grid(Blob::None));
+            self.stack.push(init_grid(width, height, Blob::None));
             self.state = State::Choosing;
             return;
         }
@@ -164,14 +177,14 @@ impl Algorithm for Exports {
         match self.state {
             State::Choosing => {
                 let board = self.stack.last_mut().unwrap();
-                let [a, b] = Self::choose_starts(board);
+                let [a, b] = Self::choose_starts(board, &mut self.rng);
                 board[a.1][a.0] = Some(Blob::First);
                 board[b.1][b.0] = Some(Blob::Second);
                 self.state = State::Expanding;
             }
             State::Expanding => {
                 let board = self.stack.last_mut().unwrap();
-                let (new_board, remaining) = Self::expand_blobs(board);
+                let (new_board, remaining) = Self::expand_blobs(board, &mut self.rng);
                 *board = new_board;
 
                 // log::info!("Expanding: {} remaining…", remaining);
@@ -185,8 +198,8 @@ impl Algorithm for Exports {
                 let mut first_size = 0;
                 let mut second_size = 0;
                 let mut walls = vec![];
-                for x in 0..COLUMNS as usize {
-                    for y in 0..ROWS as usize {
+                for x in 0..width {
+                    for y in 0..height {
                         match board[y][x] {
                             Some(Blob::First) => first_size += 1,
                             Some(Blob::Second) => second_size += 1,
@@ -196,9 +209,9 @@ impl Algorithm for Exports {
                             let direction: Direction = direction;
                             let (new_x, new_y) = direction.next(x as i32, y as i32);
                             if 0 <= new_x
-                                && new_x < COLUMNS as i32
+                                && new_x < width as i32
                                 && 0 <= new_y
-                                && new_y < ROWS as i32
+                                && new_y < height as i32
                             {
                                 let (new_x, new_y) = (new_x as usize, new_y as usize);
                                 match (board[y][x], board[new_y][new_x]) {
@@ -222,7 +235,7 @@ impl Algorithm for Exports {
                     }
                 }
                 // Carve a door in the wall.
-                walls.shuffle();
+                walls.shuffle(&mut self.rng);
                 let (x, y, direction) = walls.pop().unwrap();
                 let (new_x, new_y) = direction.next(x as i32, y as i32);
                 if let Some(cell) = &mut self.grid[y][x] {
@@ -252,7 +265,7 @@ impl Algorithm for Exports {
 
                 // If either of the blobs are big enough, add them to the stack.
                 if first_size > 2 {
-                    let mut new_board = board;
+                    let mut new_board = board.clone();
                     for (y, row) in board.iter().enumerate() {
                         for (x, cell) in row.iter().enumerate() {
                             new_board[y][x] = match cell {
@@ -265,7 +278,7 @@ impl Algorithm for Exports {
                     self.stack.push(new_board);
                 }
                 if second_size > 2 {
-                    let mut new_board = board;
+                    let mut new_board = board.clone();
                     for (y, row) in board.iter().enumerate() {
                         for (x, cell) in row.iter().enumerate() {
                             new_board[y][x] = match cell {
@@ -304,8 +317,30 @@ impl Algorithm for Exports {
         });
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Hex(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
+        let rows = self.grid.height();
 
         let mut none_color = COLORS[1];
         none_color.a = 0.3;
@@ -325,31 +360,27 @@ impl Algorithm for Exports {
                             Some(Blob::Outside) => EMPTY_COLOR,
                             _ => panic!("Out of the grid!"),
                         };
-                        draw_cell(x, y, 0.0, color);
+                        draw_cell(canvas, x, y, rows, 0.0, color);
                     }
                 }
             }
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+        draw_path(canvas, &self.path, rows);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -1,21 +1,20 @@
 use enumset::EnumSet;
-use itertools::Itertools;
-use macroquad::{
-    math::vec2,
-    prelude::{draw_line, draw_poly, Color, ImageFormat},
-    shapes::draw_poly_lines,
-    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
-};
+use macroquad::prelude::Color;
+use serde::{Deserialize, Serialize};
 
-use crate::util::{COLORS, LINE_WIDTH, OFFSET};
+use crate::canvas::Canvas;
+use crate::generator::Maze;
+use crate::graph::{self, Cell, CellGraph};
+use crate::registry::Shape;
+use crate::util::{Board, Heading, Wall, COLORS, LINE_WIDTH, OFFSET};
 
 pub use crate::util::Algorithm;
 
-pub type Grid = [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as usize];
+pub type Grid = Board<Option<EnumSet<Direction>>>;
 
 pub const CELL_WIDTH: f32 = 12.0;
-pub const ROWS: f32 = 32.0;
-pub const COLUMNS: f32 = 68.0;
+pub const ROWS: usize = 32;
+pub const COLUMNS: usize = 68;
 
 pub const WHITE: Color = Color {
     r: 1.0,
@@ -24,7 +23,7 @@ pub const WHITE: Color = Color {
     a: 1.0,
 };
 
-#[derive(EnumSetType, Debug)]
+#[derive(EnumSetType, Debug, Serialize, Deserialize)]
 pub enum Direction {
     NorthEast,
     NorthWest,
@@ -59,37 +58,184 @@ impl Direction {
     }
 }
 
+/// How many steps apart two cells are, ignoring walls. The columns lean, so `(x, y)` work like
+/// axial coordinates.
+pub fn distance((x1, y1): (usize, usize), (x2, y2): (usize, usize)) -> usize {
+    let (dx, dy) = (x2 as isize - x1 as isize, y2 as isize - y1 as isize);
+    (dx.unsigned_abs() + dy.unsigned_abs() + (dx + dy).unsigned_abs()) / 2
+}
+
+/// Every cell that's on the board, row by row.
+pub fn cells<T>(board: &Board<Option<T>>) -> Vec<(usize, usize)> {
+    board
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.is_some())
+                .map(move |(x, _)| (x, y))
+        })
+        .collect()
+}
+
+/// The cells around `(x, y)` that are on the board, and which way each of them is.
+pub fn neighbours<T>(
+    board: &Board<Option<T>>,
+    (x, y): (usize, usize),
+) -> Vec<(Direction, (usize, usize))> {
+    EnumSet::<Direction>::all()
+        .iter()
+        .filter_map(|direction| {
+            let (i, j) = direction.next(x as i32, y as i32);
+            let (i, j) = (usize::try_from(i).ok()?, usize::try_from(j).ok()?);
+            board.get(j)?.get(i)?.as_ref()?;
+            Some((direction, (i, j)))
+        })
+        .collect()
+}
+
+/// Knock down the wall between `(x, y)` and the cell `direction` of it, and return that cell.
+pub fn carve(grid: &mut Grid, (x, y): (usize, usize), direction: Direction) -> (usize, usize) {
+    let (i, j) = direction.next(x as i32, y as i32);
+    let (i, j) = (i as usize, j as usize);
+    grid[y][x] = grid[y][x].map(|cell| cell | direction);
+    grid[j][i] = grid[j][i].map(|cell| cell | direction.opposite());
+    (i, j)
+}
+
+/// Where the robot starts, in the first cell of the top row.
+pub fn start<T>(board: &Board<Option<T>>) -> (usize, usize) {
+    let x = board[0].iter().position(Option::is_some).unwrap();
+    (x, 0)
+}
+
 pub trait Playable: Algorithm {
-    fn get_grid(&self) -> [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as usize];
+    fn get_grid(&self) -> Grid;
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)>;
-    fn cell_from_pos(&self, pos: (f32, f32)) -> Option<(usize, usize)> {
-        let (x, y) = pos;
-        cell_from_pos(x, y, self.get_grid())
-    }
     fn move_to(&mut self, pos: (f32, f32)) {
-        let cursor = self.cell_from_pos(pos);
-
         let grid = self.get_grid();
-        let path = self.get_path_mut();
-        if valid_move(path.last(), cursor, grid) {
-            let cursor = cursor.unwrap();
-            if let Some((index, _)) = path.iter().find_position(|&x| x == &cursor) {
-                path.truncate(index + 1);
-            } else {
-                path.push(cursor);
-            }
+        graph::move_to(&grid, self.get_path_mut(), pos);
+    }
+    /// Take the robot one cell `heading`, unless there's a wall in the way.
+    fn walk(&mut self, heading: Heading) -> bool {
+        let grid = self.get_grid();
+        graph::walk(&grid, self.get_path_mut(), heading)
+    }
+}
+
+impl CellGraph for Grid {
+    const SHAPE: Shape = Shape::Hex;
+
+    fn empty(width: usize, height: usize) -> Self {
+        init_grid(width, height, EnumSet::new())
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        cells(self)
+    }
+
+    fn neighbours(&self, cell: Cell) -> Vec<Cell> {
+        neighbours(self, cell)
+            .into_iter()
+            .map(|(_, cell)| cell)
+            .collect()
+    }
+
+    fn links(&self, (x, y): Cell) -> Vec<Cell> {
+        neighbours(self, (x, y))
+            .into_iter()
+            .filter(|&(direction, _)| self[y][x].is_some_and(|cell| cell.contains(direction)))
+            .map(|(_, cell)| cell)
+            .collect()
+    }
+
+    fn link(&mut self, a: Cell, b: Cell) {
+        carve(self, a, towards(a, b));
+    }
+
+    fn unlink(&mut self, a: Cell, b: Cell) {
+        let direction = towards(a, b);
+        self[a.1][a.0] = self[a.1][a.0].map(|cell| cell - direction);
+        self[b.1][b.0] = self[b.1][b.0].map(|cell| cell - direction.opposite());
+    }
+
+    fn start(&self) -> Option<Cell> {
+        self[0].iter().position(Option::is_some).map(|x| (x, 0))
+    }
+
+    fn goal(&self) -> Option<Cell> {
+        let y = self.height() - 1;
+        self[y].iter().rposition(Option::is_some).map(|x| (x, y))
+    }
+
+    fn step(&self, (x, y): Cell, heading: Heading) -> Option<Cell> {
+        let Heading::Hex(direction) = heading else {
+            return None;
+        };
+        if !self[y][x].is_some_and(|cell| cell.contains(direction)) {
+            return None;
         }
+        // Open sides always lead to another cell, since `set_border` closes the rest.
+        let (i, j) = direction.next(x as i32, y as i32);
+        Some((i as usize, j as usize))
     }
+
+    fn maze(&self) -> Maze {
+        Maze::Hex(self.clone())
+    }
+
+    fn pixel_size(&self) -> (f32, f32) {
+        board_size(self.width(), self.height())
+    }
+
+    fn center(&self, (x, y): Cell) -> (f32, f32) {
+        center_pixel(x, y, self.height())
+    }
+
+    fn cell_at(&self, (x, y): (f32, f32)) -> Option<Cell> {
+        cell_from_pos(x, y, self)
+    }
+
+    fn walls(&self) -> Vec<Wall> {
+        walls(self)
+    }
+
+    fn draw_cell(&self, canvas: &mut dyn Canvas, (x, y): Cell, inset: f32, color: Color) {
+        draw_cell(canvas, x, y, self.height(), inset, color);
+    }
+
+    fn draw_current(&self, canvas: &mut dyn Canvas, (x, y): Cell, color: Color) {
+        draw_cell(canvas, x, y, self.height(), LINE_WIDTH * 1.5, color);
+    }
+
+    fn draw_path(&self, canvas: &mut dyn Canvas, path: &[Cell]) {
+        draw_path(canvas, path, self.height());
+    }
+}
+
+/// Which way `b` is from `a`, when they're next to each other.
+fn towards(a: Cell, b: Cell) -> Direction {
+    let next = (b.0 as i32, b.1 as i32);
+    EnumSet::<Direction>::all()
+        .iter()
+        .find(|direction| direction.next(a.0 as i32, a.1 as i32) == next)
+        .unwrap_or_else(|| panic!("{:?} and {:?} aren't next to each other", a, b))
 }
 
-pub fn init_grid<T: Copy>(value: T) -> [[Option<T>; COLUMNS as usize]; ROWS as usize] {
-    let mut grid = [[Some(value); COLUMNS as usize]; ROWS as usize];
+pub fn init_grid<T: Copy>(width: usize, height: usize, value: T) -> Board<Option<T>> {
+    let mut grid = Board::new(width, height, Some(value));
+    let (columns, rows) = (width as f32, height as f32);
 
     for (j, row) in grid.iter_mut().enumerate() {
         for (i, cell) in row.iter_mut().enumerate() {
             let x = i as f32;
             let y = j as f32;
-            if (x < (ROWS - 1.0 - y) / 2.0) || (x > COLUMNS - (ROWS + y) / 2.0) {
+            if (x < (rows - 1.0 - y) / 2.0) || (x > columns - (rows + y) / 2.0) {
                 *cell = None;
             }
         }
@@ -98,29 +244,19 @@ pub fn init_grid<T: Copy>(value: T) -> [[Option<T>; COLUMNS as usize]; ROWS as u
     grid
 }
 
-pub fn set_border(grid: &mut [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as usize]) {
-    // Make sure the border is filled in.
-    for (j, row) in grid.iter_mut().enumerate() {
-        for (i, cell) in row.iter_mut().enumerate() {
-            if let Some(cell) = cell {
-                if j == 0 {
-                    cell.remove(Direction::NorthEast);
-                    cell.remove(Direction::NorthWest);
-                } else if j == ROWS as usize - 1 {
-                    cell.remove(Direction::SouthEast);
-                    cell.remove(Direction::SouthWest);
-                }
-                if i >= COLUMNS as usize - (ROWS as usize + j).div_ceil(2) {
-                    cell.remove(Direction::East);
-                    if j % 2 == 0 {
-                        cell.remove(Direction::NorthEast);
-                        cell.remove(Direction::SouthEast);
-                    }
-                } else if i <= (ROWS as usize - j) / 2 {
-                    cell.remove(Direction::West);
-                    if j % 2 == 1 {
-                        cell.remove(Direction::NorthWest);
-                        cell.remove(Direction::SouthWest);
+pub fn set_border(grid: &mut Grid) {
+    let (columns, rows) = (grid.width() as i32, grid.height() as i32);
+    // Close off every side that doesn't lead to another cell.
+    for j in 0..rows {
+        for i in 0..columns {
+            for direction in EnumSet::<Direction>::all() {
+                let (x, y) = direction.next(i, j);
+                let inside = (0..columns).contains(&x)
+                    && (0..rows).contains(&y)
+                    && grid[y as usize][x as usize].is_some();
+                if !inside {
+                    if let Some(cell) = &mut grid[j as usize][i as usize] {
+                        cell.remove(direction);
                     }
                 }
             }
@@ -128,12 +264,30 @@ pub fn set_border(grid: &mut [[Option<EnumSet<Direction>>; COLUMNS as usize]; RO
     }
 }
 
-pub fn center_pixel(i: usize, j: usize) -> (f32, f32) {
+/// How far to shift the grid left so that its first column lines up with the margin.
+fn x_shift(rows: usize) -> f32 {
+    CELL_WIDTH * f32::sqrt(3.0) * (rows as f32 - 1.0) / 2.0 - 30.0
+}
+
+/// The size in pixels of a hex board, including the margins.
+pub fn board_size(width: usize, height: usize) -> (f32, f32) {
+    let mut size = (0.0f32, 0.0f32);
+    for (j, row) in init_grid(width, height, ()).iter().enumerate() {
+        for (i, _) in row.iter().enumerate().filter(|(_, cell)| cell.is_some()) {
+            let (x, y) = center_pixel(i, j, height);
+            size.0 = size.0.max(x + CELL_WIDTH + OFFSET);
+            size.1 = size.1.max(y + CELL_WIDTH + OFFSET);
+        }
+    }
+    size
+}
+
+pub fn center_pixel(i: usize, j: usize, rows: usize) -> (f32, f32) {
     let i = i as f32;
     let j = j as f32;
     let sqrt_3 = f32::sqrt(3.0);
     let mut x = CELL_WIDTH * (sqrt_3 * i + sqrt_3 / 2.0 * j);
-    x -= 292.0;
+    x -= x_shift(rows);
 
     let mut y = CELL_WIDTH * 3.0 / 2.0 * j;
     y += 12.0;
@@ -149,7 +303,6 @@ pub fn pointy_hex_corner(x: f32, y: f32, i: usize, inset: f32) -> (f32, f32) {
 }
 
 fn hex_round(x: f32, y: f32) -> (usize, usize) {
-    print!("  hex_round({}, {})", x, y);
     let z = -x - y;
     let mut rx = f32::round(x);
     let mut ry = f32::round(y);
@@ -165,19 +318,17 @@ fn hex_round(x: f32, y: f32) -> (usize, usize) {
         ry = -rx - rz;
     }
 
-    println!(" -> ({}, {})", rx, ry);
     (rx as usize, ry as usize)
 }
 
-pub fn cell_from_pos(x: f32, y: f32, grid: Grid) -> Option<(usize, usize)> {
-    println!("cell_from_pos({}, {})", x, y);
+pub fn cell_from_pos(x: f32, y: f32, grid: &Grid) -> Option<(usize, usize)> {
     if x < 0.0 || y < 0.0 {
         return None;
     }
 
     let mut x = x;
     x += CELL_WIDTH + OFFSET;
-    x += 292.0;
+    x += x_shift(grid.height());
 
     let mut y = y;
     y -= CELL_WIDTH + OFFSET;
@@ -188,48 +339,28 @@ pub fn cell_from_pos(x: f32, y: f32, grid: Grid) -> Option<(usize, usize)> {
     let r = (2.0 / 3.0 * y) / CELL_WIDTH;
     let (x, y) = hex_round(q - 1.0, r);
 
-    if x >= COLUMNS as usize || y >= ROWS as usize {
+    if x >= grid.width() || y >= grid.height() {
         return None;
     }
     grid[y][x]?;
-    println!("  -> cell ({}, {})", x, y);
     Some((x, y))
 }
 
-pub fn draw_path(path: &[(usize, usize)]) {
+pub fn draw_path(canvas: &mut dyn Canvas, path: &[(usize, usize)], rows: usize) {
     let mut color = COLORS[10];
     if let Some((&(x, y), rest)) = path.split_last() {
         color.a = 0.6;
-        draw_little_robot(x, y, color);
+        draw_little_robot(canvas, x, y, rows, color);
         color.a = 0.5;
         for &(x, y) in rest {
-            draw_cell(x, y, 0.0, color)
+            draw_cell(canvas, x, y, rows, 0.0, color)
         }
     }
 }
 
-pub fn valid_move(
-    start: Option<&(usize, usize)>,
-    next: Option<(usize, usize)>,
-    grid: [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as usize],
-) -> bool {
-    if let Some(&(x1, y1)) = start {
-        if let Some((x2, y2)) = next {
-            if let Some(cell) = grid[y1][x1] {
-                for direction in cell {
-                    if direction.next(x1 as i32, y1 as i32) == (x2 as i32, y2 as i32) {
-                        return true;
-                    }
-                }
-            }
-        }
-    }
-    false
-}
-
-fn draw_little_robot(i: usize, j: usize, color: Color) {
-    let (x, y) = center_pixel(i, j);
-    draw_poly_lines(x, y, 6, CELL_WIDTH - 4.0, 90.0, 4.0, color);
+fn draw_little_robot(canvas: &mut dyn Canvas, i: usize, j: usize, rows: usize, color: Color) {
+    let (x, y) = center_pixel(i, j, rows);
+    canvas.draw_poly_lines(x, y, 6, CELL_WIDTH - 4.0, 90.0, 4.0, color);
 
     let inset = 0.0;
     let x = x + inset - CELL_WIDTH / 2.0;
@@ -237,29 +368,26 @@ fn draw_little_robot(i: usize, j: usize, color: Color) {
     let w = CELL_WIDTH - inset * 2.0;
     let h = CELL_WIDTH - inset * 2.0;
 
-    let image = Texture2D::from_file_with_format(
-        include_bytes!("../static/little_guy.png"),
-        Some(ImageFormat::Png),
-    );
-    draw_texture_ex(
-        &image,
-        x,
-        y,
-        WHITE,
-        DrawTextureParams {
-            dest_size: Some(vec2(w, h)),
-            ..Default::default()
-        },
-    );
+    canvas.draw_robot(x, y, w, h);
 }
 
-pub fn draw_cell(i: usize, j: usize, inset: f32, color: Color) {
-    let (x, y) = center_pixel(i, j);
+pub fn draw_cell(
+    canvas: &mut dyn Canvas,
+    i: usize,
+    j: usize,
+    rows: usize,
+    inset: f32,
+    color: Color,
+) {
+    let (x, y) = center_pixel(i, j, rows);
     // This totally feels like cheating…
-    draw_poly(x, y, 6, CELL_WIDTH - inset, 90.0, color);
+    canvas.draw_poly(x, y, 6, CELL_WIDTH - inset, 90.0, color);
 }
 
-pub fn draw_board(grid: [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as usize]) {
+/// The walls of a hex board, as line segments, leaving the entrance and exit open.
+pub fn walls(grid: &Grid) -> Vec<Wall> {
+    let mut walls = vec![];
+    let rows = grid.height();
     let mut printed_first = false;
     for (j, row) in grid.iter().enumerate() {
         for (i, cell) in row.iter().enumerate() {
@@ -267,7 +395,7 @@ pub fn draw_board(grid: [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as
                 continue;
             }
             let cell = cell.unwrap();
-            let (x, y) = center_pixel(i, j);
+            let (x, y) = center_pixel(i, j, rows);
 
             let nw = pointy_hex_corner(x, y, 4, 0.0);
             let n = pointy_hex_corner(x, y, 5, 0.0);
@@ -277,42 +405,49 @@ pub fn draw_board(grid: [[Option<EnumSet<Direction>>; COLUMNS as usize]; ROWS as
             let s = pointy_hex_corner(x, y, 2, 0.0);
             let sw = pointy_hex_corner(x, y, 3, 0.0);
 
-            let skip_last = j + 1 == ROWS as usize && row[i + 1].is_none();
+            let skip_last = j + 1 == rows && row.get(i + 1).is_none_or(|cell| cell.is_none());
 
             //Figure out which lines to draw.
             if !cell.contains(Direction::NorthEast) {
-                draw_line(n.0, n.1, ne.0, ne.1, LINE_WIDTH, COLORS[0]);
+                walls.push((n, ne));
             }
             if !cell.contains(Direction::East) && !skip_last {
-                draw_line(ne.0, ne.1, se.0, se.1, LINE_WIDTH, COLORS[0]);
+                walls.push((ne, se));
             }
             if !cell.contains(Direction::SouthEast) {
-                draw_line(se.0, se.1, s.0, s.1, LINE_WIDTH, COLORS[0]);
+                walls.push((se, s));
             }
             if !cell.contains(Direction::SouthWest) {
-                draw_line(s.0, s.1, sw.0, sw.1, LINE_WIDTH, COLORS[0]);
+                walls.push((s, sw));
             }
             if !cell.contains(Direction::West) && printed_first {
-                draw_line(sw.0, sw.1, nw.0, nw.1, LINE_WIDTH, COLORS[0]);
+                walls.push((sw, nw));
             }
             if !cell.contains(Direction::NorthWest) {
-                draw_line(nw.0, nw.1, n.0, n.1, LINE_WIDTH, COLORS[0]);
+                walls.push((nw, n));
             }
             printed_first = true;
         }
     }
+    walls
+}
+
+pub fn draw_board(canvas: &mut dyn Canvas, grid: &Grid) {
+    for ((x1, y1), (x2, y2)) in walls(grid) {
+        canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH, COLORS[0]);
+    }
 }
 
 #[test]
 fn a() {
-    let grid = init_grid(EnumSet::new());
+    let grid = init_grid(COLUMNS, ROWS, EnumSet::new());
     let (i, j) = (16, 0);
-    let (x, y) = center_pixel(i, j);
+    let (x, y) = center_pixel(i, j, ROWS);
 
-    assert_eq!(cell_from_pos(x, y, grid), Some((i, j)));
+    assert_eq!(cell_from_pos(x, y, &grid), Some((i, j)));
 
     let (i, j) = (15, 0);
-    let (x, y) = center_pixel(i, j);
+    let (x, y) = center_pixel(i, j, ROWS);
 
-    assert_eq!(cell_from_pos(x, y, grid), None);
+    assert_eq!(cell_from_pos(x, y, &grid), None);
 }
@@ -0,0 +1,163 @@
+//! Going backwards and forwards through a maze as it gets built.
+//!
+//! Generators only ever go forwards, and everything they keep track of along the way (recursive
+//! division's stack, Eller's sets, Wilson's walk, and so on) lives inside them. But they're all
+//! built from a seed, so the same seed and variant always take the same steps. That means the
+//! only thing that needs recording is how many steps have been taken, and going back to an
+//! earlier one is building the maze again and replaying it up to there, which brings back the
+//! overlays along with the grid. Recording the changes instead would mean every generator
+//! exposing everything it keeps track of, and how to undo it.
+//!
+//! Replaying costs a step for every step up to the one being gone back to, which adds up on big
+//! boards, so it happens a few steps at a time with [`History::replay`], the same way finishing a
+//! maze does, and the window keeps drawing while it catches up.
+
+use crate::{
+    generator::{Maze, State},
+    registry::Entry,
+    util::{Algorithm, Rng},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct History {
+    /// How many steps the algorithm has taken to get where it is now.
+    step: usize,
+    /// The most steps it's taken, which is as far forwards as it can go without making new ones.
+    furthest: usize,
+    /// The step a seek is still replaying up to, if it hasn't got there yet.
+    target: Option<usize>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn furthest(&self) -> usize {
+        self.furthest
+    }
+
+    /// Count another step, after calling the algorithm's `update`.
+    pub fn record(&mut self) {
+        self.step += 1;
+        self.furthest = self.furthest.max(self.step);
+    }
+
+    /// The step it's seeking, if it's still on its way there.
+    pub fn target(&self) -> Option<usize> {
+        self.target
+    }
+
+    /// Start taking `algorithm` to `step`, as long as it's been there before, for `replay` to
+    /// finish. Going forwards just keeps updating it, but going backwards has to start again from
+    /// the beginning, with a fresh copy from `entry`.
+    pub fn seek(&mut self, entry: &Entry, algorithm: &mut Box<dyn Algorithm>, step: usize) {
+        let step = step.min(self.furthest);
+        if step < self.step {
+            *algorithm = rebuild(entry, algorithm.as_ref());
+            self.step = 0;
+        }
+        self.target = Some(step);
+    }
+
+    /// Take one more step towards the step being sought. Returns false once it's there.
+    pub fn replay(&mut self, algorithm: &mut dyn Algorithm) -> bool {
+        match self.target {
+            Some(target) if self.step < target && algorithm.get_state() != State::Done => {
+                algorithm.update();
+                self.step += 1;
+                true
+            }
+            _ => {
+                self.target = None;
+                false
+            }
+        }
+    }
+}
+
+/// The same maze as `algorithm`, back before its first step.
+fn rebuild(entry: &Entry, algorithm: &dyn Algorithm) -> Box<dyn Algorithm> {
+    let values = entry.parse_or_default(&algorithm.get_variant());
+    let (width, height) = match algorithm.get_maze() {
+        // Penrose pieces are numbered in one long row, so ask for the board they were cut from.
+        Maze::Penrose(grid) => (grid.width, grid.height),
+        maze => maze.size(),
+    };
+    entry.build(
+        &values,
+        (Some(width), Some(height)),
+        Rng::new(algorithm.get_seed()),
+    )
+}
+
+#[test]
+fn rewinding_restores_every_algorithm() {
+    use crate::{registry::ALGORITHMS, util::WHITE};
+
+    // Compare the drawings, since those have the overlays on them as well as the grid.
+    let draw = |algorithm: &dyn Algorithm| {
+        let (width, height) = algorithm.get_size();
+        let mut raster = crate::raster::Raster::new(width, height, WHITE);
+        algorithm.draw(&mut raster);
+        raster.to_rgba()
+    };
+
+    for entry in &ALGORITHMS {
+        let mut algorithm = entry.build(&entry.defaults(), (Some(6), Some(4)), Rng::new(7));
+        let mut history = History::new();
+        let mut drawings = vec![];
+        for _ in 0..12 {
+            if history.step().is_multiple_of(4) {
+                drawings.push(draw(algorithm.as_ref()));
+            }
+            if algorithm.get_state() == State::Done {
+                break;
+            }
+            algorithm.update();
+            history.record();
+        }
+        for (i, drawing) in drawings.iter().enumerate().rev() {
+            history.seek(entry, &mut algorithm, i * 4);
+            while history.replay(algorithm.as_mut()) {}
+            assert_eq!(history.step(), i * 4, "{}", entry.id);
+            assert!(
+                draw(algorithm.as_ref()) == *drawing,
+                "{} at {}",
+                entry.id,
+                i * 4
+            );
+        }
+        history.seek(entry, &mut algorithm, usize::MAX);
+        while history.replay(algorithm.as_mut()) {}
+        assert_eq!(history.step(), history.furthest(), "{}", entry.id);
+    }
+}
+
+#[test]
+fn slider_seeks_reach_the_history() {
+    use crate::{registry::ALGORITHMS, util::Requests};
+
+    let entry = &ALGORITHMS[0];
+    let mut algorithm = entry.build(&entry.defaults(), (Some(6), Some(4)), Rng::new(7));
+    let mut history = History::new();
+    for _ in 0..8 {
+        algorithm.update();
+        history.record();
+    }
+
+    // The window checks for a finish before it checks for a seek, every frame, so checking one
+    // mustn't throw the other away.
+    let requests = Requests::default();
+    requests.seek(3);
+    assert!(!requests.take_finish());
+    let step = requests.take_seek().expect("the seek got lost");
+    history.seek(entry, &mut algorithm, step);
+    while history.replay(algorithm.as_mut()) {}
+    assert_eq!(history.step(), 3);
+    assert_eq!(requests.take_seek(), None);
+}
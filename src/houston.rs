@@ -1,5 +1,8 @@
 use crate::aldous_broder::Exports as aldous_broder;
-use crate::util::{Algorithm, Grid, Playable, State as BaseState};
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
+use crate::util::{board_size, Algorithm, Grid, Heading, Playable, Rng, State as BaseState};
 use crate::wilson::Exports as wilson;
 use macroquad::logging as log;
 use maze_utils::From;
@@ -17,27 +20,42 @@ pub struct Exports {
     aldous_broder: aldous_broder,
     state: State,
     wilson: wilson,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let aldous_broder = aldous_broder::new(true);
-        let wilson = wilson::new(false);
+    pub fn new(width: usize, height: usize, mut rng: Rng) -> Self {
+        // The faster Aldous-Broder skips walking straight back, which skews the mazes it makes,
+        // so stick to the real thing to keep every maze equally likely.
+        let aldous_broder = aldous_broder::new(false, width, height, rng.fork());
+        let wilson = wilson::new(false, width, height, rng.fork());
         let state = State::Setup;
         Self {
             aldous_broder,
             state,
             wilson,
+            rng,
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "houston",
+    name: "Houston",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Houston")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let grid = self.get_grid();
+        let rng = self.rng.fork();
+        self.from(Exports::new(grid.width(), grid.height(), rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
@@ -67,14 +85,6 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        match self.state {
-            State::RunningAldousBroder => self.aldous_broder.draw(),
-            State::RunningWilson | State::Done => self.wilson.draw(),
-            _ => {}
-        }
-    }
-
     fn get_state(&self) -> BaseState {
         match &self.state {
             State::Setup => BaseState::Setup,
@@ -83,9 +93,35 @@ impl Algorithm for Exports {
         }
     }
 
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.get_grid())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        let grid = self.get_grid();
+        board_size(grid.width(), grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        match self.state {
+            State::RunningAldousBroder => self.aldous_broder.draw(canvas),
+            State::RunningWilson | State::Done => self.wilson.draw(canvas),
+            _ => {}
+        }
+    }
+
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
@@ -1,9 +1,12 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State as BaseState,
-    CELL_WIDTH, COLORS, COLUMNS, FIELD_COLOR, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State as BaseState, CELL_WIDTH, COLORS, FIELD_COLOR, LINE_WIDTH, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(PartialEq, Eq, Debug)]
@@ -22,11 +25,12 @@ pub struct Exports {
     grid: Grid,
     scan_line: Option<usize>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
 
         Self {
             path: vec![],
@@ -35,25 +39,38 @@ impl Exports {
             grid,
             scan_line: None,
             state: State::Setup,
+            rng,
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "huntandkill",
+    name: "Hunt and Kill",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Hunt and Kill")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         match self.state {
             State::Setup => {
-                let x = gen_range(0, COLUMNS as usize);
-                let y = gen_range(0, ROWS as usize);
+                let x = self.rng.gen_range(0, width);
+                let y = self.rng.gen_range(0, height);
                 self.curr = Some((x, y));
 
                 self.state = State::Walking;
@@ -63,7 +80,7 @@ impl Algorithm for Exports {
                 let (x, y) = self.curr.unwrap();
                 let mut potentials: Vec<Direction> = self.grid[y][x].complement().iter().collect();
                 // log::info!("({},{}) / {:?}", x, y, potentials);
-                potentials.shuffle();
+                potentials.shuffle(&mut self.rng);
                 while let Some(direction) = potentials.pop() {
                     let (new_x, new_y) = match direction {
                         Direction::North => (x as i32, y as i32 - 1),
@@ -72,7 +89,7 @@ impl Algorithm for Exports {
                         Direction::West => (x as i32 - 1, y as i32),
                     };
                     // log::info!("{:?} / {:?} -> {:?}", (x,y), direction, (new_x, new_y));
-                    if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32 {
+                    if 0 <= new_x && new_x < width as i32 && 0 <= new_y && new_y < height as i32 {
                         let (new_x, new_y) = (new_x as usize, new_y as usize);
                         if self.grid[new_y][new_x] != EnumSet::new() {
                             continue;
@@ -97,31 +114,31 @@ impl Algorithm for Exports {
                 // log::info!("Starting from {:?}", self.scan_line);
                 let y = self.scan_line.unwrap();
                 let mut found_empty_cell = false;
-                for x in 0..COLUMNS as usize {
+                for x in 0..width {
                     if self.grid[y][x] == EnumSet::empty() {
                         found_empty_cell = true;
                         let mut neighbours = vec![];
                         if y > 0 && self.grid[y - 1][x] != EnumSet::empty() {
                             neighbours.push(Direction::North);
                         }
-                        if x < COLUMNS as usize - 1 && self.grid[y][x + 1] != EnumSet::empty() {
+                        if x < width - 1 && self.grid[y][x + 1] != EnumSet::empty() {
                             neighbours.push(Direction::East);
                         }
-                        if y < ROWS as usize - 1 && self.grid[y + 1][x] != EnumSet::empty() {
+                        if y < height - 1 && self.grid[y + 1][x] != EnumSet::empty() {
                             neighbours.push(Direction::South);
                         }
                         if x > 0 && self.grid[y][x - 1] != EnumSet::empty() {
                             neighbours.push(Direction::West);
                         }
 
-                        if let Some(direction) = neighbours.choose() {
+                        if let Some(direction) = neighbours.choose(&mut self.rng) {
                             potentials.push((x, direction));
                         }
                     }
                 }
 
                 if potentials.is_empty() {
-                    if y < ROWS as usize - 1 {
+                    if y < height - 1 {
                         // Move to the next line…
                         self.scan_line = Some(y + 1);
                         if !found_empty_cell {
@@ -139,7 +156,7 @@ impl Algorithm for Exports {
                 }
 
                 // Otherwise, pick one of the potentials, and go from there!
-                let (x, direction) = potentials.choose().unwrap();
+                let (x, direction) = potentials.choose(&mut self.rng).unwrap();
 
                 let (new_x, new_y) = match direction {
                     Direction::North => (x as i32, y as i32 - 1),
@@ -159,17 +176,39 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
+        let columns = self.grid.width() as f32;
 
         let curr_color = COLORS[1];
         let mut cell_color = COLORS[1];
         cell_color.a = 0.3;
 
-        for x in 0..COLUMNS as usize {
-            for y in 0..ROWS as usize {
+        for x in 0..self.grid.width() {
+            for y in 0..self.grid.height() {
                 if self.grid[y][x] == EnumSet::empty() {
-                    draw_rectangle(
+                    canvas.draw_rectangle(
                         x as f32 * CELL_WIDTH + OFFSET,
                         y as f32 * CELL_WIDTH + OFFSET,
                         CELL_WIDTH,
@@ -180,7 +219,7 @@ impl Algorithm for Exports {
             }
         }
         if let Some((x, y)) = self.curr {
-            draw_rectangle(
+            canvas.draw_rectangle(
                 x as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 y as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -189,44 +228,40 @@ impl Algorithm for Exports {
             );
         }
         if let Some(line) = self.scan_line {
-            draw_rectangle(
+            canvas.draw_rectangle(
                 OFFSET,
                 line as f32 * CELL_WIDTH + OFFSET,
-                COLUMNS * CELL_WIDTH,
+                columns * CELL_WIDTH,
                 CELL_WIDTH,
                 cell_color,
             );
         }
 
         if self.state != State::Done {
-            draw_rectangle(
+            canvas.draw_rectangle(
                 OFFSET,
                 self.first_empty_line as f32 * CELL_WIDTH + OFFSET,
-                COLUMNS * CELL_WIDTH,
+                columns * CELL_WIDTH,
                 CELL_WIDTH,
                 cell_color,
             );
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
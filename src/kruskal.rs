@@ -1,9 +1,12 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State, CELL_WIDTH,
-    COLORS, COLUMNS, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State, CELL_WIDTH, COLORS, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(From)]
@@ -11,15 +14,16 @@ pub struct Exports {
     path: Vec<(usize, usize)>,
     edges: Vec<(usize, usize, Direction)>,
     grid: Grid,
-    parents: [[Option<(usize, usize)>; COLUMNS as usize]; ROWS as usize],
+    parents: Board<Option<(usize, usize)>>,
     roots: Vec<(usize, usize, usize)>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        let parents = [[None; COLUMNS as usize]; ROWS as usize];
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
+        let parents = Board::new(width, height, None);
 
         Self {
             path: vec![],
@@ -28,6 +32,7 @@ impl Exports {
             parents,
             roots: vec![],
             state: State::Setup,
+            rng,
         }
     }
 
@@ -41,20 +46,31 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "kruskal",
+    name: "Kruskal",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Kruskal")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
         if self.state == State::Setup {
-            for x in 0..COLUMNS as usize {
-                for y in 0..ROWS as usize {
+            for x in 0..self.grid.width() {
+                for y in 0..self.grid.height() {
                     if y > 0 {
                         self.edges.push((x, y, Direction::North));
                     }
@@ -63,7 +79,7 @@ impl Algorithm for Exports {
                     }
                 }
             }
-            self.edges.shuffle();
+            self.edges.shuffle(&mut self.rng);
 
             self.state = State::Running;
             return;
@@ -139,12 +155,29 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         if self.state == State::Running {
-            for x in 0..COLUMNS as usize {
-                for y in 0..ROWS as usize {
+            for x in 0..self.grid.width() {
+                for y in 0..self.grid.height() {
                     if self.grid[y][x] != EnumSet::empty() {
                         let root = self.find_root(x, y);
                         let index = self
@@ -154,7 +187,7 @@ impl Algorithm for Exports {
                             .unwrap();
                         let mut color = COLORS[index % COLORS.len()];
                         color.a = 0.5;
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -165,21 +198,21 @@ impl Algorithm for Exports {
                 }
             }
         }
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -1,31 +1,70 @@
-extern crate proc_macro;
+//! Maze generators, along with the bits needed to draw them and walk through them.
+//!
+//! Every algorithm implements [`Generator`](generator::Generator), which doesn't need a window,
+//! so a maze can be built from anywhere:
+//!
+//! ```
+//! use mazes::{
+//!     generator::{Generator, Maze},
+//!     kruskal,
+//!     util::Rng,
+//! };
+//!
+//! let mut kruskal = kruskal::Exports::new(10, 10, Rng::new(1));
+//! if let Maze::Square(grid) = kruskal.run() {
+//!     assert_eq!((grid.width(), grid.height()), (10, 10));
+//! }
+//! ```
+//!
+//! The [`Algorithm`](util::Algorithm) half adds the drawing on top, and needs a macroquad window.
 
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput};
+pub mod aldous_broder;
+pub mod bench;
+pub mod binarytree;
+pub mod blobby;
+pub mod canvas;
+pub mod delta_util;
+pub mod eller;
+pub mod generator;
+pub mod graph;
+pub mod graph_aldous_broder;
+pub mod graph_eller;
+pub mod graph_growingtree;
+pub mod graph_huntandkill;
+pub mod graph_kruskal;
+pub mod graph_prim;
+pub mod graph_wilson;
+pub mod growingtree;
+pub mod heatmap;
+pub mod hex_blobby;
+pub mod hex_util;
+pub mod history;
+pub mod houston;
+pub mod huntandkill;
+pub mod kruskal;
+pub mod origin_shift;
+pub mod parallel;
+pub mod params;
+pub mod penrose;
+pub mod polar_util;
+pub mod prim;
+pub mod raster;
+pub mod recdiv;
+pub mod record;
+pub mod registry;
+pub mod saved;
+pub mod sidewinder;
+pub mod solver;
+pub mod stats;
+pub mod svg;
+pub mod text;
+pub mod util;
+pub mod wilson;
 
-#[proc_macro_derive(From)]
-pub fn derive(input: TokenStream) -> TokenStream {
-    // Parse the input tokens into a syntax tree
-    let input = parse_macro_input!(input as DeriveInput);
+extern crate derive_more;
+extern crate itertools;
 
-    let ident = input.ident;
-    let mut fields = vec![];
-    if let Data::Struct(data) = input.data {
-        for field in data.fields {
-            let ident = field.ident;
-            fields.push(quote! {self.#ident = other.#ident;});
-        }
-    }
-    // Build the output, possibly using quasi-quotation
-    let expanded = quote! {
-        impl #ident {
-            fn from(&mut self, other: Self) {
-                #(#fields)*
-            }
-        }
-    };
-
-    // Hand the output tokens back to the compiler
-    TokenStream::from(expanded)
-}
+#[macro_use]
+extern crate enumset;
+#[macro_use]
+extern crate lazy_static;
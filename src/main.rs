@@ -1,53 +1,100 @@
-mod aldous_broder;
-mod binarytree;
-mod blobby;
-mod eller;
-mod growingtree;
-mod hex_blobby;
-mod hex_parallel;
-mod hex_util;
-mod houston;
-mod huntandkill;
-mod kruskal;
-mod origin_shift;
-mod parallel;
-mod penrose;
-mod prim;
-mod recdiv;
-mod sidewinder;
-mod wilson;
-
-// extern crate maze_utils;
-extern crate derive_more;
-extern crate itertools;
-
-mod util;
-
 #[cfg(not(target_arch = "wasm32"))]
 mod desktop_util;
 #[cfg(target_arch = "wasm32")]
 mod web_util;
 
-#[macro_use]
-extern crate enumset;
-#[macro_use]
-extern crate lazy_static;
+#[cfg(not(target_arch = "wasm32"))]
+use desktop_util::Desktop as RealArgs;
+#[cfg(target_arch = "wasm32")]
+use web_util::Web as RealArgs;
 
 use macroquad::{
+    camera::{set_camera, Camera2D},
     logging as log,
+    math::Rect,
     miniquad::date::now,
     prelude::mouse_position,
     prelude::{
         get_frame_time, is_key_down, is_key_pressed, is_mouse_button_pressed, Conf, KeyCode,
         MouseButton,
     },
-    rand,
-    window::{clear_background, next_frame},
+    window::{clear_background, next_frame, screen_height, screen_width},
 };
 
-use util::{Algorithm, Args, RealArgs, WHITE};
+use mazes::{
+    canvas::Screen,
+    delta_util,
+    generator::{Generator, Maze},
+    heatmap::HeatMap,
+    hex_util,
+    history::History,
+    params::Values,
+    polar_util, registry, solver,
+    util::{Algorithm, Args, Direction, Heading, Rng, Speed, State, WHITE},
+};
+
+/// The keys that walk the robot around a square maze.
+const SQUARE_KEYS: [(KeyCode, Direction); 8] = [
+    (KeyCode::Up, Direction::North),
+    (KeyCode::W, Direction::North),
+    (KeyCode::Right, Direction::East),
+    (KeyCode::D, Direction::East),
+    (KeyCode::Down, Direction::South),
+    (KeyCode::S, Direction::South),
+    (KeyCode::Left, Direction::West),
+    (KeyCode::A, Direction::West),
+];
+
+/// The keys that walk the robot around a hex maze. The rows of a keyboard are staggered the same
+/// way the rows of hexes are, so the six keys around S point the right way.
+const HEX_KEYS: [(KeyCode, hex_util::Direction); 8] = [
+    (KeyCode::W, hex_util::Direction::NorthWest),
+    (KeyCode::E, hex_util::Direction::NorthEast),
+    (KeyCode::A, hex_util::Direction::West),
+    (KeyCode::Left, hex_util::Direction::West),
+    (KeyCode::D, hex_util::Direction::East),
+    (KeyCode::Right, hex_util::Direction::East),
+    (KeyCode::Z, hex_util::Direction::SouthWest),
+    (KeyCode::X, hex_util::Direction::SouthEast),
+];
 
-use crate::util::State;
+/// The keys that walk the robot around a delta maze. They're the same as on a square maze, except
+/// that each triangle only has one of up and down.
+const DELTA_KEYS: [(KeyCode, delta_util::Direction); 8] = [
+    (KeyCode::Up, delta_util::Direction::North),
+    (KeyCode::W, delta_util::Direction::North),
+    (KeyCode::Right, delta_util::Direction::East),
+    (KeyCode::D, delta_util::Direction::East),
+    (KeyCode::Down, delta_util::Direction::South),
+    (KeyCode::S, delta_util::Direction::South),
+    (KeyCode::Left, delta_util::Direction::West),
+    (KeyCode::A, delta_util::Direction::West),
+];
+
+/// The keys that walk the robot around a polar maze. Up goes outward and down goes inward, wherever
+/// the robot is, and where a cell has two cells outside it, up goes to the left one and E to the
+/// right one.
+const POLAR_KEYS: [(KeyCode, polar_util::Direction); 9] = [
+    (KeyCode::Up, polar_util::Direction::Outward),
+    (KeyCode::W, polar_util::Direction::Outward),
+    (KeyCode::E, polar_util::Direction::OutwardClockwise),
+    (KeyCode::Right, polar_util::Direction::Clockwise),
+    (KeyCode::D, polar_util::Direction::Clockwise),
+    (KeyCode::Down, polar_util::Direction::Inward),
+    (KeyCode::S, polar_util::Direction::Inward),
+    (KeyCode::Left, polar_util::Direction::Anticlockwise),
+    (KeyCode::A, polar_util::Direction::Anticlockwise),
+];
+
+/// Which way the first of `keys` that was just pressed goes.
+fn pressed<T: Copy>(keys: &[(KeyCode, T)]) -> Option<T> {
+    keys.iter()
+        .find(|(key, _)| is_key_pressed(*key))
+        .map(|&(_, direction)| direction)
+}
+
+/// How many seconds of each frame to spend finishing the maze off, when skipping to the end.
+const FINISH_BUDGET: f64 = 0.02;
 
 fn window_conf() -> Conf {
     Conf {
@@ -62,21 +109,56 @@ fn window_conf() -> Conf {
 
 struct MyGame {
     algorithm: Box<dyn Algorithm>,
+    /// Where `algorithm` is in the registry, so that N and P know where to go next.
+    entry: usize,
+    /// Once the maze is done, this walks through it.
+    solver: Option<solver::Exports>,
+    /// How far each cell is from the start, or wherever was clicked last.
+    heat_map: Option<HeatMap>,
     args: RealArgs,
     update_timer: f32,
+    speed: Speed,
     paused: bool,
+    /// Whether to run flat out until everything's done.
+    finishing: bool,
+    /// How far through building the maze we are, to go back and forth through it.
+    history: History,
+    /// Where the mouse was, so the robot only follows it when it moves, and not while it's
+    /// being walked around with the keys.
+    mouse: (f32, f32),
 }
 
 impl MyGame {
     pub fn new(algorithm: Box<dyn Algorithm>, args: RealArgs) -> MyGame {
+        let entry = registry::position(&args.get_algorithm()).unwrap_or_default();
+        let speed = args.get_speed();
         MyGame {
             algorithm,
+            entry,
+            solver: None,
+            heat_map: None,
             args,
             update_timer: 0.0,
+            speed,
             paused: false,
+            finishing: false,
+            history: History::new(),
+            mouse: mouse_position(),
         }
     }
 
+    fn camera(&self) -> Camera2D {
+        // Scale the board to fit the window, keeping the cells in proportion.
+        let (width, height) = self.algorithm.get_size();
+        let scale = f32::min(screen_width() / width, screen_height() / height);
+        Camera2D::from_display_rect(Rect::new(
+            0.0,
+            0.0,
+            screen_width() / scale,
+            screen_height() / scale,
+        ))
+    }
+
     fn handle_events(&mut self) -> bool {
         if is_key_down(KeyCode::Q) && is_key_down(KeyCode::LeftSuper)
             || is_key_down(KeyCode::RightSuper)
@@ -87,86 +169,380 @@ impl MyGame {
         if is_key_pressed(KeyCode::R) {
             // R was pressed, so restart.
             self.paused = false;
-            log::info!("Refreshing with {}", self.args.get_variant());
-            self.algorithm.re_init(self.args.get_variant());
+            self.finishing = false;
+            self.history = History::new();
+            self.solver = None;
+            self.heat_map = None;
+            // Keep whichever variant is running, since N, P and V might have changed it.
+            let variant = self.algorithm.get_variant();
+            self.algorithm.re_init(variant.clone());
+            log::info!(
+                "Refreshing with {}, seed {}",
+                variant,
+                self.algorithm.get_seed()
+            );
+        }
+
+        if is_key_pressed(KeyCode::N) || is_key_pressed(KeyCode::P) {
+            // N or P was pressed, so switch to the next or previous algorithm.
+            let count = registry::ALGORITHMS.len();
+            let entry = if is_key_pressed(KeyCode::N) {
+                (self.entry + 1) % count
+            } else {
+                (self.entry + count - 1) % count
+            };
+            self.switch(entry, registry::ALGORITHMS[entry].defaults());
         }
 
-        if is_key_pressed(KeyCode::Space) || is_mouse_button_pressed(MouseButton::Left) {
+        if is_key_pressed(KeyCode::V) {
+            // V was pressed, so switch to the next variant of this algorithm.
+            let variants = registry::ALGORITHMS[self.entry].variants();
+            let current = self.algorithm.get_variant();
+            let next = (variants.iter())
+                .position(|values| values.to_string() == current)
+                .map_or(0, |i| (i + 1) % variants.len());
+            self.switch(self.entry, variants[next].clone());
+        }
+
+        if is_key_pressed(KeyCode::Enter) && self.algorithm.get_state() == State::Done {
+            // Enter was pressed, so solve it again (or for the first time).
+            let variant = self.args.get_solver().unwrap_or("bfs".to_owned());
+            self.solve(variant);
+        }
+
+        if is_key_pressed(KeyCode::H) && self.algorithm.get_state() == State::Done {
+            // H was pressed, so toggle the heat map.
+            let maze = self.algorithm.get_maze();
+            self.heat_map = match (&self.heat_map, maze.start()) {
+                (None, Some(start)) => Some(HeatMap::new(maze, start)),
+                _ => None,
+            };
+        }
+
+        if let (Some(heat_map), true) = (&self.heat_map, is_mouse_button_pressed(MouseButton::Left))
+        {
+            // Flood from wherever was clicked, instead of pausing.
+            let cursor = self.camera().screen_to_world(mouse_position().into());
+            if let Some(cell) = heat_map.cell_at((cursor.x, cursor.y)) {
+                self.heat_map = Some(HeatMap::new(self.algorithm.get_maze(), cell));
+            }
+        } else if is_key_pressed(KeyCode::Space) || is_mouse_button_pressed(MouseButton::Left) {
             // Space was pressed, so pause.
             self.paused = !self.paused;
+            self.finishing = false;
+        }
+
+        if is_key_pressed(KeyCode::Period) && self.paused {
+            // Period was pressed, so take a single step.
+            self.step();
+        }
+        if self.paused {
+            // The arrow keys go back and forth through the steps so far, a tenth of the way at a
+            // time with shift held down, and Home and End go to the start and the end.
+            let step = self.history.step();
+            let jump = if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                (self.history.furthest() / 10).max(1)
+            } else {
+                1
+            };
+            if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::Comma) {
+                self.seek(step.saturating_sub(jump));
+            } else if is_key_pressed(KeyCode::Right) {
+                self.seek(step + jump);
+            } else if is_key_pressed(KeyCode::Home) {
+                self.seek(0);
+            } else if is_key_pressed(KeyCode::End) {
+                self.seek(self.history.furthest());
+            }
+        }
+        if !self.paused && self.solver.is_none() && self.algorithm.get_state() == State::Done {
+            self.walk();
+        }
+        if is_key_pressed(KeyCode::F) {
+            // F was pressed, so skip to the end.
+            self.finishing = true;
+        }
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            self.set_speed(self.speed.more_steps());
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            self.set_speed(self.speed.fewer_steps());
+        }
+        if is_key_pressed(KeyCode::RightBracket) {
+            self.set_speed(self.speed.shorter_interval());
+        }
+        if is_key_pressed(KeyCode::LeftBracket) {
+            self.set_speed(self.speed.longer_interval());
         }
         false
     }
 
+    fn set_speed(&mut self, speed: Speed) {
+        self.speed = speed;
+        log::info!("{} steps every {:.3} seconds", speed.steps, speed.interval);
+    }
+
+    /// Build a new maze with `values` from the registry's `entry`, in place of this one, using the
+    /// same seed.
+    fn switch(&mut self, entry: usize, values: Values) {
+        let (width, height) = registry::ALGORITHMS[entry].size(self.args.get_size());
+        if let Err(error) = registry::ALGORITHMS[entry].check_size(width, height) {
+            log::error!("{}", error);
+            return;
+        }
+        self.entry = entry;
+        self.paused = false;
+        self.finishing = false;
+        self.history = History::new();
+        self.solver = None;
+        self.heat_map = None;
+        let seed = self.algorithm.get_seed();
+        self.algorithm =
+            registry::ALGORITHMS[entry].build(&values, self.args.get_size(), Rng::new(seed));
+        log::info!(
+            "Switching to {}, {}, seed {}",
+            self.algorithm.name(),
+            self.algorithm.get_variant(),
+            seed
+        );
+    }
+
+    /// Start solving the maze with `variant`. Returns whether it could be.
+    fn solve(&mut self, variant: String) -> bool {
+        match solver::Exports::new(variant, self.algorithm.as_ref()) {
+            Ok(solver) => {
+                log::info!("Solving with {}", solver.name());
+                self.solver = Some(solver);
+                true
+            }
+            Err(error) => {
+                log::error!("Can't solve it: {}", error);
+                false
+            }
+        }
+    }
+
     fn update(&mut self) -> bool {
         if self.args.needs_reset() {
             // log::info!("Needs reset!");
-            self.algorithm.re_init(self.args.get_variant());
+            let entry = registry::position(&self.args.get_algorithm()).unwrap_or_default();
+            if entry == self.entry {
+                self.solver = None;
+                self.heat_map = None;
+                self.history = History::new();
+                self.algorithm.re_init(self.args.get_variant());
+                log::info!("Seed {}", self.algorithm.get_seed());
+            } else {
+                // We'd switched away, but the controls are for the one we started with.
+                let values = registry::ALGORITHMS[entry].parse_or_default(&self.args.get_variant());
+                self.switch(entry, values);
+            }
+        }
+        if let Some(speed) = self.args.new_speed() {
+            self.speed = speed;
+        }
+        if self.args.needs_step() && self.paused {
+            self.step();
+        }
+        if self.args.needs_finish() {
+            self.finishing = true;
+        }
+        if let Some(step) = self.args.seek_to() {
+            self.paused = true;
+            self.seek(step);
         }
         self.update_timer += get_frame_time();
         let rv = self.handle_events();
-        if self.update_timer > 0.08 {
+        if self.history.target().is_some() {
+            // Replaying back to an earlier step can be a lot of steps, so it gets the same budget
+            // as finishing, and carries on next frame if it needs to.
+            let start = now();
+            while now() - start < FINISH_BUDGET {
+                if !self.history.replay(self.algorithm.as_mut()) {
+                    log::info!(
+                        "Step {} of {}",
+                        self.history.step(),
+                        self.history.furthest()
+                    );
+                    break;
+                }
+            }
+        } else if self.finishing {
+            // Take as many steps as fit in a frame, until there aren't any left. Big mazes take a
+            // while, so this has to stay responsive instead of looping until they're done.
+            let start = now();
+            while now() - start < FINISH_BUDGET {
+                if !self.step() {
+                    self.finishing = false;
+                    break;
+                }
+            }
+        } else if self.update_timer > self.speed.interval {
             self.update_timer = 0.0;
             if !self.paused {
-                match self.algorithm.get_state() {
-                    State::Done => {
-                        self.algorithm.move_to(mouse_position());
-                    }
-                    _ => {
-                        self.algorithm.update();
-                    }
+                let stepped = (0..self.speed.steps).take_while(|_| self.step()).count();
+                if stepped == 0 && mouse_position() != self.mouse {
+                    self.mouse = mouse_position();
+                    let cursor = self.camera().screen_to_world(self.mouse.into());
+                    self.algorithm.move_to((cursor.x, cursor.y));
                 }
             }
         }
+        self.args
+            .show_history(self.history.step(), self.history.furthest());
         rv
     }
 
+    /// Walk the robot one cell in the direction of whichever key was pressed. It won't go through
+    /// walls, and walking back the way it came takes that cell off the path.
+    fn walk(&mut self) {
+        let (square, hex, delta, polar) = (
+            pressed(&SQUARE_KEYS),
+            pressed(&HEX_KEYS),
+            pressed(&DELTA_KEYS),
+            pressed(&POLAR_KEYS),
+        );
+        if square.is_none() && hex.is_none() && delta.is_none() && polar.is_none() {
+            return;
+        }
+        let heading = match self.algorithm.get_maze() {
+            Maze::Square(_) => square.map(Heading::Square),
+            Maze::Hex(_) => hex.map(Heading::Hex),
+            Maze::Delta(_) => delta.map(Heading::Delta),
+            Maze::Polar(_) => polar.map(Heading::Polar),
+            // The pieces are turned every which way, so the arrows go to whichever is nearest.
+            Maze::Penrose(_) => square.map(Heading::Square),
+        };
+        if let Some(heading) = heading {
+            self.algorithm.walk(heading);
+        }
+    }
+
+    /// Go back (or forwards again) to an earlier step of building the maze.
+    fn seek(&mut self, step: usize) {
+        if self.history.furthest() == 0 {
+            // Nothing's been built, like with a maze that was loaded already finished.
+            return;
+        }
+        self.solver = None;
+        self.heat_map = None;
+        self.finishing = false;
+        self.history
+            .seek(&registry::ALGORITHMS[self.entry], &mut self.algorithm, step);
+    }
+
+    /// Take one step of whatever's running: the maze, and then the solver once the maze is done.
+    /// Returns false if there's nothing left to do.
+    fn step(&mut self) -> bool {
+        match self.algorithm.get_state() {
+            State::Done if self.solver.is_some() => {
+                let solver = self.solver.as_mut().unwrap();
+                if solver.get_state() == State::Done {
+                    return false;
+                }
+                solver.update();
+            }
+            State::Done if self.args.get_solver().is_some() => {
+                return self.solve(self.args.get_solver().unwrap());
+            }
+            State::Done => return false,
+            _ => {
+                self.algorithm.update();
+                self.history.record();
+            }
+        }
+        true
+    }
+
     fn draw(&mut self) {
         // Clear the screen to a blank, white color
         clear_background(WHITE);
-        self.algorithm.draw();
+        set_camera(&self.camera());
+        match &self.solver {
+            Some(solver) => solver.draw(&mut Screen),
+            None => self.algorithm.draw(&mut Screen),
+        }
+        if let Some(heat_map) = &self.heat_map {
+            heat_map.draw(&mut Screen);
+        }
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    rand::srand(now() as u64);
-
-    let args = RealArgs::new();
-    let arg = args.get_algorithm();
-    let variant = args.get_variant();
+fn build_algorithm(args: &RealArgs, seed: u64) -> Box<dyn Algorithm> {
     log::info!("Args: {}, {}", args.get_algorithm(), args.get_variant());
-
-    let message = format!("Expected an integer number of seeds. Got {}!", variant);
-    let algorithm: Box<dyn Algorithm> = match arg.as_str() {
-        "parallel" => Box::new(parallel::Exports::new(variant.parse().expect(&message))),
-        "eller" => Box::new(eller::Exports::new()),
-        "kruskal" => Box::new(kruskal::Exports::new()),
-        "prim" => Box::new(prim::Exports::new()),
-        "recdiv" => Box::new(recdiv::Exports::new()),
-        "blobby" => Box::new(blobby::Exports::new()),
-        "aldousbroder" => Box::new(aldous_broder::Exports::new(variant == "fast")),
-        "wilson" => Box::new(wilson::Exports::new(variant == "slow")),
-        "houston" => Box::new(houston::Exports::new()),
-        "huntandkill" => Box::new(huntandkill::Exports::new()),
-        "growingtree" => Box::new(growingtree::Exports::new(variant)),
-        "bintree" => Box::new(binarytree::Exports::new(variant)),
-        "sidewinder" => Box::new(sidewinder::Exports::new(variant == "hard")),
-        "originshift" => Box::new(origin_shift::Exports::new(variant.parse().expect(&message))),
-        "hexparallel" => Box::new(hex_parallel::Exports::new(variant.parse().expect(&message))),
-        "hexblobby" => Box::new(hex_blobby::Exports::new()),
-        "penrose" => Box::new(penrose::Exports::new(variant)),
-        _ => {
-            log::error!("Unimplemented algorithm: {:?}!", arg);
-            panic!("Unimplemented algorithm.")
-        }
-    };
+    let algorithm = new_algorithm(
+        &args.get_algorithm(),
+        args.get_variant(),
+        args.get_size(),
+        seed,
+    );
     log::info!(
-        "Algorithm: {:?}, {:?}",
+        "Algorithm: {:?}, {:?}, seed {}",
         algorithm.name(),
-        algorithm.get_variant()
+        algorithm.get_variant(),
+        algorithm.get_seed()
     );
+    algorithm
+}
+
+fn new_algorithm(
+    arg: &str,
+    variant: String,
+    size: (Option<usize>, Option<usize>),
+    seed: u64,
+) -> Box<dyn Algorithm> {
+    let entry = registry::find(arg).unwrap_or_else(|| {
+        log::error!("Unimplemented algorithm: {:?}!", arg);
+        &registry::ALGORITHMS[0]
+    });
+    entry.build(&entry.parse_or_default(&variant), size, Rng::new(seed))
+}
+
+fn main() {
+    let args = RealArgs::new();
+    let seed = args.get_seed().unwrap_or((now() * 1000.0) as u64);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.bench(|algorithm, variant, (width, height), seed| {
+        new_algorithm(algorithm, variant, (Some(width), Some(height)), seed)
+    }) {
+        return;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let saved = args.load().unwrap_or_else(|error| {
+        log::error!("Couldn't load the maze: {}", error);
+        std::process::exit(1);
+    });
+    #[cfg(target_arch = "wasm32")]
+    let saved: Option<mazes::saved::SavedMaze> = None;
+    let make = || match &saved {
+        Some(saved) => saved.playable(),
+        None => build_algorithm(&args, seed),
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.is_headless() {
+        // Just write out the files, without ever opening a window.
+        let id = match &saved {
+            Some(saved) => saved.algorithm.clone(),
+            None => (registry::find(&args.get_algorithm()))
+                .unwrap_or(&registry::ALGORITHMS[0])
+                .id
+                .to_owned(),
+        };
+        if let Err(error) = args.export(&id, make) {
+            log::error!("Couldn't write the maze: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let algorithm = make();
+    macroquad::Window::from_config(window_conf(), run(algorithm, args));
+}
 
+async fn run(algorithm: Box<dyn Algorithm>, args: RealArgs) {
     //     window.set_title(&format!("Some {} mazes…", algorithm.name()));
     let mut game = MyGame::new(algorithm, args);
     next_frame().await;
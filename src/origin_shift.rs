@@ -1,49 +1,69 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State, CELL_WIDTH,
-    COLORS, COLUMNS, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State, CELL_WIDTH, COLORS, LINE_WIDTH, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::shapes::draw_rectangle;
 use maze_utils::From;
+use std::cmp::Ordering;
 
 #[derive(From)]
 pub struct Exports {
     path: Vec<(usize, usize)>,
     curr: (usize, usize),
-    grid: [[Option<Direction>; COLUMNS as usize]; ROWS as usize],
+    grid: Board<Option<Direction>>,
     remaining: usize,
     iterations: usize,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(iterations: usize) -> Self {
-        let mut grid = [[Some(Direction::East); COLUMNS as usize]; ROWS as usize];
-
-        for row in grid.iter_mut().take(ROWS as usize - 1) {
-            row[COLUMNS as usize - 1] = Some(Direction::South);
+    pub fn new(iterations: usize, width: usize, height: usize, rng: Rng) -> Self {
+        // Start with every cell pointing along its row to the middle column, and then up or down
+        // that to the origin, so that it's a tree from the very first step.
+        let curr = (
+            (width / 2).saturating_sub(1),
+            (height / 2).saturating_sub(1),
+        );
+        let mut grid = Board::new(width, height, None);
+        for (y, row) in grid.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = match (x.cmp(&curr.0), y.cmp(&curr.1)) {
+                    (Ordering::Less, _) => Some(Direction::East),
+                    (Ordering::Greater, _) => Some(Direction::West),
+                    (_, Ordering::Less) => Some(Direction::South),
+                    (_, Ordering::Greater) => Some(Direction::North),
+                    (Ordering::Equal, Ordering::Equal) => None,
+                };
+            }
         }
-        grid[ROWS as usize - 1][COLUMNS as usize - 1] = None;
 
         Self {
             path: vec![],
-            curr: (0, 0),
+            curr,
             grid,
-            remaining: (ROWS * COLUMNS) as usize * 10 * iterations,
-            iterations: (ROWS * COLUMNS) as usize * 10 * iterations,
+            remaining: height * width * 10 * iterations,
+            iterations,
             state: State::Setup,
+            rng,
         }
     }
     pub fn get_grid(&self) -> Grid {
-        let mut rv = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        for y in 0..ROWS as usize {
-            for x in 0..COLUMNS as usize {
+        let mut rv = Board::new(self.grid.width(), self.grid.height(), EnumSet::new());
+        for y in 0..self.grid.height() {
+            for x in 0..self.grid.width() {
                 if let Some(direction) = self.grid[y][x] {
                     rv[y][x] |= direction;
-                    if let Some(offset) = direction.offset((x, y)) {
-                        rv[offset.1][offset.0] |= direction.opposite();
-                    } else {
-                        println!("Pointing off the map!")
+                    let offset = direction.offset((x, y), &self.grid);
+                    // Every arrow starts out pointing at a neighbour, and the origin only ever moves onto
+                    // one, so nothing points off the map.
+                    debug_assert!(offset.is_some(), "({}, {}) points off the map", x, y);
+                    if let Some((i, j)) = offset {
+                        rv[j][i] |= direction.opposite();
                     }
                 }
             }
@@ -52,21 +72,31 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "originshift",
+    name: "Origin Shift",
+    params: &[Param::Number { min: 0, max: 10 }],
+    default_variant: "1",
+    shape: Shape::Square,
+    new: |values, width, height, rng| Box::new(Exports::new(values.number(0), width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Origin Shift")
     }
     fn re_init(&mut self, variant: String) {
         // log::info!("Re-initing with {}", variant);
-        self.from(Exports::new(variant.parse().unwrap()));
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(values.number(0), width, height, rng));
     }
     fn get_variant(&self) -> String {
         self.iterations.to_string()
     }
     fn update(&mut self) {
         if self.state == State::Setup {
-            self.curr = ((COLUMNS / 2.0) as usize - 1, (ROWS / 2.0) as usize - 1);
-            self.remaining = self.iterations;
             self.state = State::Running;
             return;
         }
@@ -80,10 +110,10 @@ impl Algorithm for Exports {
 
         // Have self.curr point to a random neighbouring node.
         let mut potentials: Vec<Direction> = Vec::from_iter(EnumSet::all());
-        potentials.shuffle();
+        potentials.shuffle(&mut self.rng);
 
         for direction in potentials {
-            if let Some(new) = direction.offset(self.curr) {
+            if let Some(new) = direction.offset(self.curr, &self.grid) {
                 self.grid[self.curr.1][self.curr.0] = Some(direction);
                 // Set the new node to the origin.
                 self.curr = new;
@@ -94,12 +124,29 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.get_grid());
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.get_grid())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.get_grid());
 
         if self.state == State::Running {
             let curr_color = COLORS[1];
-            draw_rectangle(
+            canvas.draw_rectangle(
                 self.curr.0 as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 self.curr.1 as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -107,16 +154,16 @@ impl Algorithm for Exports {
                 curr_color,
             );
         }
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
@@ -1,34 +1,66 @@
-use crate::util::{
-    draw_board, draw_cell, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State,
-    COLORS, COLUMNS, LINE_WIDTH, ROWS,
-};
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::params::{Param, ParamError, Values};
+use crate::polar_util;
+use crate::registry::{Entry, Shape};
+use crate::util::{Algorithm, Board, ChooseRandom, Grid, Heading, Rng, State, COLORS};
 use maze_utils::From;
 use std::collections::{HashSet, VecDeque};
 
 use array_init::array_init;
-use enumset::EnumSet;
-use macroquad::{logging as log, rand::gen_range};
+use macroquad::logging as log;
 
 const MAX_SEEDS: usize = 6;
 
+/// Backtracking from a few seeds at once, on any shape of board.
 #[derive(From)]
-pub struct Exports {
-    path: Vec<(usize, usize)>,
-    grid: Grid,
-    grid_seeds: [[Option<usize>; COLUMNS as usize]; ROWS as usize],
+pub struct Parallel<G: CellGraph> {
+    path: Vec<Cell>,
+    grid: G,
+    grid_seeds: Board<Option<usize>>,
     seeds: usize,
     sets: [HashSet<usize>; MAX_SEEDS],
-    stack: [VecDeque<(usize, usize, EnumSet<Direction>)>; MAX_SEEDS],
+    /// Every cell the seed is backtracking through, and the neighbours it hasn't tried yet.
+    stack: [VecDeque<(Cell, Vec<Cell>)>; MAX_SEEDS],
     state: State,
+    rng: Rng,
 }
 
-impl Exports {
-    pub fn new(seeds: usize) -> Self {
-        if !(1..=MAX_SEEDS).contains(&seeds) {
-            panic!("Seeds {} must be between {} and {}", seeds, 1, MAX_SEEDS);
+pub type Exports = Parallel<Grid>;
+pub type HexExports = Parallel<hex_util::Grid>;
+pub type DeltaExports = Parallel<delta_util::Grid>;
+pub type PolarExports = Parallel<polar_util::Grid>;
+
+/// How many seeds to backtrack from, which has to be from 1 to `MAX_SEEDS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Seeds(usize);
+
+impl Seeds {
+    pub fn new(count: usize) -> Result<Self, ParamError> {
+        if (1..=MAX_SEEDS).contains(&count) {
+            Ok(Seeds(count))
+        } else {
+            Err(ParamError::OutOfRange {
+                value: count,
+                min: 1,
+                max: MAX_SEEDS,
+            })
         }
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        let grid_seeds = [[None; COLUMNS as usize]; ROWS as usize];
+    }
+
+    /// The count out of a variant, which was already checked against `PARAMS` when it was parsed.
+    fn from_values(values: &Values) -> Self {
+        Seeds(values.number(0))
+    }
+}
+
+impl<G: CellGraph> Parallel<G> {
+    pub fn new(Seeds(seeds): Seeds, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = G::empty(width, height);
+        let grid_seeds = Board::new(width, height, None);
         let sets = array_init(|_| HashSet::new());
         let stack = array_init(|_| VecDeque::new());
         Self {
@@ -39,21 +71,98 @@ impl Exports {
             sets,
             stack,
             state: State::Setup,
+            rng,
         }
     }
 }
 
-impl Algorithm for Exports {
+const PARAMS: &[Param] = &[Param::Number {
+    min: 1,
+    max: MAX_SEEDS,
+}];
+
+pub const ENTRY: Entry = Entry {
+    id: "parallel",
+    name: "Parallel Backtrack",
+    params: PARAMS,
+    default_variant: "6",
+    shape: Shape::Square,
+    new: |values, width, height, rng| {
+        Box::new(Exports::new(Seeds::from_values(values), width, height, rng))
+    },
+};
+
+pub const HEX_ENTRY: Entry = Entry {
+    id: "hexparallel",
+    name: "Parallel Hex Backtrack",
+    params: PARAMS,
+    default_variant: "6",
+    shape: Shape::Hex,
+    new: |values, width, height, rng| {
+        Box::new(HexExports::new(
+            Seeds::from_values(values),
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+pub const DELTA_ENTRY: Entry = Entry {
+    id: "deltaparallel",
+    name: "Parallel Delta Backtrack",
+    params: PARAMS,
+    default_variant: "6",
+    shape: Shape::Delta,
+    new: |values, width, height, rng| {
+        Box::new(DeltaExports::new(
+            Seeds::from_values(values),
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+pub const POLAR_ENTRY: Entry = Entry {
+    id: "polarparallel",
+    name: "Parallel Polar Backtrack",
+    params: PARAMS,
+    default_variant: "6",
+    shape: Shape::Polar,
+    new: |values, width, height, rng| {
+        Box::new(PolarExports::new(
+            Seeds::from_values(values),
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+impl<G: CellGraph> Generator for Parallel<G> {
     fn name(&self) -> String {
+        let name = match G::SHAPE {
+            Shape::Square => String::from("Backtrack"),
+            shape => format!("{} Backtrack", shape.name()),
+        };
         if self.seeds == 1 {
-            String::from("Backtrack")
+            name
         } else {
-            String::from("Parallel Backtrack")
+            format!("Parallel {}", name)
         }
     }
     fn re_init(&mut self, variant: String) {
         // log::info!("Re-initing with {}", variant);
-        self.from(Exports::new(variant.parse().unwrap()));
+        let (width, height) = self.grid.size();
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Parallel::new(
+            Seeds::from_values(&values),
+            width,
+            height,
+            rng,
+        ));
     }
     fn get_variant(&self) -> String {
         self.seeds.to_string()
@@ -61,11 +170,22 @@ impl Algorithm for Exports {
     fn update(&mut self) {
         // log::info!("Updating {}", self.name());
         if self.state == State::Setup {
+            let cells = self.grid.cells();
             for (i, stack) in self.stack.iter_mut().take(self.seeds).enumerate() {
-                let x = gen_range(0, COLUMNS as usize);
-                let y = gen_range(0, ROWS as usize);
-                stack.push_front((x, y, EnumSet::all()));
                 self.sets[i].insert(i);
+                let free = cells
+                    .iter()
+                    .filter(|&&(x, y)| self.grid_seeds[y][x].is_none())
+                    .copied()
+                    .collect::<Vec<_>>();
+                // Tiny boards might not have room for every seed.
+                let Some((x, y)) = free.choose(&mut self.rng) else {
+                    break;
+                };
+                // Claim the starting cell straight away, so that no other seed can start on it or
+                // carve into it without joining up their sets.
+                self.grid_seeds[y][x] = Some(i);
+                stack.push_front(((x, y), self.grid.neighbours((x, y))));
             }
 
             self.state = State::Running;
@@ -76,7 +196,6 @@ impl Algorithm for Exports {
 
         'outer: for (i, stack) in self.stack.iter_mut().take(self.seeds).enumerate() {
             let mut found = false;
-            // let (first_x, first_y, _) = self.stack.front().unwrap().clone();
 
             while !found {
                 if stack.is_empty() {
@@ -84,90 +203,100 @@ impl Algorithm for Exports {
                 }
                 done = false;
 
-                let (x, y, directions) = stack.pop_front().unwrap();
-                let mut potentials: Vec<Direction> = directions.iter().collect();
+                let (cell, mut potentials) = stack.pop_front().unwrap();
                 if potentials.is_empty() {
                     continue 'outer;
                 }
-                potentials.shuffle();
-                let direction = potentials.pop().unwrap();
-                // log::info!("({},{}) -> {:?}", x, y, direction);
-                stack.push_front((x, y, directions ^ direction));
-
-                let (new_x, new_y) = match direction {
-                    Direction::North => (x as i32, y as i32 - 1),
-                    Direction::East => (x as i32 + 1, y as i32),
-                    Direction::South => (x as i32, y as i32 + 1),
-                    Direction::West => (x as i32 - 1, y as i32),
-                };
-                // log::info!("{:?} / {:?} -> {:?}", (x,y), direction, (new_x, new_y));
-                if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32 {
-                    let (new_x, new_y) = (new_x as usize, new_y as usize);
-                    if self.grid[new_y][new_x] == EnumSet::new()
-                        && self.grid_seeds[new_y][new_x].is_none()
-                    {
-                        self.grid_seeds[y][x] = Some(i);
-                        self.grid[y][x] |= direction;
-                        self.grid_seeds[new_y][new_x] = Some(i);
-                        self.grid[new_y][new_x] |= direction.opposite();
-                        stack.push_front((new_x, new_y, EnumSet::all() ^ direction.opposite()));
-                        found = true;
-                    } else if let Some(set) = self.grid_seeds[new_y][new_x] {
-                        if !self.sets[i].contains(&set) {
-                            let both_sets: HashSet<usize> =
-                                self.sets[i].union(&self.sets[set]).cloned().collect();
-                            for i in &both_sets {
-                                self.sets[*i] = both_sets.clone();
-                            }
-                            self.grid[y][x] |= direction;
-                            self.grid[new_y][new_x] |= direction.opposite();
+                potentials.shuffle(&mut self.rng);
+                let next = potentials.pop().unwrap();
+                // log::info!("{:?} -> {:?}", cell, next);
+                stack.push_front((cell, potentials));
+
+                if self.grid.is_empty(next) && self.grid_seeds[next.1][next.0].is_none() {
+                    self.grid_seeds[cell.1][cell.0] = Some(i);
+                    self.grid_seeds[next.1][next.0] = Some(i);
+                    self.grid.link(cell, next);
+                    let mut rest = self.grid.neighbours(next);
+                    rest.retain(|&other| other != cell);
+                    stack.push_front((next, rest));
+                    found = true;
+                } else if let Some(set) = self.grid_seeds[next.1][next.0] {
+                    if !self.sets[i].contains(&set) {
+                        let both_sets: HashSet<usize> =
+                            self.sets[i].union(&self.sets[set]).cloned().collect();
+                        for i in &both_sets {
+                            self.sets[*i] = both_sets.clone();
                         }
+                        self.grid.link(cell, next);
                     }
-                    // Otherwise, loop again and see what we can get.
                 }
+                // Otherwise, loop again and see what we can get.
             }
         }
         if done {
-            self.path.push((0, 0));
+            self.path.extend(self.grid.start());
             self.state = State::Done;
             log::info!("Done!");
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.grid.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for Parallel<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
 
         for i in 0..self.seeds {
             let curr_color = COLORS[i + 1];
             let mut cell_color = COLORS[i + 1];
             cell_color.a = 0.5;
-            for (i, (x, y, _)) in self.stack[i].iter().enumerate() {
+            for (i, (cell, _)) in self.stack[i].iter().enumerate() {
                 if i == 0 {
-                    draw_cell(*x, *y, LINE_WIDTH, curr_color);
+                    self.grid.draw_current(canvas, *cell, curr_color);
                 } else {
-                    draw_cell(*x, *y, 0.0, cell_color);
+                    self.grid.draw_cell(canvas, *cell, 0.0, cell_color);
                 }
             }
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        self.grid.draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
-        Playable::move_to(self, pos);
+        graph::move_to(&self.grid, &mut self.path, pos);
     }
-}
 
-impl Playable for Exports {
-    fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
-        &mut self.path
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
     }
+}
 
-    fn get_grid(&self) -> crate::util::Grid {
-        self.grid
+#[test]
+fn seeds_must_be_in_range() {
+    assert_eq!(Seeds::new(1).map(|Seeds(count)| count), Ok(1));
+    assert_eq!(Seeds::new(MAX_SEEDS).map(|Seeds(count)| count), Ok(6));
+    for count in [0, 7] {
+        assert_eq!(
+            Seeds::new(count),
+            Err(ParamError::OutOfRange {
+                value: count,
+                min: 1,
+                max: MAX_SEEDS,
+            })
+        );
     }
 }
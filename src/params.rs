@@ -0,0 +1,266 @@
+//! What each algorithm's variant is allowed to be. Variants are still written as strings, like
+//! `random:NorthWest`, with a `:` between each parameter, but they get checked against the
+//! algorithm's parameters up front, so that a bad one turns into a helpful error instead of a
+//! panic halfway through building the maze.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Param {
+    /// One of a list of words.
+    Choice(&'static [&'static str]),
+    /// A whole number from `min` to `max`, inclusive.
+    Number { min: usize, max: usize },
+    /// A switch, written as the first word when it's off and the second when it's on.
+    Flag(&'static str, &'static str),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// Which of the choices it is, counting from zero.
+    Choice(usize),
+    Number(usize),
+    Flag(bool),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamError {
+    Unknown {
+        value: String,
+        expected: Vec<&'static str>,
+    },
+    NotANumber(String),
+    OutOfRange {
+        value: usize,
+        min: usize,
+        max: usize,
+    },
+    TooMany {
+        value: String,
+        count: usize,
+    },
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Unknown { value, expected } => {
+                write!(f, "'{}' isn't one of: {}", value, expected.join(", "))
+            }
+            ParamError::NotANumber(value) => write!(f, "'{}' isn't a whole number", value),
+            ParamError::OutOfRange { value, min, max } => {
+                write!(f, "{} isn't between {} and {}", value, min, max)
+            }
+            ParamError::TooMany { value, count } => write!(
+                f,
+                "'{}' has too many parts, expected at most {}",
+                value, count
+            ),
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+impl Param {
+    fn parse(&self, word: &str) -> Result<Value, ParamError> {
+        match *self {
+            Param::Choice(choices) => choices
+                .iter()
+                .position(|&choice| choice == word)
+                .map(Value::Choice)
+                .ok_or_else(|| ParamError::Unknown {
+                    value: word.to_owned(),
+                    expected: choices.to_vec(),
+                }),
+            Param::Number { min, max } => {
+                let value = word
+                    .parse()
+                    .map_err(|_| ParamError::NotANumber(word.to_owned()))?;
+                if (min..=max).contains(&value) {
+                    Ok(Value::Number(value))
+                } else {
+                    Err(ParamError::OutOfRange { value, min, max })
+                }
+            }
+            Param::Flag(off, on) => {
+                if word == off || word == on {
+                    Ok(Value::Flag(word == on))
+                } else {
+                    Err(ParamError::Unknown {
+                        value: word.to_owned(),
+                        expected: vec![off, on],
+                    })
+                }
+            }
+        }
+    }
+
+    fn write(&self, value: Value) -> String {
+        match (*self, value) {
+            (Param::Choice(choices), Value::Choice(index)) => choices[index].to_owned(),
+            (Param::Flag(off, on), Value::Flag(flag)) => (if flag { on } else { off }).to_owned(),
+            (_, Value::Number(number)) => number.to_string(),
+            _ => unreachable!("{:?} can't be {:?}", self, value),
+        }
+    }
+
+    /// Every value it can have.
+    fn values(&self) -> Vec<Value> {
+        match *self {
+            Param::Choice(choices) => (0..choices.len()).map(Value::Choice).collect(),
+            Param::Number { min, max } => (min..=max).map(Value::Number).collect(),
+            Param::Flag(..) => vec![Value::Flag(false), Value::Flag(true)],
+        }
+    }
+}
+
+/// A checked variant, one value for each of the algorithm's parameters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Values {
+    params: &'static [Param],
+    values: Vec<Value>,
+}
+
+impl Values {
+    /// Check `variant` against `params`, filling in any missing (or empty) parts from `default`,
+    /// so that `ordered` and `:SouthEast` are both fine for the binary tree.
+    pub fn parse(
+        params: &'static [Param],
+        variant: &str,
+        default: &str,
+    ) -> Result<Self, ParamError> {
+        let words = variant.split(':').collect::<Vec<_>>();
+        if params.is_empty() {
+            // Anything goes, since nothing looks at it.
+            return Ok(Values {
+                params,
+                values: vec![],
+            });
+        }
+        if words.len() > params.len() {
+            return Err(ParamError::TooMany {
+                value: variant.to_owned(),
+                count: params.len(),
+            });
+        }
+        let mut defaults = default.split(':');
+        let values = params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                let default = defaults.next().unwrap_or_default();
+                match words.get(i) {
+                    Some(word) if !word.is_empty() => param.parse(word),
+                    _ => param.parse(default),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Values { params, values })
+    }
+
+    /// Every variant `params` allows.
+    pub fn all(params: &'static [Param]) -> Vec<Self> {
+        params.iter().fold(
+            vec![Values {
+                params,
+                values: vec![],
+            }],
+            |all, param| {
+                all.iter()
+                    .flat_map(|values| {
+                        param.values().into_iter().map(|value| {
+                            let mut values = values.clone();
+                            values.values.push(value);
+                            values
+                        })
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    pub fn choice(&self, index: usize) -> usize {
+        match self.values[index] {
+            Value::Choice(choice) => choice,
+            value => panic!("Parameter {} is {:?}, not a choice", index, value),
+        }
+    }
+
+    pub fn number(&self, index: usize) -> usize {
+        match self.values[index] {
+            Value::Number(number) => number,
+            value => panic!("Parameter {} is {:?}, not a number", index, value),
+        }
+    }
+
+    pub fn flag(&self, index: usize) -> bool {
+        match self.values[index] {
+            Value::Flag(flag) => flag,
+            value => panic!("Parameter {} is {:?}, not a flag", index, value),
+        }
+    }
+}
+
+/// Written back out the way it was parsed, so it can go back on the command line. Algorithms
+/// without any parameters call theirs "unused".
+impl Display for Values {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.values.is_empty() {
+            return write!(f, "unused");
+        }
+        let words = self
+            .params
+            .iter()
+            .zip(&self.values)
+            .map(|(param, &value)| param.write(value))
+            .collect::<Vec<_>>();
+        write!(f, "{}", words.join(":"))
+    }
+}
+
+#[test]
+fn parse_variants() {
+    const BINTREE: &[Param] = &[
+        Param::Flag("ordered", "random"),
+        Param::Choice(&["NorthEast", "SouthEast", "SouthWest", "NorthWest"]),
+    ];
+    let parse = |variant| Values::parse(BINTREE, variant, "random:NorthWest");
+    assert_eq!(
+        parse("ordered").map(|values| values.to_string()),
+        Ok("ordered:NorthWest".to_owned())
+    );
+    assert_eq!(
+        parse(":SouthEast").map(|values| (values.flag(0), values.choice(1))),
+        Ok((true, 1))
+    );
+    assert_eq!(
+        parse("random:Up"),
+        Err(ParamError::Unknown {
+            value: "Up".to_owned(),
+            expected: vec!["NorthEast", "SouthEast", "SouthWest", "NorthWest"]
+        })
+    );
+    assert!(matches!(
+        parse("random:NorthEast:more"),
+        Err(ParamError::TooMany { count: 2, .. })
+    ));
+    assert_eq!(Values::all(BINTREE).len(), 8);
+
+    let seeds = &[Param::Number { min: 1, max: 6 }];
+    assert_eq!(
+        Values::parse(seeds, "7", "6"),
+        Err(ParamError::OutOfRange {
+            value: 7,
+            min: 1,
+            max: 6
+        })
+    );
+    assert_eq!(
+        Values::parse(seeds, "lots", "6"),
+        Err(ParamError::NotANumber("lots".to_owned()))
+    );
+}
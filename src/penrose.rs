@@ -1,16 +1,21 @@
+//! Kites and darts, deflated over and over until they're small enough to be the cells of a maze.
+//! Since the tiling never repeats, neither does the maze.
+
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
+use std::collections::{HashMap, VecDeque};
 use std::{f32::consts::PI, fmt::Display};
 
 use crate::util::{
-    Algorithm, ChooseRandom, State as BaseState, CELL_WIDTH, COLORS, COLUMNS, LINE_WIDTH, OFFSET,
-    ROWS, WHITE,
+    board_size, Algorithm, ChooseRandom, Direction, Heading, Rng, State as BaseState, Wall,
+    CELL_WIDTH, COLORS, LINE_WIDTH, OFFSET, WHITE,
 };
 use maze_utils::From;
 
-use macroquad::{
-    logging as log,
-    prelude::Vec2,
-    shapes::{draw_line, draw_rectangle, draw_rectangle_lines, draw_triangle},
-};
+use macroquad::{color::Color, logging as log, prelude::Vec2};
 
 const LENGTH: f32 = 300.0;
 lazy_static! {
@@ -23,11 +28,12 @@ enum State {
     Setup,
     Deflating,
     Growing,
+    Carving,
     Done,
 }
 
 #[derive(Debug, Copy, Clone)]
-struct Point(f32, f32);
+pub struct Point(pub f32, pub f32);
 
 impl Point {
     fn offset(&self, x: f32, y: f32) -> Self {
@@ -46,11 +52,26 @@ impl Point {
         Point(x, y)
     }
 
-    fn inside(&self, start: Point, end: Point) -> bool {
-        let Point(x, y) = start;
-        let Point(w, h) = end;
-        (self.0 >= x && self.0 <= w) && (self.1 >= y && self.1 <= h)
+    fn distance(&self, other: &Point) -> f32 {
+        (other.0 - self.0).hypot(other.1 - self.1)
     }
+
+    /// How far it is to the nearest point on the line from `a` to `b`.
+    fn distance_to_line(&self, a: &Point, b: &Point) -> f32 {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let t = ((self.0 - a.0) * dx + (self.1 - a.1) * dy) / (dx * dx + dy * dy).max(f32::EPSILON);
+        self.distance(&a.move_to(b, t.clamp(0.0, 1.0)))
+    }
+}
+
+/// Which way round `a`, `b` and `c` go: positive for clockwise on the screen, where y goes down.
+fn turn(a: Point, b: Point, c: Point) -> f32 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn in_triangle(p: Point, a: Point, b: Point, c: Point) -> bool {
+    let (x, y, z) = (turn(a, b, p), turn(b, c, p), turn(c, a, p));
+    (x >= 0.0 && y >= 0.0 && z >= 0.0) || (x <= 0.0 && y <= 0.0 && z <= 0.0)
 }
 
 impl From<Point> for Vec2 {
@@ -59,24 +80,26 @@ impl From<Point> for Vec2 {
     }
 }
 
+/// Half of a kite (`true`) or dart (`false`), as the three corners of a triangle.
 #[derive(Debug, Copy, Clone)]
-struct Tile(bool, Point, Point, Point);
+pub struct Tile(pub bool, pub Point, pub Point, pub Point);
 
 impl Tile {
-    fn draw(&self) {
+    fn draw(&self, canvas: &mut dyn Canvas) {
         let Tile(is_kite, a, b, c) = *self;
         let color = if is_kite { COLORS[3] } else { COLORS[4] };
-        draw_line(a.0, a.1, b.0, b.1, LINE_WIDTH, COLORS[2]);
-        draw_line(b.0, b.1, c.0, c.1, LINE_WIDTH, COLORS[2]);
-        // draw_line(c.0, c.1, a.0, a.1, LINE_WIDTH, COLORS[5]);
-        let b2 = c.move_to(&a, 0.5);
+        canvas.draw_line(a.0, a.1, b.0, b.1, LINE_WIDTH, COLORS[2]);
+        canvas.draw_line(b.0, b.1, c.0, c.1, LINE_WIDTH, COLORS[2]);
+        // canvas.draw_line(c.0, c.1, a.0, a.1, LINE_WIDTH, COLORS[5]);
+        let [d, e, f] = self.fill();
+        canvas.draw_triangle(d.into(), e.into(), f.into(), color);
+    }
 
-        draw_triangle(
-            c.move_to(&a, 0.1).into(),
-            a.move_to(&c, 0.1).into(),
-            b.move_to(&b2, 0.2).into(),
-            color,
-        );
+    /// The coloured triangle inside the tile, pulled in a bit from its edges.
+    pub fn fill(&self) -> [Point; 3] {
+        let Tile(_, a, b, c) = *self;
+        let b2 = c.move_to(&a, 0.5);
+        [c.move_to(&a, 0.1), a.move_to(&c, 0.1), b.move_to(&b2, 0.2)]
     }
 
     fn build_tile(i: f32, x_center: f32, y_center: f32, is_kite: bool) -> [Tile; 2] {
@@ -112,8 +135,18 @@ impl Tile {
         }
     }
 
+    /// Whether any of the tile might be inside the rectangle from `start` to `end`. Going by
+    /// the corners alone would lose the tiles that reach across the corners of the rectangle.
     fn inside(&self, start: Point, end: Point) -> bool {
-        self.1.inside(start, end) || self.2.inside(start, end) || self.3.inside(start, end)
+        let Tile(_, a, b, c) = *self;
+        a.0.max(b.0).max(c.0) >= start.0
+            && a.0.min(b.0).min(c.0) <= end.0
+            && a.1.max(b.1).max(c.1) >= start.1
+            && a.1.min(b.1).min(c.1) <= end.1
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        in_triangle(p, self.1, self.2, self.3)
     }
 }
 
@@ -131,8 +164,8 @@ impl Display for Variant {
     }
 }
 
-#[derive(Debug)]
-enum Variant {
+#[derive(Clone, Copy, Debug)]
+pub enum Variant {
     Sun,
     Star,
     Ace,
@@ -143,6 +176,17 @@ enum Variant {
 }
 
 impl Variant {
+    pub const ALL: [Variant; 7] = [
+        Variant::Sun,
+        Variant::Star,
+        Variant::Ace,
+        Variant::Deuce,
+        Variant::Jack,
+        Variant::Queen,
+        Variant::King,
+    ];
+    pub const NAMES: [&'static str; 7] = ["sun", "star", "ace", "deuce", "jack", "queen", "king"];
+
     fn start_tiles(&self, x_center: f32, y_center: f32) -> Vec<Tile> {
         let mut tiles = vec![];
         let small_length = *SMALL_LENGTH;
@@ -238,48 +282,576 @@ impl Variant {
     }
 }
 
+/// Numbers the corners of the tiles, so that a corner gets the same number from every tile that
+/// shares it, even though each tile worked its corners out with its own rounding errors.
+#[derive(Default)]
+struct Corners {
+    points: Vec<Point>,
+    /// The numbers of the corners in each square pixel.
+    pixels: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Corners {
+    fn number(&mut self, point: Point) -> usize {
+        let (x, y) = (point.0.floor() as i32, point.1.floor() as i32);
+        for i in x - 1..=x + 1 {
+            for j in y - 1..=y + 1 {
+                for &n in self.pixels.get(&(i, j)).into_iter().flatten() {
+                    if self.points[n].distance(&point) < 0.5 {
+                        return n;
+                    }
+                }
+            }
+        }
+        self.points.push(point);
+        self.pixels
+            .entry((x, y))
+            .or_default()
+            .push(self.points.len() - 1);
+        self.points.len() - 1
+    }
+}
+
+/// A whole kite or dart, put back together from its two halves.
+#[derive(Clone, Debug)]
+struct Piece {
+    is_kite: bool,
+    /// Going clockwise, with the edge the halves share running from the first to the third.
+    corners: [Point; 4],
+    /// The piece on the other side of each side, starting with the one from the first corner to
+    /// the second.
+    across: [Option<usize>; 4],
+    /// Which sides have been knocked through, counting the way in and the way out.
+    open: [bool; 4],
+}
+
+impl Piece {
+    /// Halfway between the middles of the two halves, which is inside even a dart.
+    fn center(&self) -> Point {
+        let [a, b, c, d] = self.corners;
+        Point(
+            (a.0 + c.0) / 3.0 + (b.0 + d.0) / 6.0,
+            (a.1 + c.1) / 3.0 + (b.1 + d.1) / 6.0,
+        )
+    }
+
+    fn side(&self, k: usize) -> (Point, Point) {
+        (self.corners[k], self.corners[(k + 1) % 4])
+    }
+
+    /// The corners, pulled in towards the middle so the nearest side moves in by `inset`.
+    fn inset(&self, inset: f32) -> [Point; 4] {
+        let center = self.center();
+        let nearest = (0..4)
+            .map(|k| {
+                let (a, b) = self.side(k);
+                center.distance_to_line(&a, &b)
+            })
+            .fold(f32::MAX, f32::min);
+        let scale = (1.0 - inset / nearest).max(0.0);
+        self.corners.map(|corner| center.move_to(&corner, scale))
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        let [a, b, c, d] = self.corners;
+        in_triangle(p, a, b, c) || in_triangle(p, a, c, d)
+    }
+
+    /// Open up the side facing the outside that's nearest `corner`, if there is one.
+    fn open_towards(&mut self, corner: Point) {
+        let nearest = (0..4)
+            .filter(|&k| self.across[k].is_none())
+            .min_by(|&k, &l| {
+                let middle = |k| {
+                    let (a, b) = self.side(k);
+                    a.move_to(&b, 0.5).distance(&corner)
+                };
+                middle(k).total_cmp(&middle(l))
+            });
+        if let Some(k) = nearest {
+            self.open[k] = true;
+        }
+    }
+}
+
+/// A maze made out of a tiling. Each kite or dart that's entirely on the `width` by `height`
+/// board is a cell, `(i, 0)`, numbered roughly row by row.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    /// The size of the board, in squares, like any other board.
+    pub width: usize,
+    pub height: usize,
+    /// All of the tiling, including the tiles hanging over the edges that aren't part of the maze.
+    pub tiles: Vec<Tile>,
+    pieces: Vec<Piece>,
+    start: Option<usize>,
+    goal: Option<usize>,
+    /// The furthest apart the middles of two pieces next to each other are.
+    reach: f32,
+}
+
+impl Grid {
+    /// Just the tiles, before there are any cells to walk between.
+    pub fn tiling(width: usize, height: usize, tiles: Vec<Tile>) -> Self {
+        Self {
+            width,
+            height,
+            tiles,
+            pieces: vec![],
+            start: None,
+            goal: None,
+            reach: CELL_WIDTH,
+        }
+    }
+
+    /// Put the halves back together into kites and darts, and find out which ones are next to
+    /// each other, with all of the walls up.
+    pub fn new(width: usize, height: usize, tiles: Vec<Tile>) -> Self {
+        let (right, bottom) = (
+            OFFSET + width as f32 * CELL_WIDTH,
+            OFFSET + height as f32 * CELL_WIDTH,
+        );
+        let on_board = |Point(x, y): Point| x >= OFFSET && x <= right && y >= OFFSET && y <= bottom;
+
+        // Halves that share their middle edge make up a whole piece. The ones whose other half
+        // got pruned away, off the edge of the board, are left out.
+        let mut corners = Corners::default();
+        let mut halves: HashMap<(usize, usize), Vec<Tile>> = HashMap::new();
+        for tile in &tiles {
+            let (a, c) = (corners.number(tile.1), corners.number(tile.3));
+            halves.entry((a.min(c), a.max(c))).or_default().push(*tile);
+        }
+        let mut pieces: Vec<Piece> = halves
+            .values()
+            .filter_map(|pair| {
+                let [Tile(is_kite, a, b, c), Tile(other_kind, _, d, _)] = pair[..] else {
+                    return None;
+                };
+                let corners = if turn(a, b, c) > 0.0 {
+                    [a, b, c, d]
+                } else {
+                    [a, d, c, b]
+                };
+                (is_kite == other_kind && corners.iter().all(|&corner| on_board(corner))).then_some(
+                    Piece {
+                        is_kite,
+                        corners,
+                        across: [None; 4],
+                        open: [false; 4],
+                    },
+                )
+            })
+            .collect();
+        pieces.sort_by(|p, q| {
+            let (Point(x1, y1), Point(x2, y2)) = (p.center(), q.center());
+            ((y1 / CELL_WIDTH).floor(), x1)
+                .partial_cmp(&((y2 / CELL_WIDTH).floor(), x2))
+                .unwrap()
+        });
+
+        // Kites and darts always meet corner to corner, so each side is shared by two pieces at
+        // most.
+        let mut sides: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for (i, piece) in pieces.iter().enumerate() {
+            for k in 0..4 {
+                let (a, b) = piece.side(k);
+                let (a, b) = (corners.number(a), corners.number(b));
+                sides.entry((a.min(b), a.max(b))).or_default().push((i, k));
+            }
+        }
+        for pair in sides.values() {
+            if let [(i, k), (j, l)] = pair[..] {
+                pieces[i].across[k] = Some(j);
+                pieces[j].across[l] = Some(i);
+            }
+        }
+        let mut pieces = largest_group(pieces);
+
+        let nearest = |pieces: &[Piece], corner: Point| {
+            (0..pieces.len()).min_by(|&i, &j| {
+                let distance = |i: usize| pieces[i].center().distance(&corner);
+                distance(i).total_cmp(&distance(j))
+            })
+        };
+        let start = nearest(&pieces, Point(OFFSET, OFFSET));
+        let goal = nearest(&pieces, Point(right, bottom));
+        if let Some(i) = start {
+            pieces[i].open_towards(Point(OFFSET, OFFSET));
+        }
+        if let Some(i) = goal {
+            pieces[i].open_towards(Point(right, bottom));
+        }
+        let reach = (pieces.iter())
+            .flat_map(|piece| {
+                (piece.across.iter().flatten())
+                    .map(|&j| piece.center().distance(&pieces[j].center()))
+            })
+            .fold(f32::EPSILON, f32::max);
+
+        Self {
+            width,
+            height,
+            tiles,
+            pieces,
+            start,
+            goal,
+            reach,
+        }
+    }
+
+    /// Whether the tiles have been put together into cells yet.
+    pub fn has_cells(&self) -> bool {
+        !self.pieces.is_empty()
+    }
+
+    /// The pieces across each side of `cell`, going clockwise, with `None` wherever there's a wall.
+    pub fn sides(&self, (i, _): Cell) -> Vec<Option<Cell>> {
+        let piece = &self.pieces[i];
+        (0..4)
+            .map(|k| piece.across[k].filter(|_| piece.open[k]).map(|j| (j, 0)))
+            .collect()
+    }
+
+    /// Which side of the start is open to the outside.
+    pub fn entrance(&self) -> usize {
+        (self.start)
+            .and_then(|i| {
+                let piece = &self.pieces[i];
+                (0..4).find(|&k| piece.open[k] && piece.across[k].is_none())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Which of the four ways `to` mostly is from `from`, for walking with the arrow keys.
+    pub fn compass(&self, from: Cell, to: Cell) -> Direction {
+        let ((x1, y1), (x2, y2)) = (self.center(from), self.center(to));
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        if dx.abs() > dy.abs() {
+            if dx > 0.0 {
+                Direction::East
+            } else {
+                Direction::West
+            }
+        } else if dy > 0.0 {
+            Direction::South
+        } else {
+            Direction::North
+        }
+    }
+
+    /// How many steps apart two cells are at the very least, going from piece to piece in as
+    /// straight a line as the tiling allows.
+    pub fn distance(&self, from: Cell, to: Cell) -> usize {
+        let ((x1, y1), (x2, y2)) = (self.center(from), self.center(to));
+        ((x2 - x1).hypot(y2 - y1) / self.reach).ceil() as usize
+    }
+
+    /// Which side of `a` leads to `b`.
+    fn towards(&self, (a, _): Cell, (b, _): Cell) -> usize {
+        let across = self.pieces[a].across;
+        (0..4)
+            .find(|&k| across[k] == Some(b))
+            .unwrap_or_else(|| panic!("{} and {} aren't next to each other", a, b))
+    }
+
+    fn draw_little_robot(&self, canvas: &mut dyn Canvas, cell: Cell, color: Color) {
+        let corners = self.pieces[cell.0].inset(2.0);
+        for k in 0..4 {
+            let (a, b) = (corners[k], corners[(k + 1) % 4]);
+            canvas.draw_line(a.0, a.1, b.0, b.1, 4.0, color);
+        }
+        let (x, y) = self.center(cell);
+        let size = self.reach * 0.45;
+        canvas.draw_robot(x - size / 2.0, y - size / 2.0, size, size);
+    }
+}
+
+/// Only keep the pieces that can all get to each other, so that there's only one maze. The
+/// tiling can leave a few stragglers cut off in the corners of the board.
+fn largest_group(pieces: Vec<Piece>) -> Vec<Piece> {
+    let mut group = vec![None; pieces.len()];
+    let mut sizes = vec![];
+    for first in 0..pieces.len() {
+        if group[first].is_some() {
+            continue;
+        }
+        group[first] = Some(sizes.len());
+        let mut size = 1;
+        let mut queue = VecDeque::from([first]);
+        while let Some(i) = queue.pop_front() {
+            for &j in pieces[i].across.iter().flatten() {
+                if group[j].is_none() {
+                    group[j] = Some(sizes.len());
+                    size += 1;
+                    queue.push_back(j);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+    let Some(largest) = (0..sizes.len()).max_by_key(|&g| sizes[g]) else {
+        return pieces;
+    };
+
+    let mut renumbered = vec![None; pieces.len()];
+    let mut count = 0;
+    for i in 0..pieces.len() {
+        if group[i] == Some(largest) {
+            renumbered[i] = Some(count);
+            count += 1;
+        }
+    }
+    (pieces.into_iter().enumerate())
+        .filter(|&(i, _)| group[i] == Some(largest))
+        .map(|(_, mut piece)| {
+            piece.across = piece.across.map(|j| j.and_then(|j| renumbered[j]));
+            piece
+        })
+        .collect()
+}
+
+impl CellGraph for Grid {
+    const SHAPE: Shape = Shape::Penrose;
+
+    /// The king's tiling, which has no randomness in it, just the order the tiles get split in.
+    fn empty(width: usize, height: usize) -> Self {
+        let mut tiling = Exports::new(Variant::King, width, height, Rng::new(0));
+        while matches!(
+            tiling.state,
+            State::Setup | State::Deflating | State::Growing
+        ) {
+            tiling.update();
+        }
+        tiling.grid
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.pieces.len(), 1)
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        (0..self.pieces.len()).map(|i| (i, 0)).collect()
+    }
+
+    fn neighbours(&self, (i, _): Cell) -> Vec<Cell> {
+        (self.pieces[i].across.iter().flatten())
+            .map(|&j| (j, 0))
+            .collect()
+    }
+
+    fn links(&self, cell: Cell) -> Vec<Cell> {
+        self.sides(cell).into_iter().flatten().collect()
+    }
+
+    fn link(&mut self, a: Cell, b: Cell) {
+        let (k, l) = (self.towards(a, b), self.towards(b, a));
+        self.pieces[a.0].open[k] = true;
+        self.pieces[b.0].open[l] = true;
+    }
+
+    fn unlink(&mut self, a: Cell, b: Cell) {
+        let (k, l) = (self.towards(a, b), self.towards(b, a));
+        self.pieces[a.0].open[k] = false;
+        self.pieces[b.0].open[l] = false;
+    }
+
+    fn start(&self) -> Option<Cell> {
+        self.start.map(|i| (i, 0))
+    }
+
+    fn goal(&self) -> Option<Cell> {
+        self.goal.map(|i| (i, 0))
+    }
+
+    /// The pieces don't line up, so go to whichever one is most nearly that way.
+    fn step(&self, cell: Cell, heading: Heading) -> Option<Cell> {
+        let Heading::Square(direction) = heading else {
+            return None;
+        };
+        let (x, y) = self.center(cell);
+        let straightness = |next: Cell| {
+            let (nx, ny) = self.center(next);
+            let (dx, dy) = (nx - x, ny - y);
+            dx.abs().max(dy.abs()) / dx.hypot(dy)
+        };
+        (self.links(cell).into_iter())
+            .filter(|&next| self.compass(cell, next) == direction)
+            .max_by(|&a, &b| straightness(a).total_cmp(&straightness(b)))
+    }
+
+    fn maze(&self) -> Maze {
+        Maze::Penrose(self.clone())
+    }
+
+    fn pixel_size(&self) -> (f32, f32) {
+        board_size(self.width, self.height)
+    }
+
+    fn center(&self, (i, _): Cell) -> (f32, f32) {
+        let Point(x, y) = self.pieces[i].center();
+        (x, y)
+    }
+
+    fn cell_at(&self, (x, y): (f32, f32)) -> Option<Cell> {
+        (0..self.pieces.len())
+            .find(|&i| self.pieces[i].contains(Point(x, y)))
+            .map(|i| (i, 0))
+    }
+
+    fn walls(&self) -> Vec<Wall> {
+        let mut walls = vec![];
+        for (i, piece) in self.pieces.iter().enumerate() {
+            for k in 0..4 {
+                // Walls between two pieces belong to the first one.
+                if !piece.open[k] && piece.across[k].is_none_or(|j| i < j) {
+                    let (a, b) = piece.side(k);
+                    walls.push(((a.0, a.1), (b.0, b.1)));
+                }
+            }
+        }
+        walls
+    }
+
+    fn draw_cell(&self, canvas: &mut dyn Canvas, (i, _): Cell, inset: f32, color: Color) {
+        let [a, b, c, d] = self.pieces[i].inset(inset);
+        canvas.draw_triangle(a.into(), b.into(), c.into(), color);
+        canvas.draw_triangle(a.into(), c.into(), d.into(), color);
+    }
+
+    fn draw_current(&self, canvas: &mut dyn Canvas, cell: Cell, color: Color) {
+        self.draw_cell(canvas, cell, LINE_WIDTH * 1.5, color);
+    }
+
+    fn draw_path(&self, canvas: &mut dyn Canvas, path: &[Cell]) {
+        let mut color = COLORS[10];
+        if let Some((&last, rest)) = path.split_last() {
+            color.a = 0.6;
+            self.draw_little_robot(canvas, last, color);
+            color.a = 0.3;
+            for &cell in rest {
+                self.draw_cell(canvas, cell, 0.0, color)
+            }
+        }
+    }
+}
+
 #[derive(From)]
 pub struct Exports {
+    width: usize,
+    height: usize,
     state: State,
     variant: Variant,
     tiles: Vec<Tile>,
     small_tiles: Vec<Tile>,
+    /// The maze, once the tiles are small enough to build one out of.
+    grid: Grid,
+    /// The pieces the backtracker has gone through and can still go back to.
+    stack: Vec<usize>,
+    visited: Vec<bool>,
+    path: Vec<Cell>,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(variant: String) -> Self {
-        let variant = match variant.as_str() {
-            "sun" => Variant::Sun,
-            "star" => Variant::Star,
-            "ace" => Variant::Ace,
-            "deuce" => Variant::Deuce,
-            "jack" => Variant::Jack,
-            "queen" => Variant::Queen,
-            "king" => Variant::King,
-            _ => panic!("Unknown Variant \"{}\"!", variant),
-        };
-
-        let w = COLUMNS * CELL_WIDTH;
-        let h = ROWS * CELL_WIDTH;
+    pub fn new(variant: Variant, width: usize, height: usize, rng: Rng) -> Self {
+        let w = width as f32 * CELL_WIDTH;
+        let h = height as f32 * CELL_WIDTH;
         let x_center = w / 2.0 + OFFSET;
         let y_center = h / 2.0 + OFFSET;
 
         let tiles = variant.start_tiles(x_center, y_center);
         Self {
+            width,
+            height,
             state: State::Setup,
             variant,
             tiles,
             small_tiles: vec![],
+            grid: Grid::tiling(width, height, vec![]),
+            stack: vec![],
+            visited: vec![],
+            path: vec![],
+            rng,
+        }
+    }
+
+    /// How many halves to deflate to before building the maze, which makes about as many cells
+    /// as a square maze the same size would have.
+    fn enough(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Whether the tiles reach right out to the corners of the board, so the maze fills it.
+    fn covers_board(&self) -> bool {
+        let (right, bottom) = (
+            OFFSET + self.width as f32 * CELL_WIDTH,
+            OFFSET + self.height as f32 * CELL_WIDTH,
+        );
+        [
+            (OFFSET, OFFSET),
+            (right, OFFSET),
+            (OFFSET, bottom),
+            (right, bottom),
+        ]
+        .into_iter()
+        .all(|(x, y)| {
+            // Just inside, so that a corner on the edge between two tiles is still covered.
+            let x = x + (self.width as f32 * CELL_WIDTH / 2.0 + OFFSET - x).signum();
+            let y = y + (self.height as f32 * CELL_WIDTH / 2.0 + OFFSET - y).signum();
+            self.tiles.iter().any(|tile| tile.contains(Point(x, y)))
+        })
+    }
+
+    /// Carve one more step of a recursive backtracker through the pieces.
+    fn carve(&mut self) {
+        let Some(&current) = self.stack.last() else {
+            self.path.extend(self.grid.start());
+            self.state = State::Done;
+            log::info!("Done!");
+            return;
+        };
+        let unvisited: Vec<Cell> = (self.grid.neighbours((current, 0)).into_iter())
+            .filter(|&(i, _)| !self.visited[i])
+            .collect();
+        if let Some(next) = unvisited.choose(&mut self.rng) {
+            self.grid.link((current, 0), next);
+            self.visited[next.0] = true;
+            self.stack.push(next.0);
+        } else {
+            self.stack.pop();
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "penrose",
+    name: "Penrose Tiles",
+    params: &[Param::Choice(&Variant::NAMES)],
+    default_variant: "king",
+    shape: Shape::Penrose,
+    new: |values, width, height, rng| {
+        Box::new(Exports::new(
+            Variant::ALL[values.choice(0)],
+            width,
+            height,
+            rng,
+        ))
+    },
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         format!("Penrose {}", self.variant)
     }
     fn re_init(&mut self, variant: String) {
-        self.from(Exports::new(variant));
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(
+            Variant::ALL[values.choice(0)],
+            self.width,
+            self.height,
+            rng,
+        ));
     }
     fn get_variant(&self) -> String {
         self.variant.to_string().to_lowercase()
@@ -292,8 +864,8 @@ impl Algorithm for Exports {
                 return;
             }
             State::Growing => {
-                let w = COLUMNS * CELL_WIDTH;
-                let h = ROWS * CELL_WIDTH;
+                let w = self.width as f32 * CELL_WIDTH;
+                let h = self.height as f32 * CELL_WIDTH;
                 let x_center = w / 2.0 + OFFSET;
                 let y_center = h / 2.0 + OFFSET;
 
@@ -317,15 +889,21 @@ impl Algorithm for Exports {
                     })
                     .collect();
                 // log::info!("Shrank from {} to {} tiles.", size, self.tiles.len());
-                if self.tiles.len() < 2000 {
+                if self.tiles.len() < self.enough() {
                     self.state = State::Deflating;
                 }
                 return;
             }
-            _ => {}
+            State::Carving => {
+                self.carve();
+                return;
+            }
+            State::Deflating => {}
+            State::Done => return,
         }
 
-        for _ in 0..(self.tiles.len() + self.small_tiles.len()) / 10 {
+        // The ace and the deuce start out with fewer than ten halves, so always split at least one.
+        for _ in 0..((self.tiles.len() + self.small_tiles.len()) / 10).max(1) {
             if let Some(tile) = self.tiles.pop() {
                 self.small_tiles.extend(tile.subdivide());
             }
@@ -333,43 +911,117 @@ impl Algorithm for Exports {
 
         if self.tiles.is_empty() {
             std::mem::swap(&mut self.tiles, &mut self.small_tiles);
-            self.tiles.shuffle();
-            self.state = State::Growing;
+            self.tiles.shuffle(&mut self.rng);
+            if self.tiles.len() < self.enough() || !self.covers_board() {
+                self.state = State::Growing;
+                return;
+            }
+            // Tiny boards can be too small for two whole pieces, so split them again, without
+            // growing them back first.
+            let grid = Grid::new(self.width, self.height, self.tiles.clone());
+            if let (Some(start), Some(goal)) = (grid.start, grid.goal) {
+                if start != goal {
+                    self.grid = grid;
+                    self.visited = vec![false; self.grid.pieces.len()];
+                    self.visited[start] = true;
+                    self.stack.push(start);
+                    self.state = State::Carving;
+                }
+            }
         }
+    }
 
-        if self.state == State::Done {
-            log::info!("Done!");
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
         }
     }
 
-    fn draw(&self) {
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        if self.grid.has_cells() {
+            return Maze::Penrose(self.grid.clone());
+        }
+        Maze::Penrose(Grid::tiling(
+            self.width,
+            self.height,
+            (self.tiles.iter().chain(self.small_tiles.iter()))
+                .copied()
+                .collect(),
+        ))
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.width, self.height)
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        if self.grid.has_cells() {
+            for (i, piece) in self.grid.pieces.iter().enumerate() {
+                if self.visited[i] {
+                    let mut color = if piece.is_kite { COLORS[3] } else { COLORS[4] };
+                    color.a = 0.4;
+                    self.grid.draw_cell(canvas, (i, 0), 0.0, color);
+                }
+            }
+            let mut stack_color = COLORS[1];
+            stack_color.a = 0.5;
+            for &i in &self.stack {
+                self.grid.draw_cell(canvas, (i, 0), 0.0, stack_color);
+            }
+            self.grid.draw_board(canvas);
+            if let Some(&i) = self.stack.last() {
+                self.grid.draw_current(canvas, (i, 0), COLORS[1]);
+            }
+            self.grid.draw_path(canvas, &self.path);
+            return;
+        }
+
         for tile in &self.tiles {
-            tile.draw();
+            tile.draw(canvas);
         }
         for tile in &self.small_tiles {
-            tile.draw();
+            tile.draw(canvas);
         }
 
         let x = OFFSET;
         let y = OFFSET;
-        let w = COLUMNS * CELL_WIDTH;
-        let h = ROWS * CELL_WIDTH;
-        draw_rectangle(0.0, 0.0, x, y + h, WHITE);
-        draw_rectangle(0.0, 0.0, x + w, y, WHITE);
-        draw_rectangle(x + w, 0.0, x, h, WHITE);
-        draw_rectangle(0.0, y + h, w, y, WHITE);
-        draw_rectangle_lines(x, y, w, h, LINE_WIDTH, COLORS[0]);
+        let w = self.width as f32 * CELL_WIDTH;
+        let h = self.height as f32 * CELL_WIDTH;
+        // Mask off the tiles that hang over the edges, even if the window is bigger than we are.
+        let m = LENGTH * 4.0;
+        canvas.draw_rectangle(-m, -m, x + m, h + y * 2.0 + m * 2.0, WHITE);
+        canvas.draw_rectangle(-m, -m, w + x * 2.0 + m * 2.0, y + m, WHITE);
+        canvas.draw_rectangle(x + w, -m, x + m, h + y * 2.0 + m * 2.0, WHITE);
+        canvas.draw_rectangle(-m, y + h, w + x * 2.0 + m * 2.0, y + m, WHITE);
+        canvas.draw_rectangle_lines(x, y, w, h, LINE_WIDTH, COLORS[0]);
     }
 
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
     }
 
-    fn move_to(&mut self, _cursor: (f32, f32)) {
-        // pass
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
+
+#[test]
+fn finding_cells() {
+    let grid = Grid::empty(24, 16);
+    assert!(grid.size().0 > 100);
+    for cell in grid.cells() {
+        assert_eq!(grid.cell_at(grid.center(cell)), Some(cell));
+        for next in grid.neighbours(cell) {
+            assert!(grid.neighbours(next).contains(&cell));
+            assert_eq!(grid.distance(cell, next), 1);
+        }
     }
+    assert_ne!(grid.start(), grid.goal());
 }
@@ -0,0 +1,369 @@
+//! Boards of rings around a hole in the middle. The outer rings are longer, so their cells get
+//! split in two whenever they'd otherwise be too wide, to keep every cell about as wide as it is
+//! deep.
+
+use std::f32::consts::{PI, TAU};
+
+use enumset::EnumSet;
+use macroquad::color::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::Canvas;
+use crate::generator::Maze;
+use crate::graph::{Cell, CellGraph};
+use crate::registry::Shape;
+use crate::util::{Board, Heading, Wall, COLORS, LINE_WIDTH, OFFSET};
+
+/// Every ring is a row, from the inside out. The rings nearer the middle have fewer cells, so the
+/// ends of their rows are `None`.
+pub type Grid = Board<Option<EnumSet<Direction>>>;
+
+/// How deep each ring is, and about how wide each cell is.
+pub const CELL_WIDTH: f32 = 24.0;
+pub const ROWS: usize = 12;
+/// How many cells there are in the outside ring.
+pub const COLUMNS: usize = 64;
+
+/// The first cell of every ring starts straight up from the middle, and the rest go clockwise.
+const START: f32 = -PI / 2.0;
+
+/// How many pieces macroquad would cut a whole circle into, when drawing part of one.
+const SIDES: u8 = 200;
+
+#[derive(EnumSetType, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    Inward,
+    /// Where the ring outside splits each cell in two, this goes to the anticlockwise half.
+    Outward,
+    /// The clockwise half, where the ring outside splits each cell in two.
+    OutwardClockwise,
+    Clockwise,
+    Anticlockwise,
+}
+
+impl Direction {
+    /// The cell on the other side of this side of `(x, y)`, if there's a cell there.
+    pub fn offset<T>(self, (x, y): Cell, board: &Board<Option<T>>) -> Option<Cell> {
+        let size = ring_size(board, y);
+        let outside = (y + 1 < board.height()).then(|| ring_size(board, y + 1));
+        match self {
+            Direction::Inward if y > 0 => Some((x * ring_size(board, y - 1) / size, y - 1)),
+            Direction::Outward => Some((x * outside? / size, y + 1)),
+            Direction::OutwardClockwise => (outside? == size * 2).then_some((x * 2 + 1, y + 1)),
+            // Two cells in a ring touch on both sides, but only one of them can open, or they'd
+            // be linked twice.
+            Direction::Clockwise if size > 2 || (size == 2 && x == 0) => Some(((x + 1) % size, y)),
+            Direction::Anticlockwise if size > 2 || (size == 2 && x == 1) => {
+                Some(((x + size - 1) % size, y))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How many cells are in each ring, from the inside out. The outside ring has `width`, and each
+/// ring has half as many as the one outside it, if that doesn't make its cells too wide.
+pub fn ring_sizes(width: usize, height: usize) -> Vec<usize> {
+    let mut sizes = vec![width; height];
+    for y in (0..height.saturating_sub(1)).rev() {
+        let outside = sizes[y + 1];
+        let halved = TAU * radius(y as f32 + 0.5) / (outside / 2) as f32;
+        if outside.is_multiple_of(2) && halved <= CELL_WIDTH * 1.5 {
+            sizes[y] = outside / 2;
+        } else {
+            sizes[y] = outside;
+        }
+    }
+    sizes
+}
+
+/// How many cells are in ring `y`.
+pub fn ring_size<T>(board: &Board<Option<T>>, y: usize) -> usize {
+    board[y].iter().take_while(|cell| cell.is_some()).count()
+}
+
+pub fn init_grid<T: Copy>(width: usize, height: usize, value: T) -> Board<Option<T>> {
+    let mut grid = Board::new(width, height, None);
+    for (row, size) in grid.iter_mut().zip(ring_sizes(width, height)) {
+        row[..size].fill(Some(value));
+    }
+    grid
+}
+
+/// At least how many steps apart two cells are, ignoring walls. Going around is shorter nearer the
+/// middle, so this only counts the rings in between.
+pub fn distance(from: Cell, to: Cell) -> usize {
+    from.1.abs_diff(to.1)
+}
+
+/// How far from the middle ring `ring` starts, in pixels. Halfway through a ring works too.
+fn radius(ring: f32) -> f32 {
+    (ring + 1.0) * CELL_WIDTH
+}
+
+/// The middle of a board with `rings` rings, in pixels.
+fn middle(rings: usize) -> (f32, f32) {
+    let middle = radius(rings as f32) + OFFSET;
+    (middle, middle)
+}
+
+/// Where `(x, y)` starts and ends, going clockwise, in radians.
+fn angles((x, _): Cell, size: usize) -> (f32, f32) {
+    let width = TAU / size as f32;
+    (START + x as f32 * width, START + (x + 1) as f32 * width)
+}
+
+/// The size in pixels of a polar board, including the margins.
+pub fn board_size(_width: usize, height: usize) -> (f32, f32) {
+    let size = radius(height as f32) * 2.0 + OFFSET * 2.0;
+    (size, size)
+}
+
+/// The middle of the cell, halfway through its ring and halfway around it.
+pub fn center_pixel<T>((x, y): Cell, board: &Board<Option<T>>) -> (f32, f32) {
+    let (from, to) = angles((x, y), ring_size(board, y));
+    let (angle, radius) = ((from + to) / 2.0, radius(y as f32 + 0.5));
+    let (cx, cy) = middle(board.height());
+    (cx + radius * angle.cos(), cy + radius * angle.sin())
+}
+
+pub fn cell_from_pos<T>((x, y): (f32, f32), board: &Board<Option<T>>) -> Option<Cell> {
+    let (cx, cy) = middle(board.height());
+    let (dx, dy) = (x - cx, y - cy);
+    // The hole in the middle is as wide as a ring, so take that one off.
+    let y = ((dx.hypot(dy) / CELL_WIDTH) as usize).checked_sub(1)?;
+    if y >= board.height() {
+        return None;
+    }
+    let size = ring_size(board, y);
+    let angle = (dy.atan2(dx) - START).rem_euclid(TAU);
+    let x = ((angle / TAU * size as f32) as usize).min(size.checked_sub(1)?);
+    Some((x, y))
+}
+
+/// Part of a circle around the middle, as lines short enough that nobody can tell.
+fn arc((cx, cy): (f32, f32), radius: f32, from: f32, to: f32) -> Vec<Wall> {
+    let pieces = ((to - from) * radius / 4.0).ceil().max(1.0) as usize;
+    let point = |i: usize| {
+        let angle = from + (to - from) * i as f32 / pieces as f32;
+        (cx + radius * angle.cos(), cy + radius * angle.sin())
+    };
+    (0..pieces).map(|i| (point(i), point(i + 1))).collect()
+}
+
+/// The walls of a polar board, as line segments, leaving the entrance on the outside and the exit
+/// into the hole in the middle open.
+pub fn walls(grid: &Grid) -> Vec<Wall> {
+    let rings = grid.height();
+    let centre = middle(rings);
+    let mut walls = vec![];
+    for (x, y) in grid.cells() {
+        let cell = grid[y][x].unwrap_or_default();
+        let size = ring_size(grid, y);
+        let (from, to) = angles((x, y), size);
+        let (inner, outer) = (radius(y as f32), radius(y as f32 + 1.0));
+
+        // Every cell draws the sides it shares with the rings inside and the cells anticlockwise,
+        // which leaves the outside for the outer ring.
+        if !cell.contains(Direction::Inward) && (x, y) != (0, 0) {
+            walls.extend(arc(centre, inner, from, to));
+        }
+        if y + 1 == rings && x != 0 {
+            walls.extend(arc(centre, outer, from, to));
+        }
+        if size > 1 && !cell.contains(Direction::Anticlockwise) {
+            let (cos, sin) = (from.cos(), from.sin());
+            walls.push((
+                (centre.0 + inner * cos, centre.1 + inner * sin),
+                (centre.0 + outer * cos, centre.1 + outer * sin),
+            ));
+        }
+    }
+    walls
+}
+
+pub fn draw_board(canvas: &mut dyn Canvas, grid: &Grid) {
+    for ((x1, y1), (x2, y2)) in walls(grid) {
+        canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH, COLORS[0]);
+    }
+}
+
+pub fn draw_cell<T>(
+    canvas: &mut dyn Canvas,
+    (x, y): Cell,
+    board: &Board<Option<T>>,
+    inset: f32,
+    color: Color,
+) {
+    let (from, to) = angles((x, y), ring_size(board, y));
+    // Pull the ends in by about `inset` too, measured halfway through the ring.
+    let turn = inset / radius(y as f32 + 0.5);
+    let (cx, cy) = middle(board.height());
+    canvas.draw_arc(
+        cx,
+        cy,
+        SIDES,
+        radius(y as f32) + inset,
+        (from + turn).to_degrees(),
+        CELL_WIDTH - inset * 2.0,
+        (to - from - turn * 2.0).to_degrees(),
+        color,
+    );
+}
+
+fn draw_little_robot(canvas: &mut dyn Canvas, (x, y): Cell, grid: &Grid, color: Color) {
+    let size = ring_size(grid, y);
+    let (from, to) = angles((x, y), size);
+    let turn = 2.0 / radius(y as f32 + 0.5);
+    let (inner, outer) = (radius(y as f32) + 2.0, radius(y as f32 + 1.0) - 2.0);
+    let (cx, cy) = middle(grid.height());
+    let (start, arc) = (
+        (from + turn).to_degrees(),
+        (to - from - turn * 2.0).to_degrees(),
+    );
+    for edge in [inner, outer - 4.0] {
+        canvas.draw_arc(cx, cy, SIDES, edge, start, 4.0, arc, color);
+    }
+    if size > 1 {
+        for angle in [from + turn, to - turn] {
+            let (cos, sin) = (angle.cos(), angle.sin());
+            let (x1, y1) = (cx + inner * cos, cy + inner * sin);
+            let (x2, y2) = (cx + outer * cos, cy + outer * sin);
+            canvas.draw_line(x1, y1, x2, y2, 4.0, color);
+        }
+    }
+
+    let (x, y) = center_pixel((x, y), grid);
+    let size = CELL_WIDTH * 0.75;
+    canvas.draw_robot(x - size / 2.0, y - size / 2.0, size, size);
+}
+
+pub fn draw_path(canvas: &mut dyn Canvas, path: &[Cell], grid: &Grid) {
+    let mut color = COLORS[10];
+    if let Some((&last, rest)) = path.split_last() {
+        color.a = 0.6;
+        draw_little_robot(canvas, last, grid, color);
+        color.a = 0.3;
+        for &cell in rest {
+            draw_cell(canvas, cell, grid, 0.0, color)
+        }
+    }
+}
+
+impl CellGraph for Grid {
+    const SHAPE: Shape = Shape::Polar;
+
+    fn empty(width: usize, height: usize) -> Self {
+        init_grid(width, height, EnumSet::new())
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        (0..self.height())
+            .flat_map(|y| (0..ring_size(self, y)).map(move |x| (x, y)))
+            .collect()
+    }
+
+    fn neighbours(&self, cell: Cell) -> Vec<Cell> {
+        EnumSet::<Direction>::all()
+            .iter()
+            .filter_map(|direction| direction.offset(cell, self))
+            .collect()
+    }
+
+    fn links(&self, (x, y): Cell) -> Vec<Cell> {
+        self[y][x]
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|direction| direction.offset((x, y), self))
+            .collect()
+    }
+
+    fn link(&mut self, a: Cell, b: Cell) {
+        let (there, back) = (towards(self, a, b), towards(self, b, a));
+        self[a.1][a.0] = self[a.1][a.0].map(|cell| cell | there);
+        self[b.1][b.0] = self[b.1][b.0].map(|cell| cell | back);
+    }
+
+    fn unlink(&mut self, a: Cell, b: Cell) {
+        let (there, back) = (towards(self, a, b), towards(self, b, a));
+        self[a.1][a.0] = self[a.1][a.0].map(|cell| cell - there);
+        self[b.1][b.0] = self[b.1][b.0].map(|cell| cell - back);
+    }
+
+    fn start(&self) -> Option<Cell> {
+        self.height().checked_sub(1).map(|y| (0, y))
+    }
+
+    fn goal(&self) -> Option<Cell> {
+        (self.height() > 0).then_some((0, 0))
+    }
+
+    fn step(&self, (x, y): Cell, heading: Heading) -> Option<Cell> {
+        let Heading::Polar(direction) = heading else {
+            return None;
+        };
+        if !self[y][x].is_some_and(|cell| cell.contains(direction)) {
+            return None;
+        }
+        direction.offset((x, y), self)
+    }
+
+    fn maze(&self) -> Maze {
+        Maze::Polar(self.clone())
+    }
+
+    fn pixel_size(&self) -> (f32, f32) {
+        board_size(self.width(), self.height())
+    }
+
+    fn center(&self, cell: Cell) -> (f32, f32) {
+        center_pixel(cell, self)
+    }
+
+    fn cell_at(&self, pos: (f32, f32)) -> Option<Cell> {
+        cell_from_pos(pos, self)
+    }
+
+    fn walls(&self) -> Vec<Wall> {
+        walls(self)
+    }
+
+    fn draw_cell(&self, canvas: &mut dyn Canvas, cell: Cell, inset: f32, color: Color) {
+        draw_cell(canvas, cell, self, inset, color);
+    }
+
+    fn draw_current(&self, canvas: &mut dyn Canvas, cell: Cell, color: Color) {
+        draw_cell(canvas, cell, self, LINE_WIDTH * 1.5, color);
+    }
+
+    fn draw_path(&self, canvas: &mut dyn Canvas, path: &[Cell]) {
+        draw_path(canvas, path, self);
+    }
+}
+
+/// Which way `b` is from `a`, when they're next to each other.
+fn towards(grid: &Grid, a: Cell, b: Cell) -> Direction {
+    EnumSet::<Direction>::all()
+        .iter()
+        .find(|direction| direction.offset(a, grid) == Some(b))
+        .unwrap_or_else(|| panic!("{:?} and {:?} aren't next to each other", a, b))
+}
+
+#[test]
+fn finding_cells() {
+    let grid = Grid::empty(COLUMNS, ROWS);
+    let sizes = ring_sizes(COLUMNS, ROWS);
+    assert_eq!(sizes.last(), Some(&COLUMNS));
+    for (y, pair) in sizes.windows(2).enumerate() {
+        assert!(pair[1] == pair[0] || pair[1] == pair[0] * 2, "ring {}", y);
+    }
+    for cell in grid.cells() {
+        assert_eq!(cell_from_pos(center_pixel(cell, &grid), &grid), Some(cell));
+    }
+    let (x, y) = middle(ROWS);
+    assert_eq!(cell_from_pos((x, y), &grid), None);
+}
@@ -1,9 +1,12 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State, CELL_WIDTH,
-    COLORS, COLUMNS, EMPTY_COLOR, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State, CELL_WIDTH, COLORS, EMPTY_COLOR, LINE_WIDTH, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(From)]
@@ -11,15 +14,16 @@ pub struct Exports {
     path: Vec<(usize, usize)>,
     frontier: Vec<(usize, usize)>,
     grid: Grid,
-    grid_state: [[bool; COLUMNS as usize]; ROWS as usize],
+    grid_state: Board<bool>,
     state: State,
     debug: Vec<(usize, usize)>,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        let grid_state = [[false; COLUMNS as usize]; ROWS as usize];
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
+        let grid_state = Board::new(width, height, false);
 
         Self {
             path: vec![],
@@ -28,25 +32,38 @@ impl Exports {
             grid_state,
             state: State::Setup,
             debug: vec![],
+            rng,
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "prim",
+    name: "Prim",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Prim")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         if self.state == State::Setup {
             // Add an initial cell to the frontier…
             self.frontier
-                .push((gen_range(0, COLUMNS as usize), gen_range(0, ROWS as usize)));
+                .push((self.rng.gen_range(0, width), self.rng.gen_range(0, height)));
             self.state = State::Running;
             return;
         }
@@ -67,16 +84,16 @@ impl Algorithm for Exports {
         let mut directions = EnumSet::all();
         if x == 0 {
             directions ^= Direction::West;
-        } else if x == COLUMNS as usize - 1 {
+        } else if x == width - 1 {
             directions ^= Direction::East;
         }
         if y == 0 {
             directions ^= Direction::North;
-        } else if y == ROWS as usize - 1 {
+        } else if y == height - 1 {
             directions ^= Direction::South;
         }
         let mut directions: Vec<Direction> = directions.iter().collect();
-        directions.shuffle();
+        directions.shuffle(&mut self.rng);
         for direction in directions {
             let (new_x, new_y) = match direction {
                 Direction::North => (x, y - 1),
@@ -100,21 +117,38 @@ impl Algorithm for Exports {
                 }
             }
         }
-        self.frontier.shuffle();
+        self.frontier.shuffle(&mut self.rng);
+    }
+
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
     }
+}
 
-    fn draw(&self) {
-        draw_board(self.grid);
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         if self.state == State::Running {
             let curr_color = COLORS[1];
             let mut cell_color = COLORS[1];
             cell_color.a = 0.5;
 
-            for x in 0..COLUMNS as usize {
-                for y in 0..ROWS as usize {
+            for x in 0..self.grid.width() {
+                for y in 0..self.grid.height() {
                     if self.grid[y][x] == EnumSet::empty() && !self.frontier.contains(&(x, y)) {
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -126,7 +160,7 @@ impl Algorithm for Exports {
             }
             for (i, (x, y)) in self.frontier.iter().enumerate() {
                 if i == self.frontier.len() - 1 {
-                    draw_rectangle(
+                    canvas.draw_rectangle(
                         *x as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                         *y as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                         CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -134,7 +168,7 @@ impl Algorithm for Exports {
                         curr_color,
                     );
                 }
-                draw_rectangle(
+                canvas.draw_rectangle(
                     *x as f32 * CELL_WIDTH + OFFSET,
                     *y as f32 * CELL_WIDTH + OFFSET,
                     CELL_WIDTH,
@@ -143,21 +177,21 @@ impl Algorithm for Exports {
                 );
             }
         }
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
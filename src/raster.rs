@@ -0,0 +1,461 @@
+//! A software renderer for finished mazes, for machines without a display (or a GPU).
+
+use std::io::Write;
+
+use macroquad::{color::Color, math::Vec2};
+
+use crate::{
+    canvas::{poly_corners, Canvas},
+    generator::Maze,
+    graph::{Cell, CellGraph},
+    heatmap::HeatMap,
+    penrose::{Point, Tile},
+    util::{self, Wall, CELL_WIDTH, COLORS, LINE_WIDTH, OFFSET, WHITE},
+};
+
+lazy_static! {
+    static ref ROBOT: Image = Image::decode(include_bytes!("../static/little_guy.png"));
+}
+
+/// How many samples to take across each pixel, in each direction, to smooth the edges.
+const SAMPLES: usize = 4;
+
+pub struct Style {
+    /// Shade in the shortest path from the entrance to the exit.
+    pub solution: bool,
+    /// Draw in the same colours as the window does, instead of black on white.
+    pub palette: bool,
+    /// Colour each cell by how far it is from the entrance, and trace the longest path.
+    pub heatmap: bool,
+}
+
+/// A decoded PNG file.
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    fn decode(bytes: &[u8]) -> Self {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().expect("Couldn't read the PNG header");
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .expect("Couldn't read the PNG image");
+        let channels = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks(channels)
+            .map(|pixel| match *pixel {
+                [grey] => Color::from_rgba(grey, grey, grey, 255),
+                [grey, alpha] => Color::from_rgba(grey, grey, grey, alpha),
+                [r, g, b] => Color::from_rgba(r, g, b, 255),
+                [r, g, b, a, ..] => Color::from_rgba(r, g, b, a),
+                _ => unreachable!(),
+            })
+            .collect();
+        Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        }
+    }
+}
+
+/// An RGBA image that we can draw lines and shapes into.
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Raster {
+    pub fn new(width: f32, height: f32, background: Color) -> Self {
+        let (width, height) = (width.ceil() as usize, height.ceil() as usize);
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Fill every pixel the `inside` test covers, within the bounding box from `min` to `max`.
+    fn fill(
+        &mut self,
+        min: (f32, f32),
+        max: (f32, f32),
+        color: Color,
+        inside: impl Fn(f32, f32) -> bool,
+    ) {
+        let x_range =
+            (min.0.floor().max(0.0) as usize)..(max.0.ceil().max(0.0) as usize).min(self.width);
+        let y_range =
+            (min.1.floor().max(0.0) as usize)..(max.1.ceil().max(0.0) as usize).min(self.height);
+        let step = 1.0 / SAMPLES as f32;
+        for y in y_range {
+            for x in x_range.clone() {
+                let mut hits = 0;
+                for j in 0..SAMPLES {
+                    for i in 0..SAMPLES {
+                        let sample_x = x as f32 + (i as f32 + 0.5) * step;
+                        let sample_y = y as f32 + (j as f32 + 0.5) * step;
+                        if inside(sample_x, sample_y) {
+                            hits += 1;
+                        }
+                    }
+                }
+                if hits > 0 {
+                    let coverage = hits as f32 / (SAMPLES * SAMPLES) as f32;
+                    self.blend(x, y, color, coverage);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        let pixel = &mut self.pixels[y * self.width + x];
+        let alpha = color.a * coverage;
+        pixel.r += (color.r - pixel.r) * alpha;
+        pixel.g += (color.g - pixel.g) * alpha;
+        pixel.b += (color.b - pixel.b) * alpha;
+        pixel.a += (1.0 - pixel.a) * alpha;
+    }
+
+    /// Fill a convex polygon, with its points in either order.
+    pub fn draw_polygon(&mut self, points: &[(f32, f32)], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let min = points
+            .iter()
+            .fold((f32::MAX, f32::MAX), |(x, y), p| (x.min(p.0), y.min(p.1)));
+        let max = points
+            .iter()
+            .fold((f32::MIN, f32::MIN), |(x, y), p| (x.max(p.0), y.max(p.1)));
+        self.fill(min, max, color, |x, y| {
+            let mut sign = 0.0;
+            for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                let cross = (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0);
+                if cross * sign < 0.0 {
+                    return false;
+                }
+                if cross != 0.0 {
+                    sign = cross;
+                }
+            }
+            true
+        });
+    }
+
+    /// Draw an RGBA image, stretched over the rectangle, on top of what's already there.
+    fn draw_image(&mut self, image: &Image, x: f32, y: f32, w: f32, h: f32) {
+        let x_range = (x.max(0.0) as usize)..((x + w).ceil().max(0.0) as usize).min(self.width);
+        let y_range = (y.max(0.0) as usize)..((y + h).ceil().max(0.0) as usize).min(self.height);
+        for j in y_range {
+            for i in x_range.clone() {
+                let u = (i as f32 + 0.5 - x) / w;
+                let v = (j as f32 + 0.5 - y) / h;
+                if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                    continue;
+                }
+                let (u, v) = (
+                    (u * image.width as f32) as usize,
+                    (v * image.height as f32) as usize,
+                );
+                let color = image.pixels[v * image.width + u];
+                self.blend(i, j, color, 1.0);
+            }
+        }
+    }
+
+    pub fn write_png(&self, writer: impl Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba())
+    }
+
+    /// The pixels, as eight bits each of red, green, blue, and alpha.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&pixel| -> [u8; 4] { pixel.into() })
+            .collect()
+    }
+}
+
+impl Canvas for Raster {
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        let radius = thickness / 2.0;
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length_squared = dx * dx + dy * dy;
+        self.fill(
+            (x1.min(x2) - radius, y1.min(y2) - radius),
+            (x1.max(x2) + radius, y1.max(y2) + radius),
+            color,
+            |x, y| {
+                // How far along the line is the closest point to us?
+                let t = if length_squared == 0.0 {
+                    0.0
+                } else {
+                    (((x - x1) * dx + (y - y1) * dy) / length_squared).clamp(0.0, 1.0)
+                };
+                let (px, py) = (x1 + t * dx - x, y1 + t * dy - y);
+                px * px + py * py <= radius * radius
+            },
+        );
+    }
+
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        self.fill((x, y), (x + w, y + h), color, |i, j| {
+            i >= x && i <= x + w && j >= y && j <= y + h
+        });
+    }
+
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        // The lines go on the inside of the rectangle, like macroquad's do.
+        let t = thickness.min(w / 2.0).min(h / 2.0);
+        self.draw_rectangle(x, y, w, t, color);
+        self.draw_rectangle(x, y + h - t, w, t, color);
+        self.draw_rectangle(x, y + t, t, h - t * 2.0, color);
+        self.draw_rectangle(x + w - t, y + t, t, h - t * 2.0, color);
+    }
+
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color) {
+        self.draw_polygon(&[(v1.x, v1.y), (v2.x, v2.y), (v3.x, v3.y)], color);
+    }
+
+    fn draw_poly(&mut self, x: f32, y: f32, sides: u8, radius: f32, rotation: f32, color: Color) {
+        self.draw_polygon(&poly_corners(x, y, sides, radius, rotation), color);
+    }
+
+    fn draw_poly_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        let corners = poly_corners(x, y, sides, radius, rotation);
+        for (a, b) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+            self.draw_line(a.0, a.1, b.0, b.1, thickness, color);
+        }
+    }
+
+    fn draw_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        _sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        arc: f32,
+        color: Color,
+    ) {
+        // Every pixel gets tested against the real curve, so there's no need to cut it into sides.
+        let (from, sweep) = (rotation.to_radians(), arc.to_radians());
+        let outer = radius + thickness;
+        let points: Vec<_> = (0..=64)
+            .flat_map(|i| {
+                let angle = from + sweep * i as f32 / 64.0;
+                [radius, outer].map(|r| (x + r * angle.cos(), y + r * angle.sin()))
+            })
+            .collect();
+        let min = points
+            .iter()
+            .fold((f32::MAX, f32::MAX), |(x, y), p| (x.min(p.0), y.min(p.1)));
+        let max = points
+            .iter()
+            .fold((f32::MIN, f32::MIN), |(x, y), p| (x.max(p.0), y.max(p.1)));
+        self.fill(
+            (min.0 - 1.0, min.1 - 1.0),
+            (max.0 + 1.0, max.1 + 1.0),
+            color,
+            |i, j| {
+                let (dx, dy) = (i - x, j - y);
+                let distance = dx.hypot(dy);
+                (radius..=outer).contains(&distance)
+                    && (dy.atan2(dx) - from).rem_euclid(std::f32::consts::TAU) <= sweep
+            },
+        );
+    }
+
+    fn draw_robot(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.draw_image(&ROBOT, x, y, w, h);
+    }
+}
+
+/// Draw a finished maze the way the window would, without needing a window.
+pub fn render(maze: &Maze, style: &Style) -> Raster {
+    let (wall_color, path_color) = if style.palette {
+        let mut path_color = COLORS[10];
+        path_color.a = 0.3;
+        (COLORS[0], path_color)
+    } else {
+        (
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            Color::new(0.0, 0.0, 0.0, 0.2),
+        )
+    };
+    let solution = if style.solution {
+        maze.solution()
+    } else {
+        vec![]
+    };
+    let heat_map = maze
+        .start()
+        .filter(|_| style.heatmap)
+        .map(|start| HeatMap::new(maze.clone(), start));
+
+    let (solution, heat_map) = (&solution, heat_map.as_ref());
+    match maze {
+        Maze::Square(grid) => render_graph(grid, solution, heat_map, wall_color, path_color),
+        Maze::Hex(grid) => render_graph(grid, solution, heat_map, wall_color, path_color),
+        Maze::Delta(grid) => render_graph(grid, solution, heat_map, wall_color, path_color),
+        Maze::Polar(grid) => render_graph(grid, solution, heat_map, wall_color, path_color),
+        Maze::Penrose(grid) if grid.has_cells() => {
+            render_graph(grid, solution, heat_map, wall_color, path_color)
+        }
+        Maze::Penrose(grid) => draw_penrose(&grid.tiles, grid.width, grid.height, style.palette),
+    }
+}
+
+/// Draw a board of cells, with the solution and the heat map's distances under the walls, and the
+/// heat map's longest path over them.
+fn render_graph<G: CellGraph>(
+    grid: &G,
+    solution: &[Cell],
+    heat_map: Option<&HeatMap>,
+    wall_color: Color,
+    path_color: Color,
+) -> Raster {
+    let (width, height) = grid.pixel_size();
+    let mut raster = Raster::new(width, height, WHITE);
+    for &cell in solution {
+        grid.draw_cell(&mut raster, cell, 0.0, path_color);
+    }
+    if let Some(heat_map) = heat_map {
+        heat_map.draw_distances(&mut raster);
+    }
+    draw_walls(&mut raster, &grid.walls(), wall_color);
+    if let Some(heat_map) = heat_map {
+        heat_map.draw_longest(&mut raster);
+    }
+    raster
+}
+
+fn draw_walls(raster: &mut Raster, walls: &[Wall], color: Color) {
+    for &((x1, y1), (x2, y2)) in walls {
+        raster.draw_line(x1, y1, x2, y2, LINE_WIDTH, color);
+    }
+}
+
+fn draw_penrose(tiles: &[Tile], width: usize, height: usize, palette: bool) -> Raster {
+    let (w, h) = (width as f32 * CELL_WIDTH, height as f32 * CELL_WIDTH);
+    let (board_width, board_height) = util::board_size(width, height);
+    let mut raster = Raster::new(board_width, board_height, WHITE);
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+    for tile in tiles {
+        let Tile(is_kite, a, b, c) = *tile;
+        let (edge, fill) = match (palette, is_kite) {
+            (true, true) => (COLORS[2], COLORS[3]),
+            (true, false) => (COLORS[2], COLORS[4]),
+            (false, true) => (black, Color::new(0.0, 0.0, 0.0, 0.4)),
+            (false, false) => (black, Color::new(0.0, 0.0, 0.0, 0.1)),
+        };
+        let corners: Vec<_> = tile.fill().iter().map(|&Point(x, y)| (x, y)).collect();
+        raster.draw_polygon(&corners, fill);
+        raster.draw_line(a.0, a.1, b.0, b.1, LINE_WIDTH, edge);
+        raster.draw_line(b.0, b.1, c.0, c.1, LINE_WIDTH, edge);
+    }
+
+    // Mask off the tiles that hang over the edges, like the window does.
+    raster.draw_rectangle(0.0, 0.0, OFFSET, board_height, WHITE);
+    raster.draw_rectangle(0.0, 0.0, board_width, OFFSET, WHITE);
+    raster.draw_rectangle(OFFSET + w, 0.0, OFFSET, board_height, WHITE);
+    raster.draw_rectangle(0.0, OFFSET + h, board_width, OFFSET, WHITE);
+    let border = if palette { COLORS[0] } else { black };
+    raster.draw_line(OFFSET, OFFSET, OFFSET + w, OFFSET, LINE_WIDTH, border);
+    raster.draw_line(
+        OFFSET + w,
+        OFFSET,
+        OFFSET + w,
+        OFFSET + h,
+        LINE_WIDTH,
+        border,
+    );
+    raster.draw_line(
+        OFFSET + w,
+        OFFSET + h,
+        OFFSET,
+        OFFSET + h,
+        LINE_WIDTH,
+        border,
+    );
+    raster.draw_line(OFFSET, OFFSET + h, OFFSET, OFFSET, LINE_WIDTH, border);
+    raster
+}
+
+#[test]
+fn render_draws_walls_and_the_solution() {
+    use crate::{generator::Generator, kruskal, util::Rng};
+
+    let maze = kruskal::Exports::new(5, 4, Rng::new(1)).run();
+    let style = Style {
+        solution: true,
+        palette: false,
+        heatmap: false,
+    };
+    let raster = render(&maze, &style);
+    let (width, height) = util::board_size(5, 4);
+    assert_eq!(
+        (raster.width(), raster.height()),
+        (width as usize, height as usize)
+    );
+
+    let rgba = raster.to_rgba();
+    let pixel = |(x, y): (f32, f32)| {
+        let i = (y as usize * raster.width() + x as usize) * 4;
+        rgba[i]
+    };
+    let center = |(x, y): Cell| {
+        (
+            (x as f32 + 0.5) * CELL_WIDTH + OFFSET,
+            (y as f32 + 0.5) * CELL_WIDTH + OFFSET,
+        )
+    };
+    // The walls around the outside are black, apart from the entrance on the left of the first
+    // cell, which is shaded in with the rest of the solution.
+    assert_eq!(pixel((OFFSET + CELL_WIDTH / 2.0, OFFSET)), 0);
+    assert_eq!(pixel((OFFSET, OFFSET + CELL_WIDTH * 1.5)), 0);
+    assert_eq!(pixel((OFFSET, OFFSET + CELL_WIDTH / 2.0)), 204);
+    let solution = maze.solution();
+    assert!(solution.len() < 5 * 4);
+    for (x, y) in (0..4).flat_map(|y| (0..5).map(move |x| (x, y))) {
+        let shade = if solution.contains(&(x, y)) { 204 } else { 255 };
+        assert_eq!(pixel(center((x, y))), shade, "({}, {})", x, y);
+    }
+}
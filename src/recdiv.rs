@@ -1,9 +1,12 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, Direction, Grid, Playable, State, CELL_WIDTH, COLORS,
-    COLUMNS, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, Direction, Grid, Heading, Playable, Rng,
+    State, CELL_WIDTH, COLORS, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(PartialEq, Eq, Debug)]
@@ -18,18 +21,21 @@ pub struct Exports {
     grid: Grid,
     stack: Vec<(usize, usize, usize, usize)>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new() -> Self {
-        let mut grid = [[EnumSet::all(); COLUMNS as usize]; ROWS as usize];
-        for x in 0..COLUMNS as usize {
-            grid[0][x].remove(Direction::North);
-            grid[ROWS as usize - 1][x].remove(Direction::South);
+    pub fn new(width: usize, height: usize, rng: Rng) -> Self {
+        let mut grid = Board::new(width, height, EnumSet::all());
+        for cell in grid[0].iter_mut() {
+            cell.remove(Direction::North);
+        }
+        for cell in grid[height - 1].iter_mut() {
+            cell.remove(Direction::South);
         }
         for row in grid.iter_mut() {
             row[0].remove(Direction::West);
-            row[COLUMNS as usize - 1].remove(Direction::East);
+            row[width - 1].remove(Direction::East);
         }
 
         Self {
@@ -37,6 +43,7 @@ impl Exports {
             grid,
             stack: vec![],
             state: State::Setup,
+            rng,
         }
     }
 
@@ -45,7 +52,7 @@ impl Exports {
             Orientation::Horizontal
         } else if height < width {
             Orientation::Vertical
-        } else if gen_range(0, 2) == 0 {
+        } else if self.rng.gen_range(0, 2) == 0 {
             Orientation::Horizontal
         } else {
             Orientation::Vertical
@@ -53,19 +60,31 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "recdiv",
+    name: "Recursive Division",
+    params: &[],
+    default_variant: "unused",
+    shape: Shape::Square,
+    new: |_values, width, height, rng| Box::new(Exports::new(width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         String::from("Recursive Division")
     }
     fn re_init(&mut self, _variant: String) {
-        self.from(Exports::new());
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        self.from(Exports::new(width, height, rng));
     }
     fn get_variant(&self) -> String {
         "unused".to_owned()
     }
     fn update(&mut self) {
         if self.state == State::Setup {
-            self.stack.push((0, 0, COLUMNS as usize, ROWS as usize));
+            self.stack
+                .push((0, 0, self.grid.width(), self.grid.height()));
             self.state = State::Running;
             return;
         }
@@ -83,13 +102,15 @@ impl Algorithm for Exports {
         match orientation {
             Orientation::Horizontal => {
                 // log::info!("GenRange 1 {}-{}", y, y + height);
-                let wall_y = gen_range(y, y + height - 1);
+                let wall_y = self.rng.gen_range(y, y + height - 1);
                 // log::info!("GenRange 2 {}-{}", x, x + width);
-                let passage_x = gen_range(x, x + width);
+                let passage_x = self.rng.gen_range(x, x + width);
                 for i in x..x + width {
                     self.grid[wall_y][i].remove(Direction::South);
+                    self.grid[wall_y + 1][i].remove(Direction::North);
                 }
                 self.grid[wall_y][passage_x].insert(Direction::South);
+                self.grid[wall_y + 1][passage_x].insert(Direction::North);
 
                 let new_height = wall_y - y + 1;
                 if width >= 2 && new_height >= 2 {
@@ -103,13 +124,15 @@ impl Algorithm for Exports {
             }
             Orientation::Vertical => {
                 // log::info!("GenRange 3 {}-{}", x, x + width);
-                let wall_x = gen_range(x, x + width - 1);
+                let wall_x = self.rng.gen_range(x, x + width - 1);
                 // log::info!("GenRange 4 {}-{}", y, y + height);
-                let passage_y = gen_range(y, y + height);
+                let passage_y = self.rng.gen_range(y, y + height);
                 for row in self.grid.iter_mut().skip(y).take(height) {
                     row[wall_x].remove(Direction::East);
+                    row[wall_x + 1].remove(Direction::West);
                 }
                 self.grid[passage_y][wall_x].insert(Direction::East);
+                self.grid[passage_y][wall_x + 1].insert(Direction::West);
 
                 let new_width = wall_x - x + 1;
                 if new_width >= 2 && height >= 2 {
@@ -129,8 +152,25 @@ impl Algorithm for Exports {
         });
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         if self.state == State::Running {
             for (i, (x, y, width, height)) in self.stack.iter().enumerate() {
@@ -138,7 +178,7 @@ impl Algorithm for Exports {
                 if i != self.stack.len() - 1 {
                     cell_color.a = 0.3;
                 }
-                draw_rectangle(
+                canvas.draw_rectangle(
                     *x as f32 * CELL_WIDTH + OFFSET,
                     *y as f32 * CELL_WIDTH + OFFSET,
                     *width as f32 * CELL_WIDTH,
@@ -147,24 +187,49 @@ impl Algorithm for Exports {
                 );
             }
         }
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> State {
-        self.state
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
         &mut self.path
     }
 }
+
+#[test]
+fn seeking_back_restores_the_stack() {
+    use crate::history::History;
+    use std::any::Any;
+
+    let inside = |algorithm: &dyn Algorithm| {
+        let recdiv = (algorithm as &dyn Any).downcast_ref::<Exports>().unwrap();
+        (recdiv.grid.clone(), recdiv.stack.clone())
+    };
+
+    let mut algorithm = ENTRY.build(&ENTRY.defaults(), (Some(8), Some(6)), Rng::new(3));
+    let mut history = History::new();
+    let mut steps = vec![];
+    while algorithm.get_state() != State::Done {
+        steps.push(inside(algorithm.as_ref()));
+        algorithm.update();
+        history.record();
+    }
+    for (step, before) in steps.iter().enumerate().rev() {
+        history.seek(&ENTRY, &mut algorithm, step);
+        while history.replay(algorithm.as_mut()) {}
+        assert_eq!(inside(algorithm.as_ref()), *before, "step {}", step);
+    }
+}
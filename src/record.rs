@@ -0,0 +1,185 @@
+//! Recording a whole run of an algorithm as an animated GIF or PNG, one frame every few steps.
+
+use std::io::{self, Write};
+
+use crate::{
+    generator::State,
+    raster::Raster,
+    util::{Algorithm, WHITE},
+};
+
+/// How long to show each frame, in hundredths of a second.
+const FRAME_DELAY: u16 = 4;
+/// How long to hold the finished maze before looping, in hundredths of a second.
+const LAST_FRAME_DELAY: u16 = 300;
+/// Turn away anything that takes longer than this, so the file doesn't get out of hand.
+pub const MAX_FRAMES: usize = 1500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    Png,
+}
+
+impl Format {
+    /// Figure out the format from a file name, like "wilson.gif" or "kruskal.apng".
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "gif" => Some(Format::Gif),
+            "png" | "apng" => Some(Format::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Run the algorithm to the end, calling `frame` at the start, every `every` steps, and at the
+/// end. Returns how many frames that was.
+fn run(
+    algorithm: &mut dyn Algorithm,
+    every: usize,
+    mut frame: impl FnMut(&dyn Algorithm),
+) -> usize {
+    let every = every.max(1);
+    let mut frames = 1;
+    let mut steps = 0;
+    frame(algorithm);
+    while algorithm.get_state() != State::Done {
+        algorithm.update();
+        steps += 1;
+        if steps % every == 0 || algorithm.get_state() == State::Done {
+            frames += 1;
+            frame(algorithm);
+        }
+    }
+    frames
+}
+
+/// How many frames `run` will call for. If that's more than `MAX_FRAMES`, it's an error saying how
+/// far apart they'd need to be instead, since a recording that stops halfway never shows the maze.
+fn count_frames(algorithm: &mut dyn Algorithm, every: usize) -> io::Result<usize> {
+    let mut steps: usize = 0;
+    while algorithm.get_state() != State::Done {
+        algorithm.update();
+        steps += 1;
+    }
+    let frames = 1 + steps.div_ceil(every.max(1));
+    if frames > MAX_FRAMES {
+        return Err(io::Error::other(format!(
+            "{} takes {} steps, which is more than {} frames. Try --every {} or more.",
+            algorithm.name(),
+            steps,
+            MAX_FRAMES,
+            steps.div_ceil(MAX_FRAMES - 1)
+        )));
+    }
+    Ok(frames)
+}
+
+fn draw(algorithm: &dyn Algorithm) -> Raster {
+    let (width, height) = algorithm.get_size();
+    let mut raster = Raster::new(width, height, WHITE);
+    algorithm.draw(&mut raster);
+    raster
+}
+
+/// Record a run of the algorithm that `make` builds. It's run once beforehand to count the frames,
+/// so `make` has to build the same algorithm every time. Nothing gets written if there are too many.
+pub fn record(
+    make: impl Fn() -> Box<dyn Algorithm>,
+    every: usize,
+    format: Format,
+    writer: impl Write,
+) -> io::Result<usize> {
+    let frames = count_frames(make().as_mut(), every)?;
+    match format {
+        Format::Gif => record_gif(make(), every, writer),
+        Format::Png => record_png(make(), every, frames, writer),
+    }
+}
+
+fn record_gif(
+    mut algorithm: Box<dyn Algorithm>,
+    every: usize,
+    writer: impl Write,
+) -> io::Result<usize> {
+    let (width, height) = algorithm.get_size();
+    let (width, height) = (width.ceil() as u16, height.ceil() as u16);
+    let mut encoder = gif::Encoder::new(writer, width, height, &[]).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    // Hang on to the last frame, so that we can hold it for longer once we know it's the last.
+    let mut previous: Option<gif::Frame> = None;
+    let mut result = Ok(());
+    let frames = run(algorithm.as_mut(), every, |algorithm| {
+        let mut pixels = draw(algorithm).to_rgba();
+        let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+        frame.delay = FRAME_DELAY;
+        if let Some(previous) = previous.replace(frame) {
+            if result.is_ok() {
+                result = encoder.write_frame(&previous);
+            }
+        }
+    });
+    result.map_err(io::Error::other)?;
+    if let Some(mut last) = previous {
+        last.delay = LAST_FRAME_DELAY;
+        encoder.write_frame(&last).map_err(io::Error::other)?;
+    }
+    Ok(frames)
+}
+
+fn record_png(
+    mut algorithm: Box<dyn Algorithm>,
+    every: usize,
+    frames: usize,
+    writer: impl Write,
+) -> io::Result<usize> {
+    let (width, height) = algorithm.get_size();
+    let mut encoder = png::Encoder::new(writer, width.ceil() as u32, height.ceil() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames as u32, 0)
+        .map_err(io::Error::other)?;
+    encoder
+        .set_frame_delay(FRAME_DELAY, 100)
+        .map_err(io::Error::other)?;
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+
+    let mut written = 0;
+    let mut result = Ok(());
+    run(algorithm.as_mut(), every, |algorithm| {
+        if result.is_err() {
+            return;
+        }
+        written += 1;
+        if written == frames {
+            result = writer.set_frame_delay(LAST_FRAME_DELAY, 100);
+        }
+        let pixels = draw(algorithm).to_rgba();
+        if result.is_ok() {
+            result = writer.write_image_data(&pixels);
+        }
+    });
+    result.map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(frames)
+}
+
+#[test]
+fn long_recordings_are_turned_away() {
+    use crate::{kruskal, util::Rng};
+
+    let make = || Box::new(kruskal::Exports::new(60, 40, Rng::new(1))) as Box<dyn Algorithm>;
+    let mut bytes = vec![];
+    let error = record(make, 1, Format::Gif, &mut bytes).unwrap_err();
+    assert!(error.to_string().contains("--every"), "{}", error);
+    assert!(bytes.is_empty());
+
+    let frames = record(make, 10_000, Format::Gif, &mut bytes).unwrap();
+    assert_eq!(frames, 2);
+    assert!(!bytes.is_empty());
+}
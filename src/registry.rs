@@ -0,0 +1,189 @@
+//! Every algorithm in one table, so the command line, the web page, and everything that builds a
+//! maze by name all agree on what there is.
+
+use macroquad::logging as log;
+
+use crate::{
+    aldous_broder, binarytree, blobby, delta_util, eller, graph_aldous_broder, graph_eller,
+    graph_growingtree, graph_huntandkill, graph_kruskal, graph_prim, graph_wilson, growingtree,
+    hex_blobby, hex_util, houston, huntandkill, kruskal, origin_shift, parallel,
+    params::{Param, ParamError, Values},
+    penrose, polar_util, prim, recdiv, sidewinder,
+    util::{self, Algorithm, Rng},
+    wilson,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+    Square,
+    Hex,
+    Delta,
+    Polar,
+    Penrose,
+}
+
+impl Shape {
+    /// What to put in front of an algorithm's name, to say which kind of board it's on.
+    pub fn name(self) -> &'static str {
+        match self {
+            Shape::Square => "Square",
+            Shape::Hex => "Hex",
+            Shape::Delta => "Delta",
+            Shape::Polar => "Polar",
+            Shape::Penrose => "Penrose",
+        }
+    }
+}
+
+pub struct Entry {
+    /// What to call it on the command line and in the web page's address.
+    pub id: &'static str,
+    pub name: &'static str,
+    /// What goes in its variant, or nothing if it doesn't have one.
+    pub params: &'static [Param],
+    pub default_variant: &'static str,
+    pub shape: Shape,
+    pub new: fn(&Values, usize, usize, Rng) -> Box<dyn Algorithm>,
+}
+
+impl Entry {
+    pub fn parse(&self, variant: &str) -> Result<Values, ParamError> {
+        Values::parse(self.params, variant, self.default_variant)
+    }
+
+    pub fn defaults(&self) -> Values {
+        self.parse(self.default_variant).unwrap()
+    }
+
+    /// For when there's nobody to show an error to, like when restarting the same algorithm.
+    pub fn parse_or_default(&self, variant: &str) -> Values {
+        self.parse(variant).unwrap_or_else(|error| {
+            log::error!("Bad variant for {}: {}", self.name, error);
+            self.defaults()
+        })
+    }
+
+    /// Every variant it has.
+    pub fn variants(&self) -> Vec<Values> {
+        Values::all(self.params)
+    }
+
+    /// How big the board is when no size is given.
+    pub fn default_size(&self) -> (usize, usize) {
+        match self.shape {
+            Shape::Hex => (hex_util::COLUMNS, hex_util::ROWS),
+            Shape::Delta => (delta_util::COLUMNS, delta_util::ROWS),
+            Shape::Polar => (polar_util::COLUMNS, polar_util::ROWS),
+            _ => (util::COLUMNS, util::ROWS),
+        }
+    }
+
+    /// The size of the board, falling back to the default size for anything that's missing.
+    pub fn size(&self, (width, height): (Option<usize>, Option<usize>)) -> (usize, usize) {
+        let (default_width, default_height) = self.default_size();
+        (
+            width.unwrap_or(default_width),
+            height.unwrap_or(default_height),
+        )
+    }
+
+    /// Whether a board of this size can hold a maze. Hex boards are cut down to a hexagon, which
+    /// leaves some rows empty when they're taller than they are wide.
+    pub fn check_size(&self, width: usize, height: usize) -> Result<(), String> {
+        if self.shape == Shape::Hex && width < height {
+            return Err(format!(
+                "{} needs a board at least as wide as it is tall, not {}x{}",
+                self.name, width, height
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build it, falling back to the default size for anything that's missing.
+    pub fn build(
+        &self,
+        values: &Values,
+        size: (Option<usize>, Option<usize>),
+        rng: Rng,
+    ) -> Box<dyn Algorithm> {
+        let (width, height) = self.size(size);
+        (self.new)(values, width, height, rng)
+    }
+}
+
+/// The first one is the default.
+pub const ALGORITHMS: [Entry; 40] = [
+    parallel::ENTRY,
+    eller::ENTRY,
+    kruskal::ENTRY,
+    prim::ENTRY,
+    recdiv::ENTRY,
+    blobby::ENTRY,
+    aldous_broder::ENTRY,
+    wilson::ENTRY,
+    houston::ENTRY,
+    huntandkill::ENTRY,
+    growingtree::ENTRY,
+    binarytree::ENTRY,
+    sidewinder::ENTRY,
+    origin_shift::ENTRY,
+    parallel::HEX_ENTRY,
+    hex_blobby::ENTRY,
+    graph_eller::HEX_ENTRY,
+    graph_kruskal::HEX_ENTRY,
+    graph_prim::HEX_ENTRY,
+    graph_aldous_broder::HEX_ENTRY,
+    graph_wilson::HEX_ENTRY,
+    graph_huntandkill::HEX_ENTRY,
+    graph_growingtree::HEX_ENTRY,
+    parallel::DELTA_ENTRY,
+    graph_eller::DELTA_ENTRY,
+    graph_kruskal::DELTA_ENTRY,
+    graph_prim::DELTA_ENTRY,
+    graph_aldous_broder::DELTA_ENTRY,
+    graph_wilson::DELTA_ENTRY,
+    graph_huntandkill::DELTA_ENTRY,
+    graph_growingtree::DELTA_ENTRY,
+    parallel::POLAR_ENTRY,
+    graph_eller::POLAR_ENTRY,
+    graph_kruskal::POLAR_ENTRY,
+    graph_prim::POLAR_ENTRY,
+    graph_aldous_broder::POLAR_ENTRY,
+    graph_wilson::POLAR_ENTRY,
+    graph_huntandkill::POLAR_ENTRY,
+    graph_growingtree::POLAR_ENTRY,
+    penrose::ENTRY,
+];
+
+pub fn find(id: &str) -> Option<&'static Entry> {
+    ALGORITHMS.iter().find(|entry| entry.id == id)
+}
+
+/// Where `id` is in the table.
+pub fn position(id: &str) -> Option<usize> {
+    ALGORITHMS.iter().position(|entry| entry.id == id)
+}
+
+#[test]
+fn every_variant_builds() {
+    for entry in &ALGORITHMS {
+        assert_eq!(find(entry.id).map(|found| found.name), Some(entry.name));
+        assert_eq!(entry.defaults().to_string(), entry.default_variant);
+        for values in entry.variants() {
+            let algorithm = entry.build(&values, (Some(8), Some(6)), Rng::new(1));
+            assert_eq!(algorithm.get_variant(), values.to_string(), "{}", entry.id);
+        }
+    }
+}
+
+#[test]
+fn hex_boards_are_no_taller_than_they_are_wide() {
+    for entry in ALGORITHMS.iter().filter(|entry| entry.shape == Shape::Hex) {
+        assert!(entry.check_size(2, 5).is_err(), "{}", entry.id);
+        // As tall as it can be, which leaves a single zigzag of cells.
+        for values in entry.variants() {
+            let mut algorithm = entry.build(&values, (Some(5), Some(5)), Rng::new(1));
+            assert!(algorithm.run().start().is_some(), "{} {}", entry.id, values);
+        }
+    }
+}
@@ -0,0 +1,450 @@
+//! Finished square, hex, delta and polar mazes as JSON, so they can be stored and walked through
+//! again later. Penrose mazes aren't on a board of cells, so they can't be saved yet.
+//!
+//! The schema looks like this, with `algorithm` the registry id it was built with, `cells` indexed
+//! by row and then column, and every cell listing the directions you can leave it by. Cells that
+//! aren't on a hex or polar board are `null`.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "algorithm": "kruskal",
+//!   "name": "Kruskal",
+//!   "variant": "unused",
+//!   "seed": 1234,
+//!   "width": 2,
+//!   "height": 2,
+//!   "start": [0, 0],
+//!   "goal": [1, 1],
+//!   "topology": "square",
+//!   "cells": [[["East"], ["West", "South"]], [["East"], ["North", "West"]]]
+//! }
+//! ```
+
+use enumset::EnumSet;
+use serde::{de::Error as _, Deserialize, Serialize};
+
+use crate::{
+    canvas::Canvas,
+    delta_util,
+    generator::{Generator, Maze, State},
+    graph::{self, Cell, CellGraph},
+    hex_util, polar_util,
+    util::{self, Algorithm, Board, Heading},
+};
+
+/// The version of the schema that [`to_json`] writes, and [`from_json`] reads.
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedMaze {
+    pub version: u32,
+    /// The id of the registry entry that built it.
+    pub algorithm: String,
+    /// What the generator called itself, for the window title.
+    pub name: String,
+    pub variant: String,
+    pub seed: u64,
+    pub width: usize,
+    pub height: usize,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    #[serde(flatten)]
+    pub cells: Cells,
+}
+
+/// The open directions of each cell, tagged with the shape of the board.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "topology", content = "cells", rename_all = "lowercase")]
+pub enum Cells {
+    Square(Vec<Vec<Vec<util::Direction>>>),
+    Hex(Vec<Vec<Option<Vec<hex_util::Direction>>>>),
+    Delta(Vec<Vec<Vec<delta_util::Direction>>>),
+    Polar(Vec<Vec<Option<Vec<polar_util::Direction>>>>),
+}
+
+impl SavedMaze {
+    /// Save whatever the generator from the registry's `id` entry has built. Penrose mazes are cut
+    /// out of a tiling rather than a board of cells, so they can't be saved.
+    pub fn new(id: &str, generator: &(impl Generator + ?Sized)) -> Option<Self> {
+        let maze = generator.get_maze();
+        let (width, height, cells) = match &maze {
+            Maze::Square(grid) => (
+                grid.width(),
+                grid.height(),
+                Cells::Square(
+                    grid.iter()
+                        .map(|row| row.iter().map(|cell| cell.iter().collect()).collect())
+                        .collect(),
+                ),
+            ),
+            Maze::Hex(grid) => (
+                grid.width(),
+                grid.height(),
+                Cells::Hex(
+                    grid.iter()
+                        .map(|row| {
+                            row.iter()
+                                .map(|cell| cell.map(|cell| cell.iter().collect()))
+                                .collect()
+                        })
+                        .collect(),
+                ),
+            ),
+            Maze::Delta(grid) => (
+                grid.width(),
+                grid.height(),
+                Cells::Delta(
+                    grid.iter()
+                        .map(|row| row.iter().map(|cell| cell.iter().collect()).collect())
+                        .collect(),
+                ),
+            ),
+            Maze::Polar(grid) => (
+                grid.width(),
+                grid.height(),
+                Cells::Polar(
+                    grid.iter()
+                        .map(|row| {
+                            row.iter()
+                                .map(|cell| cell.map(|cell| cell.iter().collect()))
+                                .collect()
+                        })
+                        .collect(),
+                ),
+            ),
+            Maze::Penrose(_) => return None,
+        };
+        Some(Self {
+            version: VERSION,
+            algorithm: id.to_owned(),
+            name: generator.name(),
+            variant: generator.get_variant(),
+            seed: generator.get_seed(),
+            width,
+            height,
+            start: maze.start()?,
+            goal: maze.goal()?,
+            cells,
+        })
+    }
+
+    /// The maze, back on a board.
+    pub fn maze(&self) -> Maze {
+        match &self.cells {
+            Cells::Square(rows) => {
+                let mut grid = Board::new(self.width, self.height, EnumSet::new());
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, cell) in row.iter().enumerate() {
+                        grid[y][x] = cell.iter().copied().collect();
+                    }
+                }
+                Maze::Square(grid)
+            }
+            Cells::Hex(rows) => {
+                let mut grid = Board::new(self.width, self.height, None);
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, cell) in row.iter().enumerate() {
+                        grid[y][x] = cell.as_ref().map(|cell| cell.iter().copied().collect());
+                    }
+                }
+                Maze::Hex(grid)
+            }
+            Cells::Delta(rows) => {
+                let mut grid = Board::new(self.width, self.height, EnumSet::new());
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, cell) in row.iter().enumerate() {
+                        grid[y][x] = cell.iter().copied().collect();
+                    }
+                }
+                Maze::Delta(grid)
+            }
+            Cells::Polar(rows) => {
+                let mut grid = Board::new(self.width, self.height, None);
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, cell) in row.iter().enumerate() {
+                        grid[y][x] = cell.as_ref().map(|cell| cell.iter().copied().collect());
+                    }
+                }
+                Maze::Polar(grid)
+            }
+        }
+    }
+
+    /// Load the maze into something the robot can walk through, starting at the entrance.
+    pub fn playable(&self) -> Box<dyn Algorithm> {
+        let path = vec![self.start];
+        match self.maze() {
+            Maze::Square(grid) => Box::new(Exports {
+                saved: self.clone(),
+                grid,
+                path,
+            }),
+            Maze::Hex(grid) => Box::new(Exports {
+                saved: self.clone(),
+                grid,
+                path,
+            }),
+            Maze::Delta(grid) => Box::new(Exports {
+                saved: self.clone(),
+                grid,
+                path,
+            }),
+            Maze::Polar(grid) => Box::new(Exports {
+                saved: self.clone(),
+                grid,
+                path,
+            }),
+            Maze::Penrose(_) => unreachable!("Penrose mazes are never saved"),
+        }
+    }
+
+    /// Make sure the cells fit the board, so that `maze` and `playable` can't go out of bounds.
+    fn check(&self) -> Result<(), String> {
+        if self.version != VERSION {
+            return Err(format!("unsupported version {}", self.version));
+        }
+        let rows = match &self.cells {
+            Cells::Square(rows) => rows.iter().map(|row| row.len()).collect::<Vec<_>>(),
+            Cells::Hex(rows) => rows.iter().map(|row| row.len()).collect(),
+            Cells::Delta(rows) => rows.iter().map(|row| row.len()).collect(),
+            Cells::Polar(rows) => rows.iter().map(|row| row.len()).collect(),
+        };
+        if rows.len() != self.height || rows.iter().any(|&len| len != self.width) {
+            return Err(format!("expected {}x{} cells", self.width, self.height));
+        }
+        if matches!(self.cells, Cells::Hex(_)) && self.width < self.height {
+            return Err(format!(
+                "hex boards must be at least as wide as they are tall, not {}x{}",
+                self.width, self.height
+            ));
+        }
+        // The rings have to split where the board would split them, or the cells won't line up.
+        if let Cells::Polar(rows) = &self.cells {
+            let sizes = polar_util::ring_sizes(self.width, self.height);
+            let misplaced = rows.iter().zip(sizes).any(|(row, size)| {
+                (row.iter().enumerate()).any(|(x, cell)| cell.is_some() != (x < size))
+            });
+            if misplaced {
+                return Err(String::from(
+                    "polar rings must be the sizes the board gives them",
+                ));
+            }
+        }
+        // Every open side has to lead to a cell that's open back, or walking through it would go
+        // off the board, or somewhere you can't walk back from.
+        let two_sided = match self.maze() {
+            Maze::Square(grid) => two_sided(&grid, |(x, y)| grid[y][x].len()),
+            Maze::Hex(grid) => two_sided(&grid, |(x, y)| grid[y][x].map_or(0, |cell| cell.len())),
+            Maze::Delta(grid) => two_sided(&grid, |(x, y)| grid[y][x].len()),
+            Maze::Polar(grid) => two_sided(&grid, |(x, y)| grid[y][x].map_or(0, |cell| cell.len())),
+            Maze::Penrose(_) => unreachable!("Penrose mazes are never saved"),
+        };
+        if !two_sided {
+            return Err(String::from(
+                "every open side must lead to a cell that's open back",
+            ));
+        }
+        let on_board = |(x, y): (usize, usize)| match &self.cells {
+            Cells::Square(_) | Cells::Delta(_) => x < self.width && y < self.height,
+            Cells::Hex(rows) => rows
+                .get(y)
+                .and_then(|row| row.get(x))
+                .is_some_and(Option::is_some),
+            Cells::Polar(rows) => rows
+                .get(y)
+                .and_then(|row| row.get(x))
+                .is_some_and(Option::is_some),
+        };
+        if !on_board(self.start) || !on_board(self.goal) {
+            return Err(String::from("start and goal must be on the board"));
+        }
+        Ok(())
+    }
+}
+
+/// Whether every open side of every cell, of which there are `sides`, is a link to a cell that
+/// links back. `links` leaves out the sides that lead off the board, so those don't count.
+fn two_sided<G: CellGraph>(grid: &G, sides: impl Fn(Cell) -> usize) -> bool {
+    grid.cells().into_iter().all(|cell| {
+        let links = grid.links(cell);
+        links.len() == sides(cell) && links.iter().all(|&next| grid.links(next).contains(&cell))
+    })
+}
+
+/// Write a saved maze as a single line of JSON.
+pub fn to_json(saved: &SavedMaze) -> String {
+    serde_json::to_string(saved).unwrap()
+}
+
+/// Read a saved maze back, checking that it's a version we know and that it hangs together.
+pub fn from_json(json: &str) -> serde_json::Result<SavedMaze> {
+    let saved: SavedMaze = serde_json::from_str(json)?;
+    saved.check().map_err(serde_json::Error::custom)?;
+    Ok(saved)
+}
+
+/// A saved maze, loaded back into the player. It's already done, so all there is to do is walk.
+pub struct Exports<G> {
+    saved: SavedMaze,
+    grid: G,
+    path: Vec<(usize, usize)>,
+}
+
+impl<G> Generator for Exports<G> {
+    fn name(&self) -> String {
+        self.saved.name.clone()
+    }
+    fn re_init(&mut self, _variant: String) {
+        // There's nothing to build again, so just send the robot back to the start.
+        self.path = vec![self.saved.start];
+    }
+    fn get_variant(&self) -> String {
+        self.saved.variant.clone()
+    }
+    fn update(&mut self) {}
+
+    fn get_state(&self) -> State {
+        State::Done
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.saved.seed
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.saved.maze()
+    }
+}
+
+impl<G: CellGraph> Algorithm for Exports<G> {
+    fn get_size(&self) -> (f32, f32) {
+        self.grid.pixel_size()
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.grid.draw_board(canvas);
+        self.grid.draw_path(canvas, &self.path);
+    }
+
+    fn move_to(&mut self, pos: (f32, f32)) {
+        graph::move_to(&self.grid, &mut self.path, pos);
+    }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        graph::walk(&self.grid, &mut self.path, heading)
+    }
+}
+
+#[test]
+fn round_trip() {
+    use crate::{graph_kruskal, hex_blobby, kruskal, util::Rng};
+
+    let mut kruskal = kruskal::Exports::new(6, 4, Rng::new(1));
+    let maze = kruskal.run();
+    let json = to_json(&SavedMaze::new("kruskal", &kruskal).unwrap());
+    let saved = from_json(&json).unwrap();
+    assert_eq!(
+        (
+            saved.algorithm.as_str(),
+            saved.name.as_str(),
+            saved.seed,
+            saved.start,
+            saved.goal
+        ),
+        ("kruskal", "Kruskal", 1, (0, 0), (5, 3))
+    );
+    assert_eq!(saved.maze().solution(), maze.solution());
+
+    let mut hex = hex_blobby::Exports::new(12, 6, Rng::new(2));
+    let maze = hex.run();
+    let saved = from_json(&to_json(&SavedMaze::new("hexblobby", &hex).unwrap())).unwrap();
+    let (Maze::Hex(before), Maze::Hex(after)) = (maze, saved.maze()) else {
+        panic!("Expected a hex maze");
+    };
+    assert_eq!(before, after);
+
+    let mut delta = graph_kruskal::DeltaExports::new(9, 5, Rng::new(3));
+    let maze = delta.run();
+    let saved = from_json(&to_json(&SavedMaze::new("deltakruskal", &delta).unwrap())).unwrap();
+    let (Maze::Delta(before), Maze::Delta(after)) = (maze, saved.maze()) else {
+        panic!("Expected a delta maze");
+    };
+    assert_eq!(before, after);
+
+    let mut polar = graph_kruskal::PolarExports::new(24, 6, Rng::new(4));
+    let maze = polar.run();
+    let saved = from_json(&to_json(&SavedMaze::new("polarkruskal", &polar).unwrap())).unwrap();
+    let (Maze::Polar(before), Maze::Polar(after)) = (maze, saved.maze()) else {
+        panic!("Expected a polar maze");
+    };
+    assert_eq!(before, after);
+
+    assert!(from_json(&json.replace(r#""width":6"#, r#""width":7"#)).is_err());
+}
+
+#[test]
+fn broken_mazes_are_rejected() {
+    use crate::{graph_kruskal, hex_blobby, kruskal, util::Rng};
+
+    let error = |saved: &SavedMaze| from_json(&to_json(saved)).unwrap_err().to_string();
+
+    let mut kruskal = kruskal::Exports::new(6, 4, Rng::new(1));
+    kruskal.run();
+    let saved = SavedMaze::new("kruskal", &kruskal).unwrap();
+    let Cells::Square(rows) = &saved.cells else {
+        panic!("Expected a square maze");
+    };
+    // Out of the east side of the board, and a passage that's only open from one end.
+    let mut off_board = rows.clone();
+    off_board[0][5].push(util::Direction::East);
+    let mut one_sided = rows.clone();
+    one_sided[0][0].clear();
+    for rows in [off_board, one_sided] {
+        let broken = SavedMaze {
+            cells: Cells::Square(rows),
+            ..saved.clone()
+        };
+        assert!(error(&broken).contains("open back"));
+    }
+
+    let mut hex = hex_blobby::Exports::new(12, 6, Rng::new(2));
+    hex.run();
+    let saved = SavedMaze::new("hexblobby", &hex).unwrap();
+    let Cells::Hex(rows) = &saved.cells else {
+        panic!("Expected a hex maze");
+    };
+    let mut off_board = rows.clone();
+    let x = off_board[0].iter().position(Option::is_some).unwrap();
+    off_board[0][x]
+        .as_mut()
+        .unwrap()
+        .push(hex_util::Direction::NorthWest);
+    let broken = SavedMaze {
+        cells: Cells::Hex(off_board),
+        ..saved.clone()
+    };
+    assert!(error(&broken).contains("open back"));
+    let tall = SavedMaze {
+        width: 6,
+        height: 12,
+        cells: Cells::Hex(vec![vec![None; 6]; 12]),
+        ..saved.clone()
+    };
+    assert!(error(&tall).contains("as wide as"));
+
+    let mut polar = graph_kruskal::PolarExports::new(24, 6, Rng::new(4));
+    polar.run();
+    let saved = SavedMaze::new("polarkruskal", &polar).unwrap();
+    let Cells::Polar(rows) = &saved.cells else {
+        panic!("Expected a polar maze");
+    };
+    // An extra cell past the end of the middle ring.
+    let mut misplaced = rows.clone();
+    let x = misplaced[0].iter().position(Option::is_none).unwrap();
+    misplaced[0][x] = Some(vec![]);
+    let broken = SavedMaze {
+        cells: Cells::Polar(misplaced),
+        ..saved.clone()
+    };
+    assert!(error(&broken).contains("sizes the board gives"));
+}
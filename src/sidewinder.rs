@@ -1,9 +1,13 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, Direction, Grid, Playable, State as BaseState, CELL_WIDTH,
-    COLORS, COLUMNS, FIELD_COLOR, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, Direction, Grid, Heading, Playable, Rng,
+    State as BaseState, CELL_WIDTH, COLORS, FIELD_COLOR, LINE_WIDTH, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{logging as log, prelude::draw_rectangle, rand::gen_range};
+use macroquad::logging as log;
 use maze_utils::From;
 
 #[derive(PartialEq, Eq, Debug)]
@@ -22,11 +26,12 @@ pub struct Exports {
     harder: bool,
     run_start: usize,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(variant: bool) -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
+    pub fn new(variant: bool, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
 
         Self {
             path: vec![],
@@ -35,6 +40,7 @@ impl Exports {
             harder: variant,
             run_start: 0,
             state: State::Setup,
+            rng,
         }
     }
 
@@ -51,7 +57,16 @@ impl Exports {
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "sidewinder",
+    name: "Sidewinder",
+    params: &[Param::Flag("easy", "hard")],
+    default_variant: "hard",
+    shape: Shape::Square,
+    new: |values, width, height, rng| Box::new(Exports::new(values.flag(0), width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         if self.harder {
             String::from("Harder Sidewinder")
@@ -60,7 +75,10 @@ impl Algorithm for Exports {
         }
     }
     fn re_init(&mut self, variant: String) {
-        self.from(Exports::new(variant == "hard"));
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(values.flag(0), width, height, rng));
     }
     fn get_variant(&self) -> String {
         if self.harder {
@@ -70,6 +88,7 @@ impl Algorithm for Exports {
         }
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         match self.state {
             State::Setup => {
                 self.curr = (0, 0);
@@ -78,12 +97,12 @@ impl Algorithm for Exports {
             }
             State::Running => {
                 let proportion = if self.harder {
-                    (0.4 + (self.curr.0 as f64 / COLUMNS as f64) * 0.4) * 100.0
+                    (0.4 + (self.curr.0 as f64 / width as f64) * 0.4) * 100.0
                 } else {
                     50.0
                 } as usize;
-                if (gen_range(0, 100) < proportion || self.curr.1 == 0)
-                    && self.curr.0 < COLUMNS as usize - 1
+                if (self.rng.gen_range(0, 100) < proportion || self.curr.1 == 0)
+                    && self.curr.0 < width - 1
                 {
                     // Carve a path to the east…
                     self.carve(self.curr, Direction::East);
@@ -95,16 +114,16 @@ impl Algorithm for Exports {
             State::Carving => {
                 self.curr.0 += 1;
                 if self.curr.1 > 0 {
-                    let north = gen_range(self.run_start, self.curr.0);
+                    let north = self.rng.gen_range(self.run_start, self.curr.0);
                     self.carve((north, self.curr.1), Direction::North);
                     self.run_start = self.curr.0;
                 }
 
-                if self.curr.0 == COLUMNS as usize {
+                if self.curr.0 == width {
                     self.curr = (0, self.curr.1 + 1);
                     self.run_start = 0;
                 }
-                if self.curr.1 == ROWS as usize {
+                if self.curr.1 == height {
                     self.path.push((0, 0));
                     self.curr.1 += 1;
                     self.state = State::Done;
@@ -118,8 +137,30 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
+        let (columns, rows) = (self.grid.width() as f32, self.grid.height() as f32);
 
         let curr_color = COLORS[1];
         let mut cell_color = COLORS[1];
@@ -127,28 +168,28 @@ impl Algorithm for Exports {
 
         // Draw the field.
         let y = self.curr.1 as f32 + 1.0;
-        if y <= ROWS {
-            draw_rectangle(
+        if y <= rows {
+            canvas.draw_rectangle(
                 0.0 * CELL_WIDTH + OFFSET,
                 y * CELL_WIDTH + OFFSET,
-                COLUMNS * CELL_WIDTH,
-                (ROWS - y) * CELL_WIDTH,
+                columns * CELL_WIDTH,
+                (rows - y) * CELL_WIDTH,
                 FIELD_COLOR,
             );
         }
 
         let x = self.curr.0 as f32 + 1.0;
         let y = y - 1.0;
-        draw_rectangle(
+        canvas.draw_rectangle(
             x * CELL_WIDTH + OFFSET,
             y * CELL_WIDTH + OFFSET,
-            (COLUMNS - x) * CELL_WIDTH,
+            (columns - x) * CELL_WIDTH,
             CELL_WIDTH,
             FIELD_COLOR,
         );
 
         let start = self.run_start as f32;
-        draw_rectangle(
+        canvas.draw_rectangle(
             start * CELL_WIDTH + OFFSET,
             y * CELL_WIDTH + OFFSET,
             (x - start) * CELL_WIDTH,
@@ -157,7 +198,7 @@ impl Algorithm for Exports {
         );
 
         let x = x - 1.0;
-        draw_rectangle(
+        canvas.draw_rectangle(
             x * CELL_WIDTH + LINE_WIDTH + OFFSET,
             y * CELL_WIDTH + LINE_WIDTH + OFFSET,
             CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -165,25 +206,21 @@ impl Algorithm for Exports {
             curr_color,
         );
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -0,0 +1,544 @@
+//! Solving finished mazes, one step at a time, so the search can be watched like the generators.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use macroquad::{color::Color, logging as log};
+
+use crate::{
+    canvas::Canvas,
+    delta_util,
+    generator::{Generator, Maze, State},
+    graph::CellGraph,
+    hex_util, polar_util,
+    util::{self, Algorithm, Board, COLORS, FIELD_COLOR, LINE_WIDTH},
+};
+
+/// What each solver is called on the command line and in the web page's address.
+pub const SOLVERS: [&str; 6] = ["bfs", "dfs", "astar", "deadend", "leftwall", "rightwall"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    Unknown(String),
+    /// The maze is missing its entrance or its exit, like a saved one that was edited by hand.
+    NoWayThrough,
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Unknown(variant) => {
+                write!(f, "'{}' isn't one of: {}", variant, SOLVERS.join(", "))
+            }
+            SolveError::NoWayThrough => {
+                write!(f, "only mazes with an entrance and an exit can be solved")
+            }
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    BreadthFirst,
+    DepthFirst,
+    AStar,
+    DeadEnd,
+    LeftWall,
+    RightWall,
+}
+
+pub struct Exports {
+    maze: Maze,
+    /// The seed the maze was built from.
+    seed: u64,
+    variant: Variant,
+    state: State,
+    start: (usize, usize),
+    goal: (usize, usize),
+    /// Where the searches came into each cell from, which doubles as the set of visited cells.
+    previous: Board<Option<(usize, usize)>>,
+    /// How far each cell is from the start, for A*.
+    cost: Board<usize>,
+    /// The cells waiting to be searched, or the dead ends waiting to be filled in.
+    frontier: VecDeque<(usize, usize)>,
+    /// A*'s cells waiting to be searched, cheapest guess first.
+    open: BinaryHeap<Reverse<(usize, (usize, usize))>>,
+    filled: Board<bool>,
+    /// Which way the wall followers are facing, as an index into `exits`.
+    heading: usize,
+    steps: usize,
+    current: Option<(usize, usize)>,
+    /// The way through, once it's found. The wall followers fill it in as they go.
+    path: Vec<(usize, usize)>,
+}
+
+impl Exports {
+    /// Get ready to solve whatever `generator` has built, with `variant`.
+    pub fn new(variant: String, generator: &(impl Generator + ?Sized)) -> Result<Self, SolveError> {
+        let variant = match variant.as_str() {
+            "bfs" => Variant::BreadthFirst,
+            "dfs" => Variant::DepthFirst,
+            "astar" => Variant::AStar,
+            "deadend" => Variant::DeadEnd,
+            "leftwall" => Variant::LeftWall,
+            "rightwall" => Variant::RightWall,
+            _ => return Err(SolveError::Unknown(variant)),
+        };
+        let maze = generator.get_maze();
+        let (Some(start), Some(goal)) = (maze.start(), maze.goal()) else {
+            return Err(SolveError::NoWayThrough);
+        };
+        let (width, height) = maze.size();
+
+        Ok(Self {
+            variant,
+            state: State::Setup,
+            start,
+            goal,
+            previous: Board::new(width, height, None),
+            cost: Board::new(width, height, usize::MAX),
+            frontier: VecDeque::new(),
+            open: BinaryHeap::new(),
+            filled: Board::new(width, height, false),
+            heading: 0,
+            steps: 0,
+            current: None,
+            path: vec![],
+            maze,
+            seed: generator.get_seed(),
+        })
+    }
+
+    /// Follow the trail of `previous` back from the goal.
+    fn trace(&self) -> Vec<(usize, usize)> {
+        let mut path = vec![self.goal];
+        while let Some(&cell) = path.last().filter(|&&cell| cell != self.start) {
+            path.push(self.previous[cell.1][cell.0].unwrap());
+        }
+        path.reverse();
+        path
+    }
+
+    /// The cells next to `cell` in clockwise order, with `None` wherever there's a wall.
+    fn exits(&self, (x, y): (usize, usize)) -> Vec<Option<(usize, usize)>> {
+        use delta_util::Direction as Delta;
+        use hex_util::Direction as Hex;
+        use polar_util::Direction as Polar;
+        use util::Direction as Square;
+
+        match &self.maze {
+            Maze::Square(grid) => [Square::North, Square::East, Square::South, Square::West]
+                .into_iter()
+                .map(|direction| {
+                    if grid[y][x].contains(direction) {
+                        direction.offset((x, y), grid)
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            Maze::Hex(_) => [
+                Hex::East,
+                Hex::SouthEast,
+                Hex::SouthWest,
+                Hex::West,
+                Hex::NorthWest,
+                Hex::NorthEast,
+            ]
+            .into_iter()
+            .map(|direction| {
+                let (i, j) = direction.next(x as i32, y as i32);
+                let next = (usize::try_from(i).ok()?, usize::try_from(j).ok()?);
+                self.maze.neighbours((x, y)).contains(&next).then_some(next)
+            })
+            .collect(),
+            // Triangles are missing one of these, which is the same as having a wall there.
+            Maze::Delta(grid) => [Delta::North, Delta::East, Delta::South, Delta::West]
+                .into_iter()
+                .map(|direction| {
+                    if grid[y][x].contains(direction) {
+                        direction.offset((x, y), grid)
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            // Six slots, so that the way back is always three round from the way in. The inside is
+            // one side, but it goes in the fourth slot or the fifth, depending on which way out of
+            // the cell inside leads here.
+            Maze::Polar(grid) => {
+                let inside = Polar::Inward.offset((x, y), grid);
+                let split = inside.and_then(|inside| Polar::OutwardClockwise.offset(inside, grid))
+                    == Some((x, y));
+                [
+                    Some(Polar::Outward),
+                    Some(Polar::OutwardClockwise),
+                    Some(Polar::Clockwise),
+                    (!split).then_some(Polar::Inward),
+                    split.then_some(Polar::Inward),
+                    Some(Polar::Anticlockwise),
+                ]
+                .into_iter()
+                .map(|direction| {
+                    let direction = direction?;
+                    if grid[y][x]?.contains(direction) {
+                        direction.offset((x, y), grid)
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+            }
+            Maze::Penrose(grid) => grid.sides((x, y)),
+        }
+    }
+
+    /// Is `cell` a dead end, once the filled-in cells are taken away?
+    fn is_dead_end(&self, cell: (usize, usize)) -> bool {
+        cell != self.start
+            && cell != self.goal
+            && !self.filled[cell.1][cell.0]
+            && self
+                .maze
+                .neighbours(cell)
+                .iter()
+                .filter(|&&(x, y)| !self.filled[y][x])
+                .count()
+                <= 1
+    }
+
+    fn setup(&mut self) {
+        let (width, height) = self.maze.size();
+        match self.variant {
+            Variant::BreadthFirst | Variant::DepthFirst => {
+                self.previous[self.start.1][self.start.0] = Some(self.start);
+                self.frontier.push_back(self.start);
+            }
+            Variant::AStar => {
+                self.cost[self.start.1][self.start.0] = 0;
+                self.previous[self.start.1][self.start.0] = Some(self.start);
+                let guess = self.maze.distance(self.start, self.goal);
+                self.open.push(Reverse((guess, self.start)));
+            }
+            Variant::DeadEnd => {
+                for y in 0..height {
+                    for x in 0..width {
+                        // Cells off the edge of a hex board have no neighbours at all.
+                        if !self.maze.neighbours((x, y)).is_empty() && self.is_dead_end((x, y)) {
+                            self.frontier.push_back((x, y));
+                        }
+                    }
+                }
+            }
+            Variant::LeftWall | Variant::RightWall => {
+                // Everyone starts out facing East, into the maze, except on polar boards, where
+                // the way in is from the outside, and Penrose ones, where it's wherever the
+                // start piece touches the edge.
+                self.heading = match self.maze {
+                    Maze::Square(_) => 1,
+                    Maze::Polar(_) => 3,
+                    Maze::Penrose(ref grid) => grid.entrance() + 2,
+                    _ => 0,
+                };
+                self.path.push(self.start);
+            }
+        }
+        self.current = Some(self.start);
+    }
+
+    /// Search one more cell, returning true once the goal has been found.
+    fn search(&mut self) -> bool {
+        let cell = match self.variant {
+            Variant::BreadthFirst => self.frontier.pop_front(),
+            Variant::DepthFirst => self.frontier.pop_back(),
+            _ => self.open.pop().map(|Reverse((_, cell))| cell),
+        };
+        let Some(cell) = cell else {
+            log::warn!("Ran out of cells without finding the goal!");
+            return true;
+        };
+        self.current = Some(cell);
+        if cell == self.goal {
+            self.path = self.trace();
+            return true;
+        }
+        for next in self.maze.neighbours(cell) {
+            if self.variant == Variant::AStar {
+                let cost = self.cost[cell.1][cell.0] + 1;
+                if cost < self.cost[next.1][next.0] {
+                    self.cost[next.1][next.0] = cost;
+                    self.previous[next.1][next.0] = Some(cell);
+                    let guess = cost + self.maze.distance(next, self.goal);
+                    self.open.push(Reverse((guess, next)));
+                }
+            } else if self.previous[next.1][next.0].is_none() {
+                self.previous[next.1][next.0] = Some(cell);
+                self.frontier.push_back(next);
+            }
+        }
+        false
+    }
+
+    /// Fill in one more dead end, returning true once there are none left.
+    fn fill(&mut self) -> bool {
+        while let Some(cell) = self.frontier.pop_front() {
+            if !self.is_dead_end(cell) {
+                continue;
+            }
+            self.filled[cell.1][cell.0] = true;
+            self.current = Some(cell);
+            for next in self.maze.neighbours(cell) {
+                if self.is_dead_end(next) {
+                    self.frontier.push_back(next);
+                }
+            }
+            return false;
+        }
+
+        // Whatever's left is the way through, so walk along it.
+        self.path = vec![self.start];
+        let (width, height) = self.maze.size();
+        while let Some(&cell) = self.path.last().filter(|&&cell| cell != self.goal) {
+            let previous = self.path.len().checked_sub(2).map(|i| self.path[i]);
+            let next = self
+                .maze
+                .neighbours(cell)
+                .into_iter()
+                .find(|&(x, y)| !self.filled[y][x] && Some((x, y)) != previous);
+            match next {
+                Some(next) if self.path.len() <= width * height => self.path.push(next),
+                _ => {
+                    log::warn!("Couldn't find a way through the unfilled cells!");
+                    self.path.clear();
+                    break;
+                }
+            }
+        }
+        true
+    }
+
+    /// Take one more step along the wall, returning true once we're at the goal.
+    fn follow(&mut self) -> bool {
+        let cell = *self.path.last().unwrap();
+        if cell == self.goal {
+            return true;
+        }
+        let (width, height) = self.maze.size();
+        self.steps += 1;
+        if self.steps > width * height * 4 {
+            log::warn!("Went round in circles without finding the goal!");
+            return true;
+        }
+
+        // Try the sharpest turn towards our hand first, and then sweep round the other way.
+        let exits = self.exits(cell);
+        let sides = exits.len();
+        let (first, turn) = match self.variant {
+            Variant::LeftWall => (self.heading + sides - (sides / 2 - 1), 1),
+            _ => (self.heading + sides / 2 - 1, sides - 1),
+        };
+        for i in 0..sides {
+            let heading = (first + i * turn) % sides;
+            if let Some(next) = exits[heading] {
+                // Face away from the side that leads back. On most boards that's the same as
+                // facing `heading`, but Penrose pieces are all turned different ways.
+                let ahead = self.exits(next);
+                self.heading = match ahead.iter().position(|&exit| exit == Some(cell)) {
+                    Some(back) => (back + ahead.len() / 2) % ahead.len(),
+                    None => heading,
+                };
+                self.current = Some(next);
+                // Backtracking takes the dead end back out of the path.
+                if let Some(index) = self.path.iter().position(|&cell| cell == next) {
+                    self.path.truncate(index + 1);
+                } else {
+                    self.path.push(next);
+                }
+                break;
+            }
+        }
+        false
+    }
+
+    fn draw_cell(&self, canvas: &mut dyn Canvas, (x, y): (usize, usize), inset: f32, color: Color) {
+        match self.maze {
+            Maze::Hex(_) => {
+                hex_util::draw_cell(canvas, x, y, self.maze.size().1, inset, color);
+            }
+            Maze::Delta(_) => delta_util::draw_cell(canvas, (x, y), inset, color),
+            Maze::Polar(ref grid) => polar_util::draw_cell(canvas, (x, y), grid, inset, color),
+            Maze::Penrose(ref grid) => grid.draw_cell(canvas, (x, y), inset, color),
+            _ => util::draw_cell(canvas, x, y, inset, color),
+        }
+    }
+}
+
+impl Generator for Exports {
+    fn name(&self) -> String {
+        match self.variant {
+            Variant::BreadthFirst => String::from("Breadth-First Search"),
+            Variant::DepthFirst => String::from("Depth-First Search"),
+            Variant::AStar => String::from("A* Search"),
+            Variant::DeadEnd => String::from("Dead-End Filling"),
+            Variant::LeftWall => String::from("Left Wall Follower"),
+            Variant::RightWall => String::from("Right Wall Follower"),
+        }
+    }
+    fn re_init(&mut self, variant: String) {
+        match Exports::new(variant, self) {
+            Ok(solver) => *self = solver,
+            Err(error) => log::error!("Can't solve it: {}", error),
+        }
+    }
+    fn get_variant(&self) -> String {
+        match self.variant {
+            Variant::BreadthFirst => "bfs".to_owned(),
+            Variant::DepthFirst => "dfs".to_owned(),
+            Variant::AStar => "astar".to_owned(),
+            Variant::DeadEnd => "deadend".to_owned(),
+            Variant::LeftWall => "leftwall".to_owned(),
+            Variant::RightWall => "rightwall".to_owned(),
+        }
+    }
+    fn update(&mut self) {
+        match self.state {
+            State::Setup => {
+                self.setup();
+                self.state = State::Running;
+            }
+            State::Running => {
+                let done = match self.variant {
+                    Variant::BreadthFirst | Variant::DepthFirst | Variant::AStar => self.search(),
+                    Variant::DeadEnd => self.fill(),
+                    Variant::LeftWall | Variant::RightWall => self.follow(),
+                };
+                if done {
+                    self.current = None;
+                    self.state = State::Done;
+                    log::info!("Solved in {} cells.", self.path.len());
+                }
+            }
+            State::Done => {}
+        }
+    }
+
+    fn get_state(&self) -> State {
+        self.state
+    }
+
+    fn get_seed(&self) -> u64 {
+        // None of the solvers need any randomness, so this is the maze's.
+        self.seed
+    }
+
+    fn get_maze(&self) -> Maze {
+        self.maze.clone()
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        let (width, height) = self.maze.size();
+        match self.maze {
+            Maze::Hex(_) => hex_util::board_size(width, height),
+            Maze::Delta(_) => delta_util::board_size(width, height),
+            Maze::Polar(_) => polar_util::board_size(width, height),
+            Maze::Penrose(ref grid) => grid.pixel_size(),
+            _ => util::board_size(width, height),
+        }
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        let (width, height) = self.maze.size();
+        let mut filled = COLORS[21];
+        filled.a = 0.5;
+        for y in 0..height {
+            for x in 0..width {
+                if self.filled[y][x] {
+                    self.draw_cell(canvas, (x, y), 0.0, filled);
+                } else if self.previous[y][x].is_some() {
+                    self.draw_cell(canvas, (x, y), 0.0, FIELD_COLOR);
+                }
+            }
+        }
+        let mut waiting = COLORS[1];
+        waiting.a = 0.5;
+        for &cell in self
+            .frontier
+            .iter()
+            .chain(self.open.iter().map(|Reverse((_, cell))| cell))
+        {
+            if self.variant != Variant::DeadEnd {
+                self.draw_cell(canvas, cell, LINE_WIDTH * 1.5, waiting);
+            }
+        }
+
+        match &self.maze {
+            Maze::Square(grid) => util::draw_board(canvas, grid),
+            Maze::Hex(grid) => hex_util::draw_board(canvas, grid),
+            Maze::Delta(grid) => delta_util::draw_board(canvas, grid),
+            Maze::Polar(grid) => polar_util::draw_board(canvas, grid),
+            Maze::Penrose(grid) => grid.draw_board(canvas),
+        }
+        if let Some(cell) = self.current {
+            if self.path.last() != Some(&cell) {
+                self.draw_cell(canvas, cell, LINE_WIDTH * 1.5, COLORS[1]);
+            }
+        }
+        match self.maze {
+            Maze::Hex(_) => hex_util::draw_path(canvas, &self.path, height),
+            Maze::Delta(_) => delta_util::draw_path(canvas, &self.path),
+            Maze::Polar(ref grid) => polar_util::draw_path(canvas, &self.path, grid),
+            Maze::Penrose(ref grid) => grid.draw_path(canvas, &self.path),
+            _ => util::draw_path(canvas, &self.path),
+        }
+    }
+
+    fn move_to(&mut self, _pos: (f32, f32)) {}
+}
+
+#[test]
+fn solvers_agree() {
+    use crate::{graph_prim, graph_wilson, hex_blobby, kruskal, penrose, util::Rng};
+
+    let mut generators: [Box<dyn Algorithm>; 5] = [
+        Box::new(kruskal::Exports::new(12, 8, Rng::new(3))),
+        Box::new(hex_blobby::Exports::new(16, 8, Rng::new(4))),
+        Box::new(graph_prim::DeltaExports::new(12, 8, Rng::new(7))),
+        Box::new(graph_wilson::PolarExports::new(false, 32, 8, Rng::new(5))),
+        Box::new(penrose::Exports::new(
+            penrose::Variant::Jack,
+            24,
+            16,
+            Rng::new(6),
+        )),
+    ];
+    for generator in &mut generators {
+        let solution = generator.run().solution();
+        for variant in SOLVERS {
+            let mut solver = Exports::new(variant.to_owned(), generator.as_ref()).unwrap();
+            assert_eq!(solver.get_seed(), generator.get_seed());
+            while solver.get_state() != State::Done {
+                solver.update();
+            }
+            assert_eq!(solver.path, solution, "{} went the wrong way", variant);
+        }
+    }
+}
+
+#[test]
+fn bad_solvers_are_errors() {
+    use crate::{kruskal, util::Rng};
+
+    let mut maze = kruskal::Exports::new(4, 3, Rng::new(1));
+    maze.run();
+    assert_eq!(
+        Exports::new("bogus".to_owned(), &maze).err(),
+        Some(SolveError::Unknown("bogus".to_owned()))
+    );
+}
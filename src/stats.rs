@@ -0,0 +1,235 @@
+//! Numbers that describe the texture of a finished maze, so that algorithms can be compared
+//! without squinting at them.
+
+use std::fmt::{self, Debug, Display};
+
+use enumset::{EnumSet, EnumSetType};
+
+use crate::{
+    generator::Maze,
+    heatmap::{flood, furthest},
+    util::Board,
+};
+
+/// The counts are `f32`s, so that a pile of them can be averaged together.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub cells: f32,
+    /// Cells with only one way out.
+    pub dead_ends: f32,
+    /// Cells with three or more ways out.
+    pub junctions: f32,
+    /// Cells with exactly two ways out.
+    pub corridors: f32,
+    /// How many cells long the corridors between dead ends and junctions are, on average.
+    pub river: f32,
+    /// How many steps it takes to get from the entrance to the exit.
+    pub solution: f32,
+    /// How much longer the solution is than a straight line would be.
+    pub tortuosity: f32,
+    /// How many steps it takes to get between the two cells that are furthest apart.
+    pub longest: f32,
+    /// What fraction of the steps along the solution go each way. Passages off the solution don't
+    /// count.
+    pub directions: Vec<(String, f32)>,
+}
+
+impl Stats {
+    /// Measure a finished maze of any shape: square, hex, delta, polar, or Penrose. There's
+    /// nothing to measure without an entrance and an exit, so that's `None`.
+    ///
+    /// The directions only count the steps along the solution, not every passage. Penrose pieces
+    /// don't have fixed sides, so their steps go by `grid.compass` instead, the same as the keys.
+    pub fn new(maze: &Maze) -> Option<Self> {
+        let (start, goal) = (maze.start()?, maze.goal()?);
+        let (width, height) = maze.size();
+        let mut rv = Stats::default();
+
+        let mut exits = Board::new(width, height, 0);
+        for y in 0..height {
+            for x in 0..width {
+                let count = maze.neighbours((x, y)).len();
+                exits[y][x] = count;
+                match count {
+                    0 => continue,
+                    1 => rv.dead_ends += 1.0,
+                    2 => rv.corridors += 1.0,
+                    _ => rv.junctions += 1.0,
+                }
+                rv.cells += 1.0;
+            }
+        }
+
+        // Follow each corridor from one end to the other to see how long it is.
+        let mut seen = Board::new(width, height, false);
+        let mut rivers = 0;
+        for y in 0..height {
+            for x in 0..width {
+                if exits[y][x] != 2 || seen[y][x] {
+                    continue;
+                }
+                rivers += 1;
+                seen[y][x] = true;
+                let mut stack = vec![(x, y)];
+                while let Some(cell) = stack.pop() {
+                    for (i, j) in maze.neighbours(cell) {
+                        if exits[j][i] == 2 && !seen[j][i] {
+                            seen[j][i] = true;
+                            stack.push((i, j));
+                        }
+                    }
+                }
+            }
+        }
+        rv.river = rv.corridors / rivers.max(1) as f32;
+
+        let solution = maze.solution();
+        rv.solution = solution.len().saturating_sub(1) as f32;
+        rv.tortuosity = rv.solution / maze.distance(start, goal).max(1) as f32;
+        let (end, _) = furthest(&flood(maze, start));
+        rv.longest = furthest(&flood(maze, end)).1 as f32;
+
+        // Biased algorithms lean one way, and so do their solutions.
+        rv.directions = match maze {
+            Maze::Square(grid) => histogram(&solution, |(x, y), next| {
+                grid[y][x]
+                    .iter()
+                    .find(|direction| direction.offset((x, y), grid) == Some(next))
+            }),
+            Maze::Hex(grid) => histogram(&solution, |(x, y), next| {
+                let next = (next.0 as i32, next.1 as i32);
+                grid[y][x]?
+                    .iter()
+                    .find(|direction| direction.next(x as i32, y as i32) == next)
+            }),
+            Maze::Delta(grid) => histogram(&solution, |(x, y), next| {
+                grid[y][x]
+                    .iter()
+                    .find(|direction| direction.offset((x, y), grid) == Some(next))
+            }),
+            Maze::Polar(grid) => histogram(&solution, |(x, y), next| {
+                grid[y][x]?
+                    .iter()
+                    .find(|direction| direction.offset((x, y), grid) == Some(next))
+            }),
+            // The pieces are turned every which way, so go by the way the robot's keys would.
+            Maze::Penrose(grid) => histogram(&solution, |from, to| Some(grid.compass(from, to))),
+        };
+        Some(rv)
+    }
+
+    pub fn dead_end_ratio(&self) -> f32 {
+        self.dead_ends / self.cells.max(1.0)
+    }
+
+    /// The average of a pile of stats, all from the same shape of board.
+    pub fn mean(all: &[Stats]) -> Stats {
+        let count = all.len().max(1) as f32;
+        let mean = |field: fn(&Stats) -> f32| all.iter().map(field).sum::<f32>() / count;
+        let directions = all
+            .first()
+            .map(|first| {
+                (first.directions.iter().enumerate())
+                    .map(|(i, (name, _))| {
+                        let total = all.iter().map(|stats| stats.directions[i].1).sum::<f32>();
+                        (name.clone(), total / count)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Stats {
+            cells: mean(|stats| stats.cells),
+            dead_ends: mean(|stats| stats.dead_ends),
+            junctions: mean(|stats| stats.junctions),
+            corridors: mean(|stats| stats.corridors),
+            river: mean(|stats| stats.river),
+            solution: mean(|stats| stats.solution),
+            tortuosity: mean(|stats| stats.tortuosity),
+            longest: mean(|stats| stats.longest),
+            directions,
+        }
+    }
+}
+
+/// What fraction of the steps along `path` go in each direction, using `step` to find the
+/// direction from one cell to the next.
+fn histogram<D: EnumSetType + Debug>(
+    path: &[(usize, usize)],
+    step: impl Fn((usize, usize), (usize, usize)) -> Option<D>,
+) -> Vec<(String, f32)> {
+    let mut counts = EnumSet::<D>::all()
+        .iter()
+        .map(|direction| (direction, 0))
+        .collect::<Vec<_>>();
+    for pair in path.windows(2) {
+        if let Some(direction) = step(pair[0], pair[1]) {
+            if let Some((_, count)) = counts.iter_mut().find(|(d, _)| *d == direction) {
+                *count += 1;
+            }
+        }
+    }
+    let total = path.len().saturating_sub(1).max(1) as f32;
+    counts
+        .into_iter()
+        .map(|(direction, count)| (format!("{:?}", direction), count as f32 / total))
+        .collect()
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  Cells:          {:.1}", self.cells)?;
+        writeln!(
+            f,
+            "  Dead ends:      {:.1} ({:.1}%)",
+            self.dead_ends,
+            self.dead_end_ratio() * 100.0
+        )?;
+        writeln!(f, "  Junctions:      {:.1}", self.junctions)?;
+        writeln!(f, "  Corridors:      {:.1}", self.corridors)?;
+        writeln!(f, "  River length:   {:.2}", self.river)?;
+        writeln!(f, "  Solution:       {:.1}", self.solution)?;
+        writeln!(f, "  Tortuosity:     {:.2}", self.tortuosity)?;
+        writeln!(f, "  Longest path:   {:.1}", self.longest)?;
+        let directions = self
+            .directions
+            .iter()
+            .map(|(name, fraction)| format!("{} {:.1}%", name, fraction * 100.0))
+            .collect::<Vec<_>>();
+        writeln!(f, "  Directions:     {}", directions.join(", "))
+    }
+}
+
+#[test]
+fn small_maze() {
+    use crate::util::Direction;
+
+    // A three by two maze shaped like a T on its side.
+    let mut grid = Board::new(3, 2, EnumSet::new());
+    grid[0][0] = Direction::East.into();
+    grid[0][1] = Direction::East | Direction::West | Direction::South;
+    grid[0][2] = Direction::West | Direction::South;
+    grid[1][2] = Direction::North.into();
+    grid[1][1] = Direction::North | Direction::West;
+    grid[1][0] = Direction::East.into();
+    let stats = Stats::new(&Maze::Square(grid)).unwrap();
+
+    assert_eq!(
+        (stats.dead_ends, stats.junctions, stats.corridors),
+        (3.0, 1.0, 2.0)
+    );
+    assert_eq!(
+        (stats.river, stats.solution, stats.longest),
+        (1.0, 3.0, 4.0)
+    );
+    assert_eq!(stats.tortuosity, 1.0);
+    assert_eq!(
+        stats.directions,
+        [
+            ("North", 0.0),
+            ("East", 2.0 / 3.0),
+            ("South", 1.0 / 3.0),
+            ("West", 0.0)
+        ]
+        .map(|(name, fraction)| (name.to_owned(), fraction))
+    );
+}
@@ -0,0 +1,162 @@
+//! Vector output for finished mazes, so they can be printed at any size.
+
+use std::fmt::Write;
+
+use macroquad::color::Color;
+
+use crate::{
+    delta_util,
+    generator::Maze,
+    graph::CellGraph,
+    hex_util,
+    penrose::{Point, Tile},
+    polar_util,
+    util::{self, Wall, CELL_WIDTH, COLORS, LINE_WIDTH, OFFSET},
+};
+
+/// Render a maze as a standalone SVG document.
+pub fn to_svg(maze: &Maze) -> String {
+    match maze {
+        Maze::Square(grid) => walls(
+            util::board_size(grid.width(), grid.height()),
+            &util::walls(grid),
+        ),
+        Maze::Hex(grid) => walls(
+            hex_util::board_size(grid.width(), grid.height()),
+            &hex_util::walls(grid),
+        ),
+        Maze::Delta(grid) => walls(
+            delta_util::board_size(grid.width(), grid.height()),
+            &delta_util::walls(grid),
+        ),
+        Maze::Polar(grid) => walls(
+            polar_util::board_size(grid.width(), grid.height()),
+            &polar_util::walls(grid),
+        ),
+        Maze::Penrose(grid) if grid.has_cells() => walls(grid.pixel_size(), &grid.walls()),
+        Maze::Penrose(grid) => penrose(&grid.tiles, grid.width, grid.height),
+    }
+}
+
+fn walls(size: (f32, f32), walls: &[Wall]) -> String {
+    let mut path = String::new();
+    for ((x1, y1), (x2, y2)) in walls {
+        write!(path, "M{} {}L{} {}", x1, y1, x2, y2).unwrap();
+    }
+    let body = format!(
+        r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+        path,
+        hex_color(COLORS[0]),
+        LINE_WIDTH
+    );
+    document(size, &body)
+}
+
+fn penrose(tiles: &[Tile], width: usize, height: usize) -> String {
+    let (w, h) = (width as f32 * CELL_WIDTH, height as f32 * CELL_WIDTH);
+    let mut body = format!(
+        r#"<clipPath id="board"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath><g clip-path="url(#board)">"#,
+        OFFSET, OFFSET, w, h
+    );
+    for tile in tiles {
+        let Tile(is_kite, a, b, c) = *tile;
+        let color = if is_kite { COLORS[3] } else { COLORS[4] };
+        write!(
+            body,
+            r#"<polygon points="{}" fill="{}"/><polyline points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            points(&tile.fill()),
+            hex_color(color),
+            points(&[a, b, c]),
+            hex_color(COLORS[2]),
+            LINE_WIDTH
+        )
+        .unwrap();
+    }
+    write!(
+        body,
+        r#"</g><rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+        OFFSET,
+        OFFSET,
+        w,
+        h,
+        hex_color(COLORS[0]),
+        LINE_WIDTH
+    )
+    .unwrap();
+    document(util::board_size(width, height), &body)
+}
+
+fn document((width, height): (f32, f32), body: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}"><rect width="100%" height="100%" fill="white"/>{2}</svg>
+"#,
+        width, height, body
+    )
+}
+
+fn points(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|Point(x, y)| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn hex_color(color: Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[test]
+fn exports_the_walls_with_gaps_for_the_entrance_and_exit() {
+    use crate::{generator::Generator, graph_kruskal, kruskal, util::Rng};
+
+    let segment = |((x1, y1), (x2, y2)): Wall| format!("M{} {}L{} {}", x1, y1, x2, y2);
+    let path = |svg: &str| {
+        let d = svg.split(r#"d=""#).nth(1).unwrap();
+        d[..d.find('"').unwrap()].to_owned()
+    };
+
+    let maze = kruskal::Exports::new(5, 4, Rng::new(1)).run();
+    let Maze::Square(grid) = &maze else {
+        panic!("Expected a square maze");
+    };
+    let svg = to_svg(&maze);
+    assert!(svg.contains(r#"viewBox="0 0 116 96""#));
+    let d = path(&svg);
+    assert_eq!(d.matches('M').count(), grid.walls().len());
+    // The west side of the first cell and the east side of the last are open, but the sides below
+    // and above them are still there.
+    let side = |x: usize, y: usize| {
+        let x = x as f32 * CELL_WIDTH + OFFSET;
+        let (north, south) = (
+            y as f32 * CELL_WIDTH + OFFSET,
+            (y + 1) as f32 * CELL_WIDTH + OFFSET,
+        );
+        ((x, north), (x, south))
+    };
+    assert!(!d.contains(&segment(side(0, 0))));
+    assert!(d.contains(&segment(side(0, 1))));
+    assert!(!d.contains(&segment(side(5, 3))));
+    assert!(d.contains(&segment(side(5, 2))));
+
+    let maze = graph_kruskal::HexExports::new(8, 4, Rng::new(2)).run();
+    let Maze::Hex(grid) = &maze else {
+        panic!("Expected a hex maze");
+    };
+    let svg = to_svg(&maze);
+    let (width, height) = hex_util::board_size(8, 4);
+    assert!(svg.contains(&format!(r#"viewBox="0 0 {} {}""#, width, height)));
+    let d = path(&svg);
+    assert_eq!(d.matches('M').count(), grid.walls().len());
+    let corner = |(i, j): (usize, usize), corner| {
+        let (x, y) = hex_util::center_pixel(i, j, grid.height());
+        hex_util::pointy_hex_corner(x, y, corner, 0.0)
+    };
+    // Hex boards are open on the same sides, with the border next to them still closed.
+    let (start, goal) = (grid.start().unwrap(), grid.goal().unwrap());
+    assert!(!d.contains(&segment((corner(start, 3), corner(start, 4)))));
+    assert!(d.contains(&segment((corner(start, 4), corner(start, 5)))));
+    assert!(!d.contains(&segment((corner(goal, 0), corner(goal, 1)))));
+    assert!(d.contains(&segment((corner(goal, 1), corner(goal, 2)))));
+}
@@ -0,0 +1,117 @@
+//! Plain-text versions of finished square mazes, for terminals, issue trackers, and tests.
+
+use crate::util::{Direction, Grid};
+
+/// Is there a wall along the top of cell (x, y)? `y` can go one past the bottom row.
+fn wall_above(grid: &Grid, x: usize, y: usize) -> bool {
+    if y == grid.height() {
+        !grid[y - 1][x].contains(Direction::South)
+    } else {
+        !grid[y][x].contains(Direction::North)
+    }
+}
+
+/// Is there a wall along the left of cell (x, y)? `x` can go one past the last column. Like
+/// `draw_board`, this leaves the entrance and exit open.
+fn wall_left(grid: &Grid, x: usize, y: usize) -> bool {
+    if x == grid.width() {
+        !grid[y][x - 1].contains(Direction::East) && y != grid.height() - 1
+    } else {
+        !grid[y][x].contains(Direction::West) && (x, y) != (0, 0)
+    }
+}
+
+/// The classic `+--+` drawing.
+pub fn ascii(grid: &Grid) -> String {
+    let mut rv = String::new();
+    for y in 0..=grid.height() {
+        for x in 0..grid.width() {
+            rv.push('+');
+            rv.push_str(if wall_above(grid, x, y) { "--" } else { "  " });
+        }
+        rv.push_str("+\n");
+        if y == grid.height() {
+            break;
+        }
+        for x in 0..=grid.width() {
+            rv.push(if wall_left(grid, x, y) { '|' } else { ' ' });
+            if x < grid.width() {
+                rv.push_str("  ");
+            }
+        }
+        rv.push('\n');
+    }
+    rv
+}
+
+/// The same drawing with box-drawing characters, joined up at each corner.
+pub fn unicode(grid: &Grid) -> String {
+    // Indexed by which arms the corner has: up, right, down, and left, in that order of bits.
+    const CORNERS: [char; 16] = [
+        ' ', '╵', '╶', '└', '╷', '│', '┌', '├', '╴', '┘', '─', '┴', '┐', '┤', '┬', '┼',
+    ];
+    let (width, height) = (grid.width(), grid.height());
+    let mut rv = String::new();
+    for y in 0..=height {
+        for x in 0..=width {
+            let up = y > 0 && wall_left(grid, x, y - 1);
+            let right = x < width && wall_above(grid, x, y);
+            let down = y < height && wall_left(grid, x, y);
+            let left = x > 0 && wall_above(grid, x - 1, y);
+            let arms =
+                up as usize | (right as usize) << 1 | (down as usize) << 2 | (left as usize) << 3;
+            rv.push(CORNERS[arms]);
+            if x < width {
+                rv.push_str(if right { "──" } else { "  " });
+            }
+        }
+        rv.push('\n');
+        if y == height {
+            break;
+        }
+        for x in 0..=width {
+            rv.push(if wall_left(grid, x, y) { '│' } else { ' ' });
+            if x < width {
+                rv.push_str("  ");
+            }
+        }
+        rv.push('\n');
+    }
+    rv
+}
+
+#[test]
+fn small_maze() {
+    use crate::util::Board;
+    use enumset::EnumSet;
+
+    // A three by two maze that snakes along the top row and back along the bottom one.
+    let mut grid = Board::new(3, 2, EnumSet::new());
+    grid[0][0] = Direction::East.into();
+    grid[0][1] = Direction::East | Direction::West;
+    grid[0][2] = Direction::West | Direction::South;
+    grid[1][2] = Direction::North | Direction::West;
+    grid[1][1] = Direction::East | Direction::West;
+    grid[1][0] = Direction::East.into();
+
+    assert_eq!(
+        ascii(&grid),
+        concat!(
+            "+--+--+--+\n",
+            "         |\n",
+            "+--+--+  +\n",
+            "|         \n",
+            "+--+--+--+\n",
+        )
+    );
+    assert_eq!(
+        unicode(&grid),
+        concat!(
+            "╶────────┐\n",
+            "         │\n",
+            "┌─────╴  ╵\n",
+            "│         \n",
+            "└────────╴\n",
+        )
+    );
+}
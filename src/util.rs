@@ -1,27 +1,90 @@
+use std::any::Any;
+use std::cell;
+use std::ops::{Index, IndexMut};
+
+use crate::canvas::Canvas;
+use crate::delta_util;
+use crate::generator::{Generator, Maze};
+use crate::graph::{self, Cell, CellGraph};
+use crate::hex_util;
+use crate::polar_util;
+use crate::registry::Shape;
+
 use enumset::EnumSet;
 use itertools::Itertools;
-use macroquad::{
-    color::Color,
-    math::vec2,
-    prelude::{color_u8, draw_line, draw_rectangle, ImageFormat},
-    rand::gen_range,
-    shapes::draw_rectangle_lines,
-    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
-};
-
-#[cfg(not(target_arch = "wasm32"))]
-pub use crate::desktop_util::Desktop as RealArgs;
-
-#[cfg(target_arch = "wasm32")]
-pub use crate::web_util::Web as RealArgs;
+use macroquad::{color::Color, prelude::color_u8};
+use serde::{Deserialize, Serialize};
 
 pub const LINE_WIDTH: f32 = 2.0;
 pub const CELL_WIDTH: f32 = 20.0;
-pub const COLUMNS: f32 = 40.0;
-pub const ROWS: f32 = 30.0;
+pub const COLUMNS: usize = 40;
+pub const ROWS: usize = 30;
 pub const OFFSET: f32 = 8.0;
 
-pub type Grid = [[EnumSet<Direction>; COLUMNS as usize]; ROWS as usize];
+/// A rectangular board of cells, indexed by row and then column (`board[y][x]`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Board<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+impl<T> Board<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The row at `y`, or `None` if it's off the board.
+    pub fn get(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self[y])
+    }
+
+    pub fn iter(&self) -> std::slice::Chunks<'_, T> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::ChunksMut<'_, T> {
+        self.cells.chunks_mut(self.width)
+    }
+}
+
+impl<T> Index<usize> for Board<T> {
+    type Output = [T];
+
+    fn index(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl<T> IndexMut<usize> for Board<T> {
+    fn index_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+}
+
+pub type Grid = Board<EnumSet<Direction>>;
+
+/// The size in pixels of a square board, including the margins.
+pub fn board_size(width: usize, height: usize) -> (f32, f32) {
+    (
+        width as f32 * CELL_WIDTH + OFFSET * 2.0,
+        height as f32 * CELL_WIDTH + OFFSET * 2.0,
+    )
+}
 
 pub const EMPTY_COLOR: Color = Color {
     r: 0.0,
@@ -117,46 +180,168 @@ lazy_static! {
     ];
 }
 
+/// How quickly a maze gets built on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Speed {
+    /// How many steps to take at a time.
+    pub steps: usize,
+    /// How many seconds to wait between each lot of steps.
+    pub interval: f32,
+}
+
+impl Speed {
+    pub const MAX_STEPS: usize = 4096;
+    pub const MAX_INTERVAL: f32 = 2.0;
+
+    pub fn new(steps: usize, interval: f32) -> Self {
+        Self {
+            steps: steps.clamp(1, Self::MAX_STEPS),
+            interval: interval.clamp(0.0, Self::MAX_INTERVAL),
+        }
+    }
+
+    pub fn more_steps(self) -> Self {
+        Self::new(self.steps * 2, self.interval)
+    }
+
+    pub fn fewer_steps(self) -> Self {
+        Self::new(self.steps / 2, self.interval)
+    }
+
+    /// Halve the wait, down to every frame.
+    pub fn shorter_interval(self) -> Self {
+        let interval = if self.interval < 0.01 {
+            0.0
+        } else {
+            self.interval / 2.0
+        };
+        Self::new(self.steps, interval)
+    }
+
+    pub fn longer_interval(self) -> Self {
+        Self::new(self.steps, (self.interval * 2.0).max(0.01))
+    }
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Self::new(1, 0.08)
+    }
+}
+
+/// What the buttons outside the window have asked for, waiting for the next frame to pick it up.
+/// Picking up one of them clears it, and only it, so that asking for two things in the same frame
+/// doesn't lose either.
+#[derive(Debug, Default)]
+pub struct Requests {
+    reset: cell::Cell<bool>,
+    speed: cell::Cell<Option<Speed>>,
+    step: cell::Cell<bool>,
+    finish: cell::Cell<bool>,
+    seek: cell::Cell<Option<usize>>,
+}
+
+impl Requests {
+    pub fn reset(&self) {
+        self.reset.set(true);
+    }
+    pub fn speed(&self, speed: Speed) {
+        self.speed.set(Some(speed));
+    }
+    pub fn step(&self) {
+        self.step.set(true);
+    }
+    pub fn finish(&self) {
+        self.finish.set(true);
+    }
+    pub fn seek(&self, step: usize) {
+        self.seek.set(Some(step));
+    }
+
+    pub fn take_reset(&self) -> bool {
+        self.reset.take()
+    }
+    pub fn take_speed(&self) -> Option<Speed> {
+        self.speed.take()
+    }
+    pub fn take_step(&self) -> bool {
+        self.step.take()
+    }
+    pub fn take_finish(&self) -> bool {
+        self.finish.take()
+    }
+    pub fn take_seek(&self) -> Option<usize> {
+        self.seek.take()
+    }
+}
+
 pub trait Args {
     fn get_algorithm(&self) -> String;
     fn get_variant(&self) -> String;
+    fn get_size(&self) -> (Option<usize>, Option<usize>);
+    fn get_seed(&self) -> Option<u64>;
+    /// Which solver to run once the maze is done, if any.
+    fn get_solver(&self) -> Option<String>;
+    fn get_speed(&self) -> Speed;
     fn needs_reset(&self) -> bool;
+    /// A speed that was changed from outside the window, like the web page's controls.
+    fn new_speed(&self) -> Option<Speed>;
+    /// Whether to take a single step while paused.
+    fn needs_step(&self) -> bool;
+    /// Whether to skip straight to the end.
+    fn needs_finish(&self) -> bool;
+    /// A step to go back (or forwards) to, from outside the window, like the web page's slider.
+    fn seek_to(&self) -> Option<usize>;
+    /// Let the outside know how far through building the maze we are.
+    fn show_history(&self, step: usize, furthest: usize);
 }
 
-pub trait Algorithm {
-    fn name(&self) -> String;
-    fn re_init(&mut self, variant: String);
-    fn update(&mut self);
-    fn draw(&self);
-    fn get_variant(&self) -> String;
-    fn get_state(&self) -> State;
+/// The drawing half of an algorithm. The headless half is in [`Generator`]. They're [`Any`], so a
+/// boxed one can be looked inside again.
+pub trait Algorithm: Generator + Any {
+    fn get_size(&self) -> (f32, f32);
+    fn draw(&self, canvas: &mut dyn Canvas);
     fn move_to(&mut self, cursor: (f32, f32));
+    /// Take the robot one cell that way, once the maze is done. Returns false if it couldn't go,
+    /// because of a wall or because the robot can't get around this kind of maze.
+    fn walk(&mut self, _heading: Heading) -> bool {
+        false
+    }
+}
+
+/// Which way to walk the robot, in the directions its kind of board has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Heading {
+    Square(Direction),
+    Hex(hex_util::Direction),
+    Delta(delta_util::Direction),
+    Polar(polar_util::Direction),
 }
 
 pub trait Playable: Algorithm {
     fn get_grid(&self) -> Grid;
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)>;
-    fn cell_from_pos(&self, pos: (f32, f32)) -> Option<(usize, usize)> {
-        cell_from_pos(pos)
-    }
     fn move_to(&mut self, pos: (f32, f32)) {
-        let cursor = self.cell_from_pos(pos);
-
         let grid = self.get_grid();
-        let path = self.get_path_mut();
-        if let Some(moves) = valid_move(path.last(), cursor, grid) {
-            for cursor in moves {
-                if let Some((index, _)) = path.iter().find_position(|&x| x == &cursor) {
-                    path.truncate(index + 1);
-                } else {
-                    path.push(cursor);
-                }
-            }
-        }
+        graph::move_to(&grid, self.get_path_mut(), pos);
+    }
+    /// Take the robot one cell `heading`, unless there's a wall in the way.
+    fn walk(&mut self, heading: Heading) -> bool {
+        let grid = self.get_grid();
+        graph::walk(&grid, self.get_path_mut(), heading)
+    }
+}
+
+/// Add `cell` to the end of the robot's path, or if it's been there already, go back to it.
+pub fn follow(path: &mut Vec<(usize, usize)>, cell: (usize, usize)) {
+    if let Some((index, _)) = path.iter().find_position(|&x| x == &cell) {
+        path.truncate(index + 1);
+    } else {
+        path.push(cell);
     }
 }
 
-#[derive(EnumSetType, Debug)]
+#[derive(EnumSetType, Debug, Serialize, Deserialize)]
 pub enum Direction {
     North,
     East,
@@ -174,7 +359,7 @@ impl Direction {
         }
     }
 
-    pub fn offset(self, start: (usize, usize)) -> Option<(usize, usize)> {
+    pub fn offset<T>(self, start: (usize, usize), board: &Board<T>) -> Option<(usize, usize)> {
         let mut new_x = start.0 as isize;
         let mut new_y = start.1 as isize;
 
@@ -185,7 +370,11 @@ impl Direction {
             Direction::West => new_x -= 1,
         };
 
-        if 0 <= new_x && new_x < COLUMNS as isize && 0 <= new_y && new_y < ROWS as isize {
+        if 0 <= new_x
+            && new_x < board.width() as isize
+            && 0 <= new_y
+            && new_y < board.height() as isize
+        {
             Some((new_x as usize, new_y as usize))
         } else {
             None
@@ -193,14 +382,14 @@ impl Direction {
     }
 }
 
-pub fn cell_from_pos(pos: (f32, f32)) -> Option<(usize, usize)> {
+pub fn cell_from_pos<T>(pos: (f32, f32), board: &Board<T>) -> Option<(usize, usize)> {
     let (x, y) = pos;
     if x < 0.0 || y < 0.0 {
         return None;
     }
     let x = ((x - OFFSET) / CELL_WIDTH) as usize;
     let y = ((y - OFFSET) / CELL_WIDTH) as usize;
-    if x >= COLUMNS as usize || y >= ROWS as usize {
+    if x >= board.width() || y >= board.height() {
         return None;
     }
     Some((x, y))
@@ -209,15 +398,10 @@ pub fn cell_from_pos(pos: (f32, f32)) -> Option<(usize, usize)> {
 pub fn valid_move(
     start: Option<&(usize, usize)>,
     next: Option<(usize, usize)>,
-    grid: [[EnumSet<Direction>; COLUMNS as usize]; ROWS as usize],
+    grid: &Grid,
 ) -> Option<Vec<(usize, usize)>> {
     if let Some(&(x1, y1)) = start {
         if let Some((x2, y2)) = next {
-            print!(
-                "Moving ({}, {})",
-                x2 as i32 - x1 as i32,
-                y2 as i32 - y1 as i32
-            );
             let delta = (x2 as i32 - x1 as i32, y2 as i32 - y1 as i32);
             let direction = match delta {
                 (-1, y) if y < 0 => (Some(Direction::North), Some(Direction::West)),
@@ -238,7 +422,6 @@ pub fn valid_move(
 
                 _ => (None, None),
             };
-            println!(" => {:?}", direction);
             let sideways = direction.1;
             if let Some(direction) = direction.0 {
                 let mut start = *start.unwrap();
@@ -247,13 +430,13 @@ pub fn valid_move(
                 while start != next {
                     if let Some(sideways) = sideways {
                         if grid[start.1][start.0].contains(sideways)
-                            && sideways.offset(start).unwrap() == next
+                            && sideways.offset(start, grid).unwrap() == next
                         {
                             break;
                         }
                     }
                     if grid[start.1][start.0].contains(direction) {
-                        start = direction.offset(start).unwrap();
+                        start = direction.offset(start, grid).unwrap();
                         rv.push(start);
                     } else {
                         return None;
@@ -276,7 +459,7 @@ pub fn valid_move(
 
                 if grid[start.1][start.0].contains(first) {
                     rv.push(start);
-                    let start = first.offset(start).unwrap();
+                    let start = first.offset(start, grid).unwrap();
                     if grid[start.1][start.0].contains(second) {
                         rv.push(start);
                         return Some(rv);
@@ -288,10 +471,10 @@ pub fn valid_move(
     None
 }
 
-fn draw_little_robot(x: usize, y: usize, color: Color) {
+fn draw_little_robot(canvas: &mut dyn Canvas, x: usize, y: usize, color: Color) {
     let x = x as f32 * CELL_WIDTH + OFFSET;
     let y = y as f32 * CELL_WIDTH + OFFSET;
-    draw_rectangle_lines(x, y, CELL_WIDTH, CELL_WIDTH, 4.0, color);
+    canvas.draw_rectangle_lines(x, y, CELL_WIDTH, CELL_WIDTH, 4.0, color);
 
     let inset = 2.0;
     let x = x + inset;
@@ -299,24 +482,11 @@ fn draw_little_robot(x: usize, y: usize, color: Color) {
     let w = CELL_WIDTH - inset * 2.0;
     let h = CELL_WIDTH - inset * 2.0;
 
-    let image = Texture2D::from_file_with_format(
-        include_bytes!("../static/little_guy.png"),
-        Some(ImageFormat::Png),
-    );
-    draw_texture_ex(
-        &image,
-        x,
-        y,
-        WHITE,
-        DrawTextureParams {
-            dest_size: Some(vec2(w, h)),
-            ..Default::default()
-        },
-    );
+    canvas.draw_robot(x, y, w, h);
 }
 
-pub fn draw_cell(x: usize, y: usize, inset: f32, color: Color) {
-    draw_rectangle(
+pub fn draw_cell(canvas: &mut dyn Canvas, x: usize, y: usize, inset: f32, color: Color) {
+    canvas.draw_rectangle(
         x as f32 * CELL_WIDTH + inset + OFFSET,
         y as f32 * CELL_WIDTH + inset + OFFSET,
         CELL_WIDTH - inset * 2.0,
@@ -325,7 +495,13 @@ pub fn draw_cell(x: usize, y: usize, inset: f32, color: Color) {
     );
 }
 
-pub fn draw_board(grid: [[EnumSet<Direction>; COLUMNS as usize]; ROWS as usize]) {
+/// A wall between two points on the board.
+pub type Wall = ((f32, f32), (f32, f32));
+
+/// The walls of a square board, as line segments, leaving the entrance and exit open.
+pub fn walls(grid: &Grid) -> Vec<Wall> {
+    let (columns, rows) = (grid.width() as f32, grid.height() as f32);
+    let mut walls = vec![];
     for (j, row) in grid.iter().enumerate() {
         for (i, cell) in row.iter().enumerate() {
             let x = i as f32;
@@ -337,33 +513,156 @@ pub fn draw_board(grid: [[EnumSet<Direction>; COLUMNS as usize]; ROWS as usize])
 
             //Figure out which lines to draw.
             if !cell.contains(Direction::North) {
-                draw_line(east, north, west, north, LINE_WIDTH, COLORS[0]);
+                walls.push(((east, north), (west, north)));
             }
-            if !cell.contains(Direction::East) && (x, y) != (COLUMNS - 1.0, ROWS - 1.0) {
-                draw_line(east, north, east, south, LINE_WIDTH, COLORS[0]);
+            if !cell.contains(Direction::East) && (x, y) != (columns - 1.0, rows - 1.0) {
+                walls.push(((east, north), (east, south)));
             }
             if !cell.contains(Direction::South) {
-                draw_line(east, south, west, south, LINE_WIDTH, COLORS[0]);
+                walls.push(((east, south), (west, south)));
             }
             if !cell.contains(Direction::West) && (x, y) != (0.0, 0.0) {
-                draw_line(west, north, west, south, LINE_WIDTH, COLORS[0]);
+                walls.push(((west, north), (west, south)));
             }
         }
     }
+    walls
 }
 
-pub fn draw_path(path: &[(usize, usize)]) {
+pub fn draw_board(canvas: &mut dyn Canvas, grid: &Grid) {
+    for ((x1, y1), (x2, y2)) in walls(grid) {
+        canvas.draw_line(x1, y1, x2, y2, LINE_WIDTH, COLORS[0]);
+    }
+}
+
+pub fn draw_path(canvas: &mut dyn Canvas, path: &[(usize, usize)]) {
     let mut color = COLORS[10];
     if let Some((&(x, y), rest)) = path.split_last() {
         color.a = 0.6;
-        draw_little_robot(x, y, color);
+        draw_little_robot(canvas, x, y, color);
         color.a = 0.3;
         for &(x, y) in rest {
-            draw_cell(x, y, 0.0, color)
+            draw_cell(canvas, x, y, 0.0, color)
         }
     }
 }
 
+impl CellGraph for Grid {
+    const SHAPE: Shape = Shape::Square;
+
+    fn empty(width: usize, height: usize) -> Self {
+        Board::new(width, height, EnumSet::new())
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .collect()
+    }
+
+    fn neighbours(&self, cell: Cell) -> Vec<Cell> {
+        EnumSet::<Direction>::all()
+            .iter()
+            .filter_map(|direction| direction.offset(cell, self))
+            .collect()
+    }
+
+    fn links(&self, (x, y): Cell) -> Vec<Cell> {
+        self[y][x]
+            .iter()
+            .filter_map(|direction| direction.offset((x, y), self))
+            .collect()
+    }
+
+    fn link(&mut self, a: Cell, b: Cell) {
+        let direction = towards(a, b);
+        self[a.1][a.0] |= direction;
+        self[b.1][b.0] |= direction.opposite();
+    }
+
+    fn unlink(&mut self, a: Cell, b: Cell) {
+        let direction = towards(a, b);
+        self[a.1][a.0] -= direction;
+        self[b.1][b.0] -= direction.opposite();
+    }
+
+    fn start(&self) -> Option<Cell> {
+        Some((0, 0))
+    }
+
+    fn goal(&self) -> Option<Cell> {
+        Some((self.width() - 1, self.height() - 1))
+    }
+
+    fn step(&self, (x, y): Cell, heading: Heading) -> Option<Cell> {
+        let Heading::Square(direction) = heading else {
+            return None;
+        };
+        if !self[y][x].contains(direction) {
+            return None;
+        }
+        direction.offset((x, y), self)
+    }
+
+    fn maze(&self) -> Maze {
+        Maze::Square(self.clone())
+    }
+
+    fn pixel_size(&self) -> (f32, f32) {
+        board_size(self.width(), self.height())
+    }
+
+    fn center(&self, (x, y): Cell) -> (f32, f32) {
+        (
+            (x as f32 + 0.5) * CELL_WIDTH + OFFSET,
+            (y as f32 + 0.5) * CELL_WIDTH + OFFSET,
+        )
+    }
+
+    fn cell_at(&self, pos: (f32, f32)) -> Option<Cell> {
+        cell_from_pos(pos, self)
+    }
+
+    fn walls(&self) -> Vec<Wall> {
+        walls(self)
+    }
+
+    fn draw_cell(&self, canvas: &mut dyn Canvas, (x, y): Cell, inset: f32, color: Color) {
+        draw_cell(canvas, x, y, inset, color);
+    }
+
+    fn draw_current(&self, canvas: &mut dyn Canvas, (x, y): Cell, color: Color) {
+        draw_cell(canvas, x, y, LINE_WIDTH, color);
+    }
+
+    fn draw_path(&self, canvas: &mut dyn Canvas, path: &[Cell]) {
+        draw_path(canvas, path);
+    }
+
+    /// The robot can go down a whole corridor at once, or around one corner.
+    fn route(&self, from: Cell, to: Cell) -> Option<Vec<Cell>> {
+        valid_move(Some(&from), Some(to), self)
+    }
+}
+
+/// Which way `b` is from `a`, when they're next to each other.
+fn towards(a: Cell, b: Cell) -> Direction {
+    match (b.0 as isize - a.0 as isize, b.1 as isize - a.1 as isize) {
+        (0, -1) => Direction::North,
+        (1, 0) => Direction::East,
+        (0, 1) => Direction::South,
+        (-1, 0) => Direction::West,
+        delta => panic!(
+            "{:?} and {:?} aren't next to each other ({:?})",
+            a, b, delta
+        ),
+    }
+}
+
 pub struct VecChooseIter<'a, T> {
     source: &'a Vec<T>,
     indices: std::vec::IntoIter<usize>,
@@ -376,35 +675,82 @@ impl<'a, T> Iterator for VecChooseIter<'a, T> {
         self.indices.next().map(|ix| &self.source[ix])
     }
 }
+
+/// A small seedable random number generator (PCG32, the same one macroquad uses), so that any
+/// maze can be built again from the seed it started with.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    seed: u64,
+    state: u64,
+}
+
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+    const INCREMENT: u64 = 1442695040888963407;
+
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// The seed this generator started from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
+        let shifted = (((old >> 18) ^ old) >> 27) as u32;
+        shifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// A new generator, seeded from this one, for the next maze.
+    pub fn fork(&mut self) -> Self {
+        Self::new(self.next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    /// A random number in `low..high`.
+    pub fn gen_range(&mut self, low: usize, high: usize) -> usize {
+        let r = self.next_u32() as f64 / (u32::MAX as f64 + 1.0);
+        low + (r * (high - low) as f64) as usize
+    }
+}
+
 pub trait ChooseRandom<T> {
-    fn shuffle(&mut self);
-    fn choose(&self) -> Option<T>;
-    fn choose_multiple(&'_ self, amount: usize) -> VecChooseIter<'_, T>;
+    fn shuffle(&mut self, rng: &mut Rng);
+    fn choose(&self, rng: &mut Rng) -> Option<T>;
+    fn choose_multiple(&'_ self, amount: usize, rng: &mut Rng) -> VecChooseIter<'_, T>;
 }
 
 impl<T: Copy> ChooseRandom<T> for Vec<T> {
-    fn shuffle(&mut self) {
+    fn shuffle(&mut self, rng: &mut Rng) {
         for i in (1..self.len()).rev() {
-            let j = gen_range(0, i + 1);
+            let j = rng.gen_range(0, i + 1);
             self.swap(i, j);
         }
     }
 
-    fn choose(&self) -> Option<T> {
+    fn choose(&self, rng: &mut Rng) -> Option<T> {
         if self.is_empty() {
             return None;
         }
         let mut indices = (0..self.len()).collect::<Vec<usize>>();
-        indices.shuffle();
+        indices.shuffle(rng);
         Some(self[indices[0]])
     }
 
-    fn choose_multiple(&'_ self, amount: usize) -> VecChooseIter<'_, T> {
+    fn choose_multiple(&'_ self, amount: usize, rng: &mut Rng) -> VecChooseIter<'_, T> {
         let mut indices = (0..self.len())
             .enumerate()
             .map(|(i, _)| i)
             .collect::<Vec<usize>>();
-        indices.shuffle();
+        indices.shuffle(rng);
         indices.resize(amount, 0);
 
         VecChooseIter {
@@ -413,3 +759,60 @@ impl<T: Copy> ChooseRandom<T> for Vec<T> {
         }
     }
 }
+
+#[test]
+fn walking_the_robot() {
+    use crate::{generator::Maze, hex_blobby, kruskal};
+
+    let mut kruskal = kruskal::Exports::new(6, 4, Rng::new(1));
+    let maze = kruskal.run();
+    let Maze::Square(grid) = &maze else {
+        unreachable!()
+    };
+    let solution = maze.solution();
+    for pair in solution.windows(2) {
+        let (cell, next) = (pair[0], pair[1]);
+        let blocked = (EnumSet::all() - grid[cell.1][cell.0]).iter().next();
+        if let Some(blocked) = blocked {
+            assert!(!Algorithm::walk(&mut kruskal, Heading::Square(blocked)));
+        }
+        let direction = grid[cell.1][cell.0]
+            .iter()
+            .find(|direction| direction.offset(cell, grid) == Some(next))
+            .unwrap();
+        assert!(Algorithm::walk(&mut kruskal, Heading::Square(direction)));
+    }
+    assert_eq!(*kruskal.get_path_mut(), solution);
+    // Hex directions don't mean anything on a square board.
+    assert!(!Algorithm::walk(
+        &mut kruskal,
+        Heading::Hex(hex_util::Direction::East)
+    ));
+
+    // Walking back the way it came takes the last cell off the path.
+    let (&last, rest) = solution.split_last().unwrap();
+    let back = grid[last.1][last.0]
+        .iter()
+        .find(|direction| direction.offset(last, grid) == rest.last().copied())
+        .unwrap();
+    assert!(Algorithm::walk(&mut kruskal, Heading::Square(back)));
+    assert_eq!(kruskal.get_path_mut(), rest);
+
+    let mut hex = hex_blobby::Exports::new(12, 6, Rng::new(2));
+    let maze = hex.run();
+    let Maze::Hex(grid) = &maze else {
+        unreachable!()
+    };
+    let solution = maze.solution();
+    for pair in solution.windows(2) {
+        let ((x, y), next) = (pair[0], pair[1]);
+        let direction = grid[y][x]
+            .unwrap()
+            .iter()
+            .find(|direction| direction.next(x as i32, y as i32) == (next.0 as i32, next.1 as i32))
+            .unwrap();
+        assert!(!Algorithm::walk(&mut hex, Heading::Square(Direction::East)));
+        assert!(Algorithm::walk(&mut hex, Heading::Hex(direction)));
+    }
+    assert_eq!(*hex_util::Playable::get_path_mut(&mut hex), solution);
+}
@@ -1,14 +1,15 @@
-use crate::util::Args;
 use macroquad::experimental::collections::storage;
+use mazes::{
+    registry, solver,
+    util::{Args, Requests, Speed},
+};
 use sapp_jsutils::JsObject;
 
-struct Reset(bool);
-
 pub struct Web {}
 
 impl Web {
     pub fn new() -> Self {
-        storage::store(Reset(false));
+        storage::store(Requests::default());
         Self {}
     }
 }
@@ -17,6 +18,8 @@ extern "C" {
     fn get_search() -> JsObject;
     fn get_value(key: JsObject) -> JsObject;
     fn get_checked(key: JsObject) -> bool;
+    fn show_error(message: JsObject);
+    fn show_history(step: u32, furthest: u32);
 }
 
 #[allow(unused_mut)]
@@ -27,6 +30,15 @@ fn web_get_search() -> String {
     algorithm
 }
 
+fn web_get_param(key: &str) -> Option<String> {
+    web_get_search()
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value.to_owned())
+}
+
 fn web_get_value(key: &str) -> String {
     let mut value = String::new();
     let key = JsObject::string(&(key.to_owned() + " :checked"));
@@ -41,67 +53,146 @@ fn web_get_checked(key: &str) -> bool {
     rv
 }
 
+fn web_show_error(message: &str) {
+    unsafe { show_error(JsObject::string(message)) };
+}
+
 #[no_mangle]
 extern "C" fn send_reset() {
-    storage::store(Reset(true));
+    storage::get::<Requests>().reset();
+}
+
+#[no_mangle]
+extern "C" fn send_speed(steps: u32, interval: f32) {
+    storage::get::<Requests>().speed(Speed::new(steps as usize, interval));
+}
+
+#[no_mangle]
+extern "C" fn send_step() {
+    storage::get::<Requests>().step();
+}
+
+#[no_mangle]
+extern "C" fn send_finish() {
+    storage::get::<Requests>().finish();
+}
+
+#[no_mangle]
+extern "C" fn send_seek(step: u32) {
+    storage::get::<Requests>().seek(step as usize);
+}
+
+/// The algorithm named in the search, as in "?wilson&seed=1234", if there is one.
+fn web_get_algorithm() -> Option<String> {
+    let search = web_get_search();
+    let algorithm = search.trim_start_matches('?').split('&').next()?;
+    if algorithm.is_empty() || algorithm.contains('=') {
+        None
+    } else {
+        Some(algorithm.to_owned())
+    }
+}
+
+fn is_solver(solver: &str) -> bool {
+    solver::SOLVERS.contains(&solver)
 }
 
 impl Args for Web {
     fn get_algorithm(&self) -> String {
-        let mut algorithm = web_get_search();
-        if algorithm.is_empty() {
-            algorithm = "?parallel".to_string();
-        }
-        algorithm[1..].to_owned()
+        let entry = web_get_algorithm()
+            .and_then(|algorithm| registry::find(&algorithm))
+            .unwrap_or(&registry::ALGORITHMS[0]);
+        entry.id.to_owned()
     }
 
     fn get_variant(&self) -> String {
-        let algorithm = self.get_algorithm();
-        let variant = match algorithm.as_str() {
-            "parallel" => web_get_value("#parallel"),
-            "aldousbroder" => {
-                if web_get_checked("#aldousbroder") {
-                    "fast".to_owned()
-                } else {
-                    "slow".to_owned()
-                }
-            }
-            "wilson" => {
-                if web_get_checked("#wilson") {
-                    "slow".to_owned()
-                } else {
-                    "fast".to_owned()
-                }
-            }
-            "growingtree" => web_get_value("#growingtree"),
-            "bintree" => {
-                let random = if web_get_checked("#bintree-random") {
-                    "random"
-                } else {
-                    "ordered"
-                };
-                let element = web_get_value("#bintree-bias");
-                format!("{}:{}", random, element).to_owned()
-            }
-            "sidewinder" => {
-                if web_get_checked("#sidewinder") {
-                    "hard".to_owned()
-                } else {
-                    "easy".to_owned()
-                }
-            }
-            "originshift" => web_get_value("#originshift"),
-            "hexparallel" => web_get_value("#hexparallel"),
-            "penrose" => web_get_value("#penrose"),
-
-            _ => "unused".to_owned(),
+        let entry = registry::find(&self.get_algorithm()).unwrap();
+        // A variant in the address wins, otherwise each algorithm's variants are in a select
+        // named after it, except the binary tree's, which are split across two controls.
+        let variant = if let Some(variant) = web_get_param("variant") {
+            variant
+        } else if entry.params.is_empty() {
+            entry.default_variant.to_owned()
+        } else if entry.id == "bintree" {
+            let random = if web_get_checked("#bintree-random") {
+                "random"
+            } else {
+                "ordered"
+            };
+            let element = web_get_value("#bintree-bias");
+            format!("{}:{}", random, element)
+        } else {
+            web_get_value(&format!("#{}", entry.id))
         };
-        variant
+
+        // Show what went wrong on the page, and carry on with the defaults.
+        let mut errors = vec![];
+        if let Some(algorithm) = web_get_algorithm().filter(|algorithm| *algorithm != entry.id) {
+            errors.push(format!(
+                "There's no \"{}\" algorithm, so here's {} instead.",
+                algorithm, entry.name
+            ));
+        }
+        if let Some(solver) = web_get_param("solve").filter(|solver| !is_solver(solver)) {
+            errors.push(format!(
+                "There's no \"{}\" solver, so it won't be solved. Try one of: {}.",
+                solver,
+                solver::SOLVERS.join(", ")
+            ));
+        }
+        let values = entry.parse(&variant).unwrap_or_else(|error| {
+            errors.push(format!(
+                "Bad variant for {}: {}. Using \"{}\" instead.",
+                entry.name, error, entry.default_variant
+            ));
+            entry.defaults()
+        });
+        web_show_error(&errors.join(" "));
+        values.to_string()
+    }
+
+    fn get_size(&self) -> (Option<usize>, Option<usize>) {
+        (None, None)
+    }
+
+    fn get_seed(&self) -> Option<u64> {
+        web_get_param("seed").and_then(|seed| seed.parse().ok())
+    }
+
+    fn get_solver(&self) -> Option<String> {
+        // Anything else was shown as an error along with the variant.
+        web_get_param("solve").filter(|solver| is_solver(solver))
+    }
+
+    fn get_speed(&self) -> Speed {
+        let default = Speed::default();
+        let steps = web_get_param("steps").and_then(|steps| steps.parse().ok());
+        let interval = web_get_param("interval").and_then(|interval| interval.parse().ok());
+        Speed::new(
+            steps.unwrap_or(default.steps),
+            interval.unwrap_or(default.interval),
+        )
     }
 
     fn needs_reset(&self) -> bool {
-        let rv: bool = storage::get::<Reset>().0;
-        storage::store(Reset(false));
-        rv
+        storage::get::<Requests>().take_reset()
+    }
+    fn new_speed(&self) -> Option<Speed> {
+        storage::get::<Requests>().take_speed()
+    }
+
+    fn needs_step(&self) -> bool {
+        storage::get::<Requests>().take_step()
+    }
+
+    fn needs_finish(&self) -> bool {
+        storage::get::<Requests>().take_finish()
+    }
+    fn seek_to(&self) -> Option<usize> {
+        storage::get::<Requests>().take_seek()
+    }
+
+    fn show_history(&self, step: usize, furthest: usize) {
+        unsafe { show_history(step as u32, furthest as u32) };
     }
 }
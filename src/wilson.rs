@@ -1,13 +1,13 @@
+use crate::canvas::Canvas;
+use crate::generator::{Generator, Maze};
+use crate::params::Param;
+use crate::registry::{Entry, Shape};
 use crate::util::{
-    draw_board, draw_path, Algorithm, ChooseRandom, Direction, Grid, Playable, State as BaseState,
-    CELL_WIDTH, COLORS, COLUMNS, FIELD_COLOR, LINE_WIDTH, OFFSET, ROWS,
+    board_size, draw_board, draw_path, Algorithm, Board, ChooseRandom, Direction, Grid, Heading,
+    Playable, Rng, State as BaseState, CELL_WIDTH, COLORS, FIELD_COLOR, LINE_WIDTH, OFFSET,
 };
 use enumset::EnumSet;
-use macroquad::{
-    logging as log,
-    prelude::{draw_line, draw_rectangle, Color},
-    rand::gen_range,
-};
+use macroquad::{logging as log, prelude::Color};
 use maze_utils::From;
 
 const UNITS: f32 = CELL_WIDTH / 12.0;
@@ -33,17 +33,18 @@ pub struct Exports {
     current: Option<(usize, usize)>,
     grid: Grid,
     previous: Option<(usize, usize)>,
-    processing: [[Cell; COLUMNS as usize]; ROWS as usize],
+    processing: Board<Cell>,
     remaining: usize,
     slowdown: bool,
     start: Option<(usize, usize)>,
     state: State,
+    rng: Rng,
 }
 
 impl Exports {
-    pub fn new(variant: bool) -> Self {
-        let grid = [[EnumSet::new(); COLUMNS as usize]; ROWS as usize];
-        let processing = [[Cell::Out; COLUMNS as usize]; ROWS as usize];
+    pub fn new(variant: bool, width: usize, height: usize, rng: Rng) -> Self {
+        let grid = Board::new(width, height, EnumSet::new());
+        let processing = Board::new(width, height, Cell::Out);
 
         Self {
             path: vec![],
@@ -55,6 +56,7 @@ impl Exports {
             slowdown: variant,
             start: None,
             state: State::Setup,
+            rng,
         }
     }
     pub fn is_done(&self) -> bool {
@@ -62,8 +64,7 @@ impl Exports {
     }
     pub fn init_from_grid(&mut self, incoming: Grid) {
         self.state = State::Finding;
-        self.remaining = (ROWS * COLUMNS) as usize;
-        self.grid = incoming;
+        self.remaining = incoming.width() * incoming.height();
         for (y, row) in incoming.iter().enumerate() {
             for (x, &cell) in row.iter().enumerate() {
                 if cell == EnumSet::new() {
@@ -74,8 +75,16 @@ impl Exports {
                 }
             }
         }
+        self.grid = incoming;
     }
-    fn draw_arrow(&self, x: f32, y: f32, direction: Direction, color: Color) {
+    fn draw_arrow(
+        &self,
+        canvas: &mut dyn Canvas,
+        x: f32,
+        y: f32,
+        direction: Direction,
+        color: Color,
+    ) {
         let x = x * CELL_WIDTH + OFFSET;
         let y = y * CELL_WIDTH + OFFSET;
         let mut points = vec![];
@@ -110,12 +119,21 @@ impl Exports {
             }
         }
         for (first, second) in points.iter().zip(points.iter().skip(1)) {
-            draw_line(first.0, first.1, second.0, second.1, LINE_WIDTH, color);
+            canvas.draw_line(first.0, first.1, second.0, second.1, LINE_WIDTH, color);
         }
     }
 }
 
-impl Algorithm for Exports {
+pub const ENTRY: Entry = Entry {
+    id: "wilson",
+    name: "Wilson",
+    params: &[Param::Flag("fast", "slow")],
+    default_variant: "fast",
+    shape: Shape::Square,
+    new: |values, width, height, rng| Box::new(Exports::new(values.flag(0), width, height, rng)),
+};
+
+impl Generator for Exports {
     fn name(&self) -> String {
         if self.slowdown {
             String::from("Slower Wilsonish")
@@ -130,7 +148,10 @@ impl Algorithm for Exports {
             self.get_variant(),
             variant
         );
-        self.from(Exports::new(variant == "slow"));
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let rng = self.rng.fork();
+        let values = ENTRY.parse_or_default(&variant);
+        self.from(Exports::new(values.flag(0), width, height, rng));
         log::info!("  to {}/{}", self.slowdown, self.get_variant());
     }
     fn get_variant(&self) -> String {
@@ -141,11 +162,12 @@ impl Algorithm for Exports {
         }
     }
     fn update(&mut self) {
+        let (width, height) = (self.grid.width(), self.grid.height());
         if self.state == State::Setup {
-            let x = gen_range(0, COLUMNS as usize);
-            let y = gen_range(0, ROWS as usize);
+            let x = self.rng.gen_range(0, width);
+            let y = self.rng.gen_range(0, height);
             self.processing[y][x] = Cell::In;
-            self.remaining = (ROWS * COLUMNS - 1.0) as usize;
+            self.remaining = height * width - 1;
 
             self.state = State::Finding;
             return;
@@ -165,14 +187,14 @@ impl Algorithm for Exports {
                 // log::info!("Finding, start={:?}", self.start);
                 if self.start.is_none() {
                     let mut potentials = vec![];
-                    for x in 0..COLUMNS as usize {
-                        for y in 0..ROWS as usize {
+                    for x in 0..width {
+                        for y in 0..height {
                             if self.processing[y][x] == Cell::Out {
                                 potentials.push((x, y));
                             }
                         }
                     }
-                    self.start = potentials.choose();
+                    self.start = potentials.choose(&mut self.rng);
                     if self.start.is_none() {
                         panic!("Couldn't find a random element, but we think we need one!");
                     }
@@ -181,7 +203,7 @@ impl Algorithm for Exports {
                 let (x, y) = self.current.unwrap();
 
                 let mut potentials: Vec<Direction> = EnumSet::all().iter().collect();
-                potentials.shuffle();
+                potentials.shuffle(&mut self.rng);
                 for direction in potentials {
                     let (new_x, new_y) = match direction {
                         Direction::North => (x as i32, y as i32 - 1),
@@ -189,9 +211,10 @@ impl Algorithm for Exports {
                         Direction::South => (x as i32, y as i32 + 1),
                         Direction::West => (x as i32 - 1, y as i32),
                     };
-                    if 0 <= new_x && new_x < COLUMNS as i32 && 0 <= new_y && new_y < ROWS as i32 {
+                    if 0 <= new_x && new_x < width as i32 && 0 <= new_y && new_y < height as i32 {
                         // For some reason the checking-previous trick that sped up Aldous-Broder
-                        // seems to slow down Wilson… ¯\_(ツ)_/¯
+                        // seems to slow down Wilson… ¯\_(ツ)_/¯ It also makes some mazes more
+                        // likely than others, so it's not really Wilson any more.
                         let (new_x, new_y) = (new_x as usize, new_y as usize);
                         if self.slowdown && Some((new_x, new_y)) == self.previous {
                             continue;
@@ -230,8 +253,8 @@ impl Algorithm for Exports {
                     }
                     Cell::In => {
                         // We found it!
-                        for x in 0..COLUMNS as usize {
-                            for y in 0..ROWS as usize {
+                        for x in 0..width {
+                            for y in 0..height {
                                 if let Cell::Direction(_) = self.processing[y][x] {
                                     self.processing[y][x] = Cell::Out;
                                 }
@@ -257,8 +280,29 @@ impl Algorithm for Exports {
         }
     }
 
-    fn draw(&self) {
-        draw_board(self.grid);
+    fn get_state(&self) -> BaseState {
+        match &self.state {
+            State::Setup => BaseState::Setup,
+            State::Done => BaseState::Done,
+            _ => BaseState::Running,
+        }
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    fn get_maze(&self) -> Maze {
+        Maze::Square(self.grid.clone())
+    }
+}
+
+impl Algorithm for Exports {
+    fn get_size(&self) -> (f32, f32) {
+        board_size(self.grid.width(), self.grid.height())
+    }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        draw_board(canvas, &self.grid);
 
         let mut start_color = COLORS[1];
         start_color.a = 0.5;
@@ -266,7 +310,7 @@ impl Algorithm for Exports {
         let curr_color = COLORS[1];
 
         if let Some((x, y)) = self.current {
-            draw_rectangle(
+            canvas.draw_rectangle(
                 x as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 y as f32 * CELL_WIDTH + LINE_WIDTH + OFFSET,
                 CELL_WIDTH - LINE_WIDTH * 2.0,
@@ -276,7 +320,7 @@ impl Algorithm for Exports {
         }
 
         if let Some((x, y)) = self.start {
-            draw_rectangle(
+            canvas.draw_rectangle(
                 x as f32 * CELL_WIDTH + OFFSET,
                 y as f32 * CELL_WIDTH + OFFSET,
                 CELL_WIDTH,
@@ -285,8 +329,8 @@ impl Algorithm for Exports {
             );
         }
 
-        for x in 0..COLUMNS as usize {
-            for y in 0..ROWS as usize {
+        for x in 0..self.grid.width() {
+            for y in 0..self.grid.height() {
                 if Some((x, y)) == self.current {
                     continue;
                 }
@@ -295,7 +339,7 @@ impl Algorithm for Exports {
                         if Some((x, y)) == self.start {
                             continue;
                         }
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
@@ -304,39 +348,35 @@ impl Algorithm for Exports {
                         );
                     }
                     Cell::Direction(direction) => {
-                        draw_rectangle(
+                        canvas.draw_rectangle(
                             x as f32 * CELL_WIDTH + OFFSET,
                             y as f32 * CELL_WIDTH + OFFSET,
                             CELL_WIDTH,
                             CELL_WIDTH,
                             start_color,
                         );
-                        self.draw_arrow(x as f32, y as f32, direction, arrow_color);
+                        self.draw_arrow(canvas, x as f32, y as f32, direction, arrow_color);
                     }
                     _ => {}
                 }
             }
         }
 
-        draw_path(&self.path);
-    }
-
-    fn get_state(&self) -> BaseState {
-        match &self.state {
-            State::Setup => BaseState::Setup,
-            State::Done => BaseState::Done,
-            _ => BaseState::Running,
-        }
+        draw_path(canvas, &self.path);
     }
 
     fn move_to(&mut self, pos: (f32, f32)) {
         Playable::move_to(self, pos);
     }
+
+    fn walk(&mut self, heading: Heading) -> bool {
+        Playable::walk(self, heading)
+    }
 }
 
 impl Playable for Exports {
     fn get_grid(&self) -> Grid {
-        self.grid
+        self.grid.clone()
     }
 
     fn get_path_mut(&mut self) -> &mut Vec<(usize, usize)> {
@@ -0,0 +1,71 @@
+//! The web page has its own controls for picking each algorithm's variant, which start out on
+//! whatever's marked as selected in the HTML. Check them against the registry, so that the page
+//! and the command line start every algorithm the same way.
+
+use mazes::registry::ALGORITHMS;
+
+const PAGE: &str = include_str!("../static/index.html");
+
+/// The options in the select with the given id, as their values and whether they're selected.
+fn options(id: &str) -> Vec<(&'static str, bool)> {
+    let start = format!("<select id=\"{}\">", id);
+    let Some((_, rest)) = PAGE.split_once(&start) else {
+        panic!("There's no select for {}", id);
+    };
+    let (select, _) = rest.split_once("</select>").unwrap();
+    select
+        .split("<option value=\"")
+        .skip(1)
+        .map(|option| {
+            let (value, rest) = option.split_once('"').unwrap();
+            let (attributes, _) = rest.split_once('>').unwrap();
+            (value, attributes.contains("selected"))
+        })
+        .collect()
+}
+
+/// The one option in the select with the given id that's selected.
+fn selected(id: &str) -> &'static str {
+    let selected: Vec<_> = (options(id).into_iter())
+        .filter(|&(_, selected)| selected)
+        .map(|(value, _)| value)
+        .collect();
+    assert_eq!(selected.len(), 1, "{} should have one option selected", id);
+    selected[0]
+}
+
+#[test]
+fn every_algorithm_has_a_link() {
+    for entry in &ALGORITHMS {
+        let link = format!("<a href=\"?{}\">{}</a>", entry.id, entry.name);
+        assert!(PAGE.contains(&link), "There's no link for {}", entry.id);
+    }
+}
+
+#[test]
+fn controls_start_on_the_default_variant() {
+    for entry in ALGORITHMS.iter().filter(|entry| !entry.params.is_empty()) {
+        // The binary tree's variant is split across a checkbox and a select.
+        let variant = if entry.id == "bintree" {
+            let random = PAGE.contains("<input type=\"checkbox\" id=\"bintree-random\" checked>");
+            let order = if random { "random" } else { "ordered" };
+            format!("{}:{}", order, selected("bintree-bias"))
+        } else {
+            for (value, _) in options(entry.id) {
+                assert!(
+                    entry.parse(value).is_ok(),
+                    "{} can't be {}",
+                    entry.id,
+                    value
+                );
+            }
+            selected(entry.id).to_owned()
+        };
+        assert_eq!(
+            entry.parse(&variant).unwrap().to_string(),
+            entry.default_variant,
+            "{} starts out different on the page",
+            entry.id
+        );
+    }
+}
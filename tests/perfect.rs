@@ -0,0 +1,245 @@
+//! Every generator should build a perfect maze: every cell can get to every other cell, by exactly
+//! one route, without any passage leading off the board.
+
+use std::collections::VecDeque;
+
+use mazes::{
+    generator::{Generator, Maze, State},
+    graph::CellGraph,
+    houston,
+    registry::{Shape, ALGORITHMS},
+    util::{Board, Rng},
+};
+use proptest::prelude::*;
+
+type Passage = ((usize, usize), (usize, usize));
+
+/// Every passage, as a pair of cells, after checking that it's open from both ends.
+fn passages(maze: &Maze) -> Result<Vec<Passage>, String> {
+    let mut rv = vec![];
+    match maze {
+        Maze::Square(grid) => {
+            for (y, row) in grid.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    for direction in cell.iter() {
+                        let Some((i, j)) = direction.offset((x, y), grid) else {
+                            return Err(format!(
+                                "({}, {}) opens {:?} off the board",
+                                x, y, direction
+                            ));
+                        };
+                        if !grid[j][i].contains(direction.opposite()) {
+                            return Err(format!(
+                                "({}, {}) opens {:?}, but ({}, {}) doesn't open {:?}",
+                                x,
+                                y,
+                                direction,
+                                i,
+                                j,
+                                direction.opposite()
+                            ));
+                        }
+                        rv.push(((x, y), (i, j)));
+                    }
+                }
+            }
+        }
+        Maze::Hex(grid) => {
+            for (y, row) in grid.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    for direction in cell.unwrap_or_default().iter() {
+                        let (i, j) = direction.next(x as i32, y as i32);
+                        let next = usize::try_from(i).ok().zip(usize::try_from(j).ok());
+                        let Some(Some(other)) = next.and_then(|(i, j)| grid.get(j)?.get(i)) else {
+                            return Err(format!(
+                                "({}, {}) opens {:?} off the board",
+                                x, y, direction
+                            ));
+                        };
+                        let (i, j) = next.unwrap();
+                        if !other.contains(direction.opposite()) {
+                            return Err(format!(
+                                "({}, {}) opens {:?}, but ({}, {}) doesn't open {:?}",
+                                x,
+                                y,
+                                direction,
+                                i,
+                                j,
+                                direction.opposite()
+                            ));
+                        }
+                        rv.push(((x, y), (i, j)));
+                    }
+                }
+            }
+        }
+        Maze::Delta(grid) => {
+            for (y, row) in grid.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    for direction in cell.iter() {
+                        let Some((i, j)) = direction.offset((x, y), grid) else {
+                            return Err(format!(
+                                "({}, {}) opens {:?} off the board",
+                                x, y, direction
+                            ));
+                        };
+                        if !grid[j][i].contains(direction.opposite()) {
+                            return Err(format!(
+                                "({}, {}) opens {:?}, but ({}, {}) doesn't open {:?}",
+                                x,
+                                y,
+                                direction,
+                                i,
+                                j,
+                                direction.opposite()
+                            ));
+                        }
+                        rv.push(((x, y), (i, j)));
+                    }
+                }
+            }
+        }
+        Maze::Polar(grid) => {
+            for (y, row) in grid.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    for direction in cell.unwrap_or_default().iter() {
+                        let Some((i, j)) = direction.offset((x, y), grid) else {
+                            return Err(format!(
+                                "({}, {}) opens {:?} off the board",
+                                x, y, direction
+                            ));
+                        };
+                        // Which side leads back depends on how the rings split, so try them all.
+                        let back = (grid[j][i].unwrap_or_default().iter())
+                            .any(|other| other.offset((i, j), grid) == Some((x, y)));
+                        if !back {
+                            return Err(format!(
+                                "({}, {}) opens {:?}, but ({}, {}) doesn't open back",
+                                x, y, direction, i, j
+                            ));
+                        }
+                        rv.push(((x, y), (i, j)));
+                    }
+                }
+            }
+        }
+        Maze::Penrose(grid) => {
+            for cell in grid.cells() {
+                for next in grid.links(cell) {
+                    if !grid.is_linked(next, cell) {
+                        return Err(format!("{:?} opens into {:?}, but not back", cell, next));
+                    }
+                    rv.push((cell, next));
+                }
+            }
+        }
+    }
+    Ok(rv)
+}
+
+fn check_perfect(maze: &Maze) -> Result<(), String> {
+    let edges = passages(maze)?.len() / 2;
+    let (width, height) = maze.size();
+    let cells = match maze {
+        Maze::Hex(grid) => grid.iter().flatten().filter(|cell| cell.is_some()).count(),
+        Maze::Polar(grid) => grid.iter().flatten().filter(|cell| cell.is_some()).count(),
+        _ => width * height,
+    };
+
+    let start = maze.start().ok_or("There's no start")?;
+    let mut seen = Board::new(width, height, false);
+    seen[start.1][start.0] = true;
+    let mut reached = 1;
+    let mut queue = VecDeque::from([start]);
+    while let Some(cell) = queue.pop_front() {
+        for (x, y) in maze.neighbours(cell) {
+            if !seen[y][x] {
+                seen[y][x] = true;
+                reached += 1;
+                queue.push_back((x, y));
+            }
+        }
+    }
+    if reached != cells {
+        return Err(format!("Only {} of {} cells are connected", reached, cells));
+    }
+    if edges != cells - 1 {
+        return Err(format!(
+            "{} passages between {} cells means there's a loop",
+            edges, cells
+        ));
+    }
+    Ok(())
+}
+
+/// Build every variant of every algorithm of the given shape, and check them all. Sizes the
+/// registry turns away never get built, so they're left out.
+fn check_all(shape: Shape, width: usize, height: usize, seed: u64) {
+    for entry in ALGORITHMS.iter().filter(|entry| entry.shape == shape) {
+        if entry.check_size(width, height).is_err() {
+            continue;
+        }
+        for values in entry.variants() {
+            let maze = entry
+                .build(&values, (Some(width), Some(height)), Rng::new(seed))
+                .run();
+            if let Err(error) = check_perfect(&maze) {
+                panic!("{} {} at {}x{}: {}", entry.id, values, width, height, error);
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn square_mazes_are_perfect(seed: u64, width in 2..12usize, height in 2..12usize) {
+        check_all(Shape::Square, width, height, seed);
+    }
+
+    /// Hex boards narrower than they are tall would lose whole rows off their corners, so only
+    /// those get turned away.
+    #[test]
+    fn hex_mazes_are_perfect(seed: u64, width in 2..24usize, height in 2..12usize) {
+        for entry in ALGORITHMS.iter().filter(|entry| entry.shape == Shape::Hex) {
+            prop_assert_eq!(entry.check_size(width, height).is_ok(), width >= height);
+        }
+        check_all(Shape::Hex, width, height, seed);
+    }
+
+    /// A single column of triangles falls apart, since every other one has no way down.
+    #[test]
+    fn delta_mazes_are_perfect(seed: u64, width in 2..12usize, height in 2..12usize) {
+        check_all(Shape::Delta, width, height, seed);
+    }
+
+    /// Narrow rings come out with one or two cells each, which link up differently.
+    #[test]
+    fn polar_mazes_are_perfect(seed: u64, width in 1..40usize, height in 1..10usize) {
+        check_all(Shape::Polar, width, height, seed);
+    }
+
+    /// Every variant deflates down to pieces about the size of a square, whatever the board.
+    #[test]
+    fn penrose_mazes_are_perfect(seed: u64, width in 2..16usize, height in 2..12usize) {
+        check_all(Shape::Penrose, width, height, seed);
+    }
+
+    /// Houston hands Aldous-Broder's half-built maze over to Wilson, which has to keep building on
+    /// it instead of starting again.
+    #[test]
+    fn houston_keeps_its_passages(seed: u64, width in 2..12usize, height in 2..12usize) {
+        let mut houston = houston::Exports::new(width, height, Rng::new(seed));
+        let mut before = vec![];
+        while houston.get_state() != State::Done {
+            houston.update();
+            let after = passages(&houston.get_maze()).unwrap();
+            for passage in &before {
+                prop_assert!(after.contains(passage), "Lost the passage {:?}", passage);
+            }
+            before = after;
+        }
+        prop_assert_eq!(check_perfect(&houston.get_maze()), Ok(()));
+    }
+}
@@ -0,0 +1,184 @@
+//! Some generators promise to pick every possible maze with the same chance. A 3x3 board only has
+//! 192 of them, so build a few thousand mazes, count how often each one comes up, and check the
+//! counts with a chi-squared test.
+
+use std::collections::HashMap;
+
+use mazes::{
+    aldous_broder,
+    generator::Maze,
+    houston, parallel,
+    util::{Algorithm, Direction, Rng},
+    wilson,
+};
+
+const SIZE: usize = 3;
+/// How many times we'd expect to see each maze, if they're all equally likely.
+const EXPECTED: usize = 50;
+
+/// A maze as a bitmask of which edges between neighbouring cells are open, with the horizontal
+/// ones first and then the vertical ones.
+fn edges(maze: &Maze) -> u32 {
+    let Maze::Square(grid) = maze else {
+        panic!("Only square mazes have edges");
+    };
+    let mut rv = 0;
+    for y in 0..SIZE {
+        for x in 0..SIZE - 1 {
+            if grid[y][x].contains(Direction::East) {
+                rv |= 1 << (y * (SIZE - 1) + x);
+            }
+        }
+    }
+    for y in 0..SIZE - 1 {
+        for x in 0..SIZE {
+            if grid[y][x].contains(Direction::South) {
+                rv |= 1 << (SIZE * (SIZE - 1) + y * SIZE + x);
+            }
+        }
+    }
+    rv
+}
+
+/// Every spanning tree of the board, found by trying every set of `cells - 1` edges and keeping
+/// the ones that join everything up.
+fn spanning_trees() -> Vec<u32> {
+    let cells = SIZE * SIZE;
+    let count = 2 * SIZE * (SIZE - 1);
+    let ends = |edge: usize| {
+        if edge < SIZE * (SIZE - 1) {
+            let (y, x) = (edge / (SIZE - 1), edge % (SIZE - 1));
+            (y * SIZE + x, y * SIZE + x + 1)
+        } else {
+            let cell = edge - SIZE * (SIZE - 1);
+            (cell, cell + SIZE)
+        }
+    };
+    (0..1u32 << count)
+        .filter(|mask| mask.count_ones() as usize == cells - 1)
+        .filter(|&mask| {
+            // Union-find, without the ranks.
+            let mut parents = (0..cells).collect::<Vec<_>>();
+            fn root(parents: &mut [usize], mut cell: usize) -> usize {
+                while parents[cell] != cell {
+                    parents[cell] = parents[parents[cell]];
+                    cell = parents[cell];
+                }
+                cell
+            }
+            (0..count)
+                .filter(|edge| mask & (1 << edge) != 0)
+                .all(|edge| {
+                    let (a, b) = ends(edge);
+                    let (a, b) = (root(&mut parents, a), root(&mut parents, b));
+                    parents[a] = b;
+                    a != b
+                })
+        })
+        .collect()
+}
+
+/// Pearson's chi-squared statistic for the mazes `make` builds, against every tree being equally
+/// likely, along with the critical value it should stay under.
+fn chi_squared(make: impl Fn(Rng) -> Box<dyn Algorithm>) -> (f64, f64) {
+    let trees = spanning_trees();
+    assert_eq!(trees.len(), 192);
+    let mut counts = trees
+        .iter()
+        .map(|&tree| (tree, 0))
+        .collect::<HashMap<_, _>>();
+
+    let mut seeds = Rng::new(1234);
+    for _ in 0..trees.len() * EXPECTED {
+        let maze = make(seeds.fork()).run();
+        let Some(count) = counts.get_mut(&edges(&maze)) else {
+            panic!("{:?} isn't a spanning tree", maze);
+        };
+        *count += 1;
+    }
+
+    let expected = EXPECTED as f64;
+    let statistic = counts
+        .values()
+        .map(|&count| (count as f64 - expected).powi(2) / expected)
+        .sum();
+
+    // The Wilson-Hilferty approximation of the chi-squared distribution, at p = 0.001.
+    let k = (trees.len() - 1) as f64;
+    let z = 3.09;
+    let critical = k * (1.0 - 2.0 / (9.0 * k) + z * (2.0 / (9.0 * k)).sqrt()).powi(3);
+    (statistic, critical)
+}
+
+fn assert_uniform(name: &str, make: impl Fn(Rng) -> Box<dyn Algorithm>) {
+    let (statistic, critical) = chi_squared(make);
+    assert!(
+        statistic < critical,
+        "{} looks biased: chi-squared is {:.1}, over {:.1}",
+        name,
+        statistic,
+        critical
+    );
+}
+
+fn assert_biased(name: &str, make: impl Fn(Rng) -> Box<dyn Algorithm>) {
+    let (statistic, critical) = chi_squared(make);
+    assert!(
+        statistic > critical,
+        "{} looks uniform: chi-squared is {:.1}, under {:.1}",
+        name,
+        statistic,
+        critical
+    );
+}
+
+#[test]
+fn aldous_broder_is_uniform() {
+    assert_uniform("Aldous-Broder", |rng| {
+        Box::new(aldous_broder::Exports::new(false, SIZE, SIZE, rng))
+    });
+}
+
+#[test]
+fn wilson_is_uniform() {
+    assert_uniform("Wilson", |rng| {
+        Box::new(wilson::Exports::new(false, SIZE, SIZE, rng))
+    });
+}
+
+#[test]
+fn houston_is_uniform() {
+    assert_uniform("Houston", |rng| {
+        Box::new(houston::Exports::new(SIZE, SIZE, rng))
+    });
+}
+
+/// Not stepping straight back where the walk came from makes Aldous-Broder faster, but it's no
+/// longer a random walk, so some mazes come up more than others.
+#[test]
+fn fast_aldous_broder_is_biased() {
+    assert_biased("Fast Aldous-Broder", |rng| {
+        Box::new(aldous_broder::Exports::new(true, SIZE, SIZE, rng))
+    });
+}
+
+/// The same trick skews Wilson the same way.
+#[test]
+fn slow_wilson_is_biased() {
+    assert_biased("Slow Wilson", |rng| {
+        Box::new(wilson::Exports::new(true, SIZE, SIZE, rng))
+    });
+}
+
+/// Make sure the test can tell when something's off, with a generator that's famously biased.
+#[test]
+fn backtracking_is_biased() {
+    assert_biased("Backtracking", |rng| {
+        Box::new(parallel::Exports::new(
+            parallel::Seeds::new(1).unwrap(),
+            SIZE,
+            SIZE,
+            rng,
+        ))
+    });
+}